@@ -0,0 +1,588 @@
+//! Zoom animations rendered into numbered PNG sequences.
+//!
+//! An animation moves through a list of keyframes, each a complete `ParamDescription`.
+//! Between two keyframes, the radius is interpolated exponentially, so a zoom proceeds
+//! with constant speed, and the center moves towards the target proportionally to the
+//! radius, so the offset to the target shrinks steadily while zooming in. The progress
+//! through the keyframes is shaped by an `Easing` curve. Every frame is written as PNG
+//! with its parameters embedded.
+//!
+//! Each frame reuses the data of the previous one where its points hit old points exactly,
+//! see `CompStorage::resampled_clone()`. The rendering runs without GUI, the GUI's
+//! animation dialog calls `render_animation()` in a background thread.
+//!
+//! # Example
+//!
+//! ```bash
+//! mandel-rs animate --from MandelbrotFull --to MandelbrotSeahorseValley --frames 300 \
+//!     --easing EaseInOut --width 1920 --height 1080 -o frames
+//! ```
+
+use std::{
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+use bigdecimal::{BigDecimal, FromPrimitive};
+use clap::Args;
+use euclid::Point2D;
+
+use super::render::{
+    ImageSettings, Named, RenderOptions, compute, load_param_file, parse_named, write_image,
+};
+use crate::{
+    comp::{bd_math, math_area::MathArea},
+    storage::{
+        computation::comp_storage::CompStorage,
+        coord_spaces::MathSpace,
+        param_description::{ParamDescription, ParamDescriptionError},
+        param_presets::ParamPreset,
+    },
+};
+
+/// Radius ratio (as logarithm) below which a keyframe segment is regarded as pure pan
+const PAN_LN_RATIO: f64 = 1e-9;
+
+/// Curve mapping the linear time of an animation to its progress through the keyframes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    /// Constant speed from start to end
+    Linear,
+    /// Start slowly, end at full speed
+    EaseIn,
+    /// Start at full speed, end slowly
+    EaseOut,
+    /// Start and end slowly
+    EaseInOut,
+}
+
+impl Easing {
+    /// Returns all easing curves, e.g. for UI selection lists.
+    pub fn all() -> &'static [Self] {
+        &[Self::Linear, Self::EaseIn, Self::EaseOut, Self::EaseInOut]
+    }
+
+    /// Returns the human-readable name of the easing curve.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::EaseIn => "Ease in",
+            Self::EaseOut => "Ease out",
+            Self::EaseInOut => "Ease in and out",
+        }
+    }
+
+    /// Map the linear time `t` in `0..=1` to the progress in `0..=1`.
+    pub fn apply(&self, t: f64) -> f64 {
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+impl fmt::Display for Easing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Named for Easing {
+    fn all_values() -> &'static [Self] {
+        Self::all()
+    }
+}
+
+/// Errors which prevent an animation from being set up.
+#[derive(Debug)]
+pub enum AnimationError {
+    /// Less than two keyframes have been given
+    TooFewKeyframes(usize),
+    /// The frame count is zero
+    NoFrames,
+    /// A keyframe has invalid parameters
+    InvalidKeyframe(ParamDescriptionError),
+}
+
+impl fmt::Display for AnimationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooFewKeyframes(count) => {
+                write!(f, "an animation needs at least 2 keyframes, not {}", count)
+            }
+            Self::NoFrames => write!(f, "an animation needs at least one frame"),
+            Self::InvalidKeyframe(error) => write!(f, "invalid keyframe: {}", error),
+        }
+    }
+}
+
+impl Error for AnimationError {}
+
+impl From<ParamDescriptionError> for AnimationError {
+    fn from(error: ParamDescriptionError) -> Self {
+        Self::InvalidKeyframe(error)
+    }
+}
+
+/// Animation through a list of keyframes with a fixed number of frames.
+pub struct Animation {
+    /// Parameters of the keyframes, the first one also gives the coloring of all frames
+    keyframes: Vec<ParamDescription>,
+    /// Math areas of the keyframes
+    areas: Vec<MathArea>,
+    /// Natural logarithms of the keyframes' radii
+    ln_radii: Vec<f64>,
+    /// Number of frames, the first and last show the first and last keyframe
+    frame_count: u32,
+    /// Curve for the progress through the keyframes
+    easing: Easing,
+}
+
+impl Animation {
+    /// Creates an animation.
+    ///
+    /// # Arguments
+    ///
+    /// * `keyframes` - At least two keyframes, passed in equal time intervals
+    /// * `frame_count` - Number of frames to render, at least one
+    /// * `easing` - Curve for the progress through the keyframes
+    ///
+    /// # Returns
+    ///
+    /// The animation, or the error describing the unusable argument
+    pub fn new(
+        keyframes: Vec<ParamDescription>,
+        frame_count: u32,
+        easing: Easing,
+    ) -> Result<Self, AnimationError> {
+        if keyframes.len() < 2 {
+            return Err(AnimationError::TooFewKeyframes(keyframes.len()));
+        }
+        if frame_count == 0 {
+            return Err(AnimationError::NoFrames);
+        }
+        let areas = keyframes
+            .iter()
+            .map(|keyframe| keyframe.validate().and_then(|_| keyframe.math_area()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let ln_radii = areas
+            .iter()
+            .map(|area| bd_math::ln(area.radius()))
+            .collect();
+        Ok(Animation {
+            keyframes,
+            areas,
+            ln_radii,
+            frame_count,
+            easing,
+        })
+    }
+
+    /// Returns the number of frames.
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    /// Return the keyframe segment and the progress within it for a frame.
+    fn segment_of(&self, frame: u32) -> (usize, f64) {
+        let t = if self.frame_count > 1 {
+            frame.min(self.frame_count - 1) as f64 / (self.frame_count - 1) as f64
+        } else {
+            0.0
+        };
+        let position = self.easing.apply(t) * (self.keyframes.len() - 1) as f64;
+        let segment = (position.floor() as usize).min(self.keyframes.len() - 2);
+        (segment, position - segment as f64)
+    }
+
+    /// Return the math area shown in a frame.
+    ///
+    /// The radius is interpolated exponentially between the keyframes. The center moves
+    /// proportionally to the radius towards the keyframe with the smaller radius, so the
+    /// distance to this keyframe's center shrinks monotonically and reaches zero with it.
+    /// Measured in screen units, i.e. relative to the radius, the offset shrinks as well,
+    /// so the target drifts steadily towards the center of the image while zooming.
+    /// Segments between keyframes of equal radius are panned linearly.
+    pub fn frame_area(&self, frame: u32) -> MathArea {
+        let (segment, u) = self.segment_of(frame);
+        let (ln_r0, ln_r1) = (self.ln_radii[segment], self.ln_radii[segment + 1]);
+        let ln_radius = ln_r0 + (ln_r1 - ln_r0) * u;
+        let (a, b) = (&self.areas[segment], &self.areas[segment + 1]);
+        let center = if (ln_r1 - ln_r0).abs() < PAN_LN_RATIO {
+            interpolate(a.center(), b.center(), u)
+        } else {
+            // Anchor is the smaller area, the weight is (r - r_anchor) / (r_other - r_anchor),
+            // computed relative to r_other to stay precise for large zoom factors
+            let (anchor, other, ln_anchor, ln_other) = if ln_r1 < ln_r0 {
+                (b, a, ln_r1, ln_r0)
+            } else {
+                (a, b, ln_r0, ln_r1)
+            };
+            let q_anchor = (ln_anchor - ln_other).exp();
+            let weight = ((ln_radius - ln_other).exp() - q_anchor) / (1.0 - q_anchor);
+            interpolate(anchor.center(), other.center(), weight)
+        };
+        MathArea::new(center, bd_math::exp(ln_radius), a.ratio().clone())
+    }
+
+    /// Return the parameters of a frame.
+    ///
+    /// The area is taken from `frame_area()`, the maximum iteration count is interpolated
+    /// linearly between the keyframes, all other parameters are those of the first keyframe.
+    pub fn frame_description(&self, frame: u32) -> ParamDescription {
+        let (segment, u) = self.segment_of(frame);
+        let (it0, it1) = (
+            self.keyframes[segment].max_iteration as f64,
+            self.keyframes[segment + 1].max_iteration as f64,
+        );
+        let area = self.frame_area(frame);
+        ParamDescription {
+            name: format!("{} frame {}", self.keyframes[0].name, frame + 1),
+            center_x: area.center().x.to_string(),
+            center_y: area.center().y.to_string(),
+            radius: area.radius().to_string(),
+            ratio: area.ratio().to_string(),
+            max_iteration: (it0 + (it1 - it0) * u).round().max(1.0) as u32,
+            ..self.keyframes[0].clone()
+        }
+    }
+}
+
+/// Return `a + (b - a) * weight` for mathematical points.
+fn interpolate(
+    a: &Point2D<BigDecimal, MathSpace>,
+    b: &Point2D<BigDecimal, MathSpace>,
+    weight: f64,
+) -> Point2D<BigDecimal, MathSpace> {
+    let weight = BigDecimal::from_f64(weight).unwrap_or_default();
+    Point2D::new(
+        &a.x + (&b.x - &a.x) * &weight,
+        &a.y + (&b.y - &a.y) * &weight,
+    )
+}
+
+/// Progress report of `render_animation()`, sent after each written frame
+#[derive(Debug, Clone)]
+pub struct AnimationProgress {
+    /// Number of frames written so far
+    pub frames_done: u32,
+    /// Total number of frames of the animation
+    pub frame_count: u32,
+    /// Path of the frame written last
+    pub path: PathBuf,
+}
+
+/// Return the path of a frame, numbered from 1 with leading zeros for correct sorting.
+pub fn frame_path(directory: &Path, prefix: &str, frame: u32, frame_count: u32) -> PathBuf {
+    let digits = frame_count.to_string().len().max(4);
+    directory.join(format!("{}_{:0digits$}.png", prefix, frame + 1))
+}
+
+/// Render all frames of an animation into numbered PNG files.
+///
+/// # Arguments
+///
+/// * `animation` - The animation to render
+/// * `settings` - Image settings used for all frames
+/// * `directory` - Existing directory to write the frames into
+/// * `prefix` - File name prefix of the frames, see `frame_path()`
+/// * `stop_flag` - Cancels the rendering, also within the computation of a frame
+/// * `progress` - Called after each written frame
+///
+/// # Returns
+///
+/// The number of completely written frames, or the error which prevented writing a frame
+pub fn render_animation(
+    animation: &Animation,
+    settings: &ImageSettings,
+    directory: &Path,
+    prefix: &str,
+    stop_flag: &AtomicBool,
+    mut progress: impl FnMut(AnimationProgress),
+) -> Result<u32, Box<dyn Error>> {
+    compute_frames(animation, settings, stop_flag, |frame, storage, descr| {
+        let path = frame_path(directory, prefix, frame, animation.frame_count());
+        write_image(storage, descr, settings, &path)?;
+        progress(AnimationProgress {
+            frames_done: frame + 1,
+            frame_count: animation.frame_count(),
+            path,
+        });
+        Ok(())
+    })
+}
+
+/// Compute all frames of an animation, each starting with the data of the previous one.
+///
+/// # Arguments
+///
+/// * `frame_done` - Called with the number, storage and parameters of each computed frame
+///
+/// # Returns
+///
+/// The number of frames passed to `frame_done`, or the first error
+fn compute_frames(
+    animation: &Animation,
+    settings: &ImageSettings,
+    stop_flag: &AtomicBool,
+    mut frame_done: impl FnMut(u32, &CompStorage, &ParamDescription) -> Result<(), Box<dyn Error>>,
+) -> Result<u32, Box<dyn Error>> {
+    let mut previous: Option<Arc<CompStorage>> = None;
+    for frame in 0..animation.frame_count() {
+        if stop_flag.load(Ordering::Relaxed) {
+            return Ok(frame);
+        }
+        let descr = animation.frame_description(frame);
+        let storage = match compute(
+            settings.comp_properties(&descr)?,
+            settings.strategy,
+            previous.as_deref(),
+            stop_flag,
+        ) {
+            Err(_) if stop_flag.load(Ordering::Relaxed) => return Ok(frame),
+            result => result?,
+        };
+        frame_done(frame, &storage, &descr)?;
+        previous = Some(storage);
+    }
+    Ok(animation.frame_count())
+}
+
+/// Arguments of the `animate` subcommand
+#[derive(Args, Debug)]
+pub struct AnimateArgs {
+    /// First keyframe: preset name, parameter file (.toml) or PNG image written by mandel.rs
+    #[arg(long)]
+    pub from: String,
+    /// Intermediate keyframe like --from, may be given several times
+    #[arg(long)]
+    pub via: Vec<String>,
+    /// Last keyframe like --from
+    #[arg(long)]
+    pub to: String,
+    /// Number of frames
+    #[arg(long, default_value_t = 100)]
+    pub frames: u32,
+    /// Easing curve, e.g. EaseInOut
+    #[arg(long, value_parser = parse_named::<Easing>, default_value = "Linear")]
+    pub easing: Easing,
+    #[command(flatten)]
+    pub options: RenderOptions,
+    /// Directory to write the frames into, created if missing
+    #[arg(long, short = 'o')]
+    pub output_dir: PathBuf,
+    /// File name prefix of the frames
+    #[arg(long, default_value = "frame")]
+    pub prefix: String,
+}
+
+/// Load a keyframe given as preset name or parameter file and apply the overrides.
+fn keyframe(source: &str, options: &RenderOptions) -> Result<ParamDescription, Box<dyn Error>> {
+    let mut descr = match parse_named::<ParamPreset>(source) {
+        Ok(preset) => preset.preset(),
+        Err(_) => load_param_file(Path::new(source))
+            .map_err(|error| format!("cannot load keyframe {}: {}", source, error))?,
+    };
+    options.apply_overrides(&mut descr)?;
+    Ok(descr)
+}
+
+/// Perform the `animate` subcommand.
+///
+/// # Returns
+///
+/// `Ok(())` once all frames have been written, otherwise the error which prevented it
+pub fn run(args: &AnimateArgs) -> Result<(), Box<dyn Error>> {
+    args.options.check()?;
+    let keyframes = std::iter::once(&args.from)
+        .chain(&args.via)
+        .chain(std::iter::once(&args.to))
+        .map(|source| keyframe(source, &args.options))
+        .collect::<Result<Vec<_>, _>>()?;
+    let settings = args.options.image_settings();
+    let animation = Animation::new(keyframes, args.frames, args.easing)?;
+    std::fs::create_dir_all(&args.output_dir)?;
+
+    let start = Instant::now();
+    render_animation(
+        &animation,
+        &settings,
+        &args.output_dir,
+        &args.prefix,
+        &AtomicBool::new(false),
+        |progress| {
+            println!(
+                "Frame {}/{}: {}",
+                progress.frames_done,
+                progress.frame_count,
+                progress.path.display()
+            )
+        },
+    )?;
+    println!(
+        "Rendered {} frames into {} in {:.1} s",
+        animation.frame_count(),
+        args.output_dir.display(),
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::mandelbrot_engine::ComputationStrategy,
+        storage::{data_point::DataPoint, image_comp_properties::Supersampling},
+    };
+    use bigdecimal::ToPrimitive;
+    use euclid::Size2D;
+
+    fn zoom(frames: u32, easing: Easing) -> Animation {
+        let start = ParamPreset::MandelbrotFull.preset();
+        let end = ParamDescription {
+            center_x: "-0.75".to_string(),
+            center_y: "0.1".to_string(),
+            radius: "0.0001".to_string(),
+            max_iteration: 1000,
+            ..start.clone()
+        };
+        Animation::new(vec![start, end], frames, easing).unwrap()
+    }
+
+    #[test]
+    fn radius_is_interpolated_exponentially() {
+        let animation = zoom(5, Easing::Linear);
+        let radii: Vec<f64> = (0..5)
+            .map(|frame| animation.frame_area(frame).radius().to_f64().unwrap())
+            .collect();
+        for pair in radii.windows(3) {
+            // Radii are rounded to the relevant precision of the math area
+            assert!((pair[1] / pair[0] - pair[2] / pair[1]).abs() < 1e-6);
+        }
+        let last = animation.frame_description(4);
+        assert_eq!(
+            ("-0.75", "0.1"),
+            (last.center_x.as_str(), last.center_y.as_str())
+        );
+        assert_eq!(1000, last.max_iteration);
+        assert_eq!(
+            ParamPreset::MandelbrotFull.preset().max_iteration,
+            animation.frame_description(0).max_iteration
+        );
+    }
+
+    #[test]
+    fn zoom_target_offset_shrinks() {
+        let animation = zoom(10, Easing::EaseInOut);
+        let start = animation.frame_area(0);
+        let target_x = -0.75;
+        let start_offset =
+            (target_x - start.center().x.to_f64().unwrap()) / start.radius().to_f64().unwrap();
+        for frame in 1..9 {
+            let area = animation.frame_area(frame);
+            let offset =
+                (target_x - area.center().x.to_f64().unwrap()) / area.radius().to_f64().unwrap();
+            assert!(offset.abs() <= start_offset.abs() + 1e-9);
+        }
+    }
+
+    #[test]
+    fn too_few_keyframes_are_rejected() {
+        assert!(matches!(
+            Animation::new(
+                vec![ParamPreset::MandelbrotFull.preset()],
+                10,
+                Easing::Linear
+            ),
+            Err(AnimationError::TooFewKeyframes(1))
+        ));
+    }
+
+    #[test]
+    fn frame_paths_sort_by_frame_number() {
+        assert_eq!(
+            PathBuf::from("out/frame_0007.png"),
+            frame_path(Path::new("out"), "frame", 6, 120)
+        );
+        assert_eq!(
+            PathBuf::from("out/zoom_00001.png"),
+            frame_path(Path::new("out"), "zoom", 0, 12000)
+        );
+    }
+
+    #[test]
+    fn frames_reuse_the_previous_frame() {
+        let still = ParamPreset::MandelbrotFull.preset();
+        let animation = Animation::new(vec![still.clone(), still], 3, Easing::Linear).unwrap();
+        let settings = ImageSettings {
+            size: Size2D::new(60, 40),
+            strategy: ComputationStrategy::Shuffled,
+            supersampling: Supersampling::Off,
+        };
+        let marker = DataPoint::computed(12345, Point2D::zero(), 12345.0, None);
+        let mut marked = Vec::new();
+        let frames = compute_frames(
+            &animation,
+            &settings,
+            &AtomicBool::new(false),
+            |frame, storage, _| {
+                if frame == 0 {
+                    // Computed points are taken over, so the marker survives in all frames
+                    storage.stage.set(0, 0, marker);
+                }
+                marked.push(storage.stage.get(0, 0).unwrap().iteration_count == 12345);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(3, frames);
+        assert_eq!(vec![true, true, true], marked);
+    }
+
+    #[test]
+    fn stop_flag_cancels_a_running_frame() {
+        let deep = ParamDescription {
+            max_iteration: 1_000_000,
+            ..ParamPreset::MandelbrotFull.preset()
+        };
+        let animation = Animation::new(vec![deep.clone(), deep], 2, Easing::Linear).unwrap();
+        let settings = ImageSettings {
+            size: Size2D::new(200, 150),
+            strategy: ComputationStrategy::Shuffled,
+            supersampling: Supersampling::Off,
+        };
+        let directory =
+            std::env::temp_dir().join(format!("mandel-rs-animation-test-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let stop_flag = AtomicBool::new(false);
+        let frames = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                stop_flag.store(true, Ordering::Relaxed);
+            });
+            render_animation(
+                &animation,
+                &settings,
+                &directory,
+                "frame",
+                &stop_flag,
+                |_| panic!("no frame can be finished"),
+            )
+        })
+        .unwrap();
+        assert_eq!(0, frames);
+        assert!(!frame_path(&directory, "frame", 0, 2).exists());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
+
+// end of file
@@ -0,0 +1,8 @@
+//! Command-line operations of mandel.rs which run without any GUI
+//!
+//! Each file herein implements one subcommand: its clap arguments and the function
+//! performing it. They are meant for scripted runs, e.g. on build servers without a display.
+pub mod animation;
+pub mod render;
+
+// end of file
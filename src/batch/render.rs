@@ -0,0 +1,623 @@
+//! Headless rendering of a single image into a PNG file.
+//!
+//! The `render` subcommand takes the parameters from a parameter file, a PNG image
+//! written by mandel.rs or a built-in preset, applies the overrides given on the command
+//! line, runs the `MandelbrotEngine` until the stage is completed and writes the result
+//! as PNG with the effective parameters embedded.
+//!
+//! The steps are available separately as `compute()` and `write_image()`, so that
+//! other renderers like the animation export can produce images the same way.
+//!
+//! # Tiled Rendering
+//!
+//! With `--tile-size`, the image is computed tile by tile, see `render_tiled()`.
+//! Finished tiles are moved into a spill directory and the PNG file is written
+//! one row of tiles after the other, so the image size is not limited by the memory.
+//!
+//! # Example
+//!
+//! ```bash
+//! mandel-rs render --preset MandelbrotSeahorseValley --width 3840 --height 2160 \
+//!     --max-iteration 5000 --color-preset Moonlight -o seahorse.png
+//! mandel-rs render --preset MandelbrotFull --width 32768 --height 32768 \
+//!     --tile-size 1024 --spill-dir /var/tmp -o poster.png
+//! ```
+
+use std::{
+    error::Error,
+    fmt::Debug,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::{Arc, atomic::AtomicBool},
+    time::Instant,
+};
+
+use clap::Args;
+use euclid::{Point2D, Rect, Size2D};
+
+use crate::{
+    comp::{
+        mandelbrot_engine::{ComputationStrategy, MandelbrotEngine},
+        math_area::RasteredMathArea,
+    },
+    storage::{
+        computation::{comp_storage::CompStorage, tiled_stage::TiledStage},
+        coord_spaces::StageSpace,
+        image_comp_properties::{ImageCompProperties, StageProperties, StageState, Supersampling},
+        param_description::{ParamDescription, ParamDescriptionError},
+        param_presets::ParamPreset,
+        png_file::{read_param_description_file, write_png_file, write_png_in_bands},
+        visualization::{
+            coloring::{
+                base::GradientColors,
+                interpolation::{HueDirection, InterpolationSpace},
+                presets::{GradientColorPreset, IterationAssignment},
+            },
+            iteration_histogram::IterationHistogram,
+            renderer::{GuessPolicy, Renderer},
+            viz_stage::VizStage,
+        },
+    },
+};
+
+/// Arguments of the `render` subcommand
+#[derive(Args, Debug)]
+#[command(group = clap::ArgGroup::new("source").required(true).args(["params", "preset"]))]
+pub struct RenderArgs {
+    /// Parameter file (.toml) or PNG image written by mandel.rs to render
+    #[arg(long, short = 'p')]
+    pub params: Option<PathBuf>,
+    /// Built-in preset to render, e.g. MandelbrotFull
+    #[arg(long, value_parser = parse_named::<ParamPreset>)]
+    pub preset: Option<ParamPreset>,
+    #[command(flatten)]
+    pub options: RenderOptions,
+    /// Compute and write the image in square tiles of this edge length in pixels,
+    /// for images larger than the memory
+    #[arg(long)]
+    pub tile_size: Option<u32>,
+    /// Directory for the finished tiles of a tiled render, default is the temporary directory
+    #[arg(long, requires = "tile_size")]
+    pub spill_dir: Option<PathBuf>,
+    /// Path of the PNG file to write
+    #[arg(long, short = 'o')]
+    pub output: PathBuf,
+}
+
+/// Image and coloring options shared by all rendering subcommands
+#[derive(Args, Debug)]
+pub struct RenderOptions {
+    /// Width of the image in pixels
+    #[arg(long, default_value_t = 800)]
+    pub width: u32,
+    /// Height of the image in pixels
+    #[arg(long, default_value_t = 600)]
+    pub height: u32,
+    /// Maximum iteration count, overrides the parameters
+    #[arg(long)]
+    pub max_iteration: Option<u32>,
+    /// Escape radius of the iteration, at least 2, overrides the parameters
+    #[arg(long)]
+    pub bailout: Option<f64>,
+    /// Computation strategy, e.g. MarianiSilver
+    #[arg(long, value_parser = parse_named::<ComputationStrategy>, default_value = "Shuffled")]
+    pub strategy: ComputationStrategy,
+    /// Gradient color preset, e.g. Sunrise, overrides the parameters
+    #[arg(long, value_parser = parse_named::<GradientColorPreset>)]
+    pub color_preset: Option<GradientColorPreset>,
+    /// Iteration assignment function, e.g. Logarithmic, overrides the parameters
+    #[arg(long, value_parser = parse_named::<IterationAssignment>)]
+    pub assignment: Option<IterationAssignment>,
+    /// Color space of the gradient interpolation, e.g. Oklch, overrides the parameters
+    #[arg(long, value_parser = parse_named::<InterpolationSpace>)]
+    pub color_space: Option<InterpolationSpace>,
+    /// Hue direction in cylindrical color spaces, e.g. Longer, overrides the parameters
+    #[arg(long, value_parser = parse_named::<HueDirection>)]
+    pub hue_direction: Option<HueDirection>,
+    /// Number of gradient stripes, overrides the parameters
+    #[arg(long)]
+    pub stripes: Option<u32>,
+    /// Offset into the gradient stripes, overrides the parameters
+    #[arg(long)]
+    pub offset: Option<u32>,
+    /// Interpolate between gradient stripes using the smooth iteration count, true if given
+    /// without value, overrides the parameters
+    #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+    pub smooth: Option<bool>,
+    /// Anti-aliasing by supersampling, e.g. Grid3x3 or Jittered3x3
+    #[arg(long, value_parser = parse_named::<Supersampling>, default_value = "Off")]
+    pub supersampling: Supersampling,
+}
+
+impl RenderOptions {
+    /// Check the options which clap cannot check by itself.
+    pub fn check(&self) -> Result<(), Box<dyn Error>> {
+        if self.width == 0 || self.height == 0 {
+            return Err("width and height must be positive".into());
+        }
+        if self.bailout.is_some_and(|bailout| bailout < 2.0) {
+            return Err("bailout must be at least 2".into());
+        }
+        Ok(())
+    }
+
+    /// Apply the overrides of the options to the parameters and validate the result.
+    pub fn apply_overrides(
+        &self,
+        descr: &mut ParamDescription,
+    ) -> Result<(), ParamDescriptionError> {
+        if let Some(max_iteration) = self.max_iteration {
+            descr.max_iteration = max_iteration;
+        }
+        if let Some(color_preset) = self.color_preset {
+            // An explicitly selected preset replaces a custom scheme of the parameters
+            descr.color_preset = color_preset;
+            descr.custom_scheme = None;
+        }
+        if let Some(color_space) = self.color_space {
+            descr.color_interpolation.space = color_space;
+        }
+        if let Some(hue_direction) = self.hue_direction {
+            descr.color_interpolation.hue_direction = hue_direction;
+        }
+        if let Some(assignment) = self.assignment {
+            descr.iteration_assignment = assignment;
+        }
+        if let Some(stripes) = self.stripes {
+            descr.stripe_count = stripes;
+        }
+        if let Some(offset) = self.offset {
+            descr.stripe_offset = offset;
+        }
+        if let Some(bailout) = self.bailout {
+            descr.bailout = bailout;
+        }
+        if let Some(smooth) = self.smooth {
+            descr.smooth_coloring = smooth;
+        }
+        descr.validate()
+    }
+
+    /// Return the image settings selected by the options.
+    pub fn image_settings(&self) -> ImageSettings {
+        ImageSettings {
+            size: Size2D::new(self.width, self.height),
+            strategy: self.strategy,
+            supersampling: self.supersampling,
+        }
+    }
+}
+
+/// Settings of a rendered image which are not part of its parameter description
+///
+/// Bailout, smooth coloring and lighting are taken from the parameter description,
+/// so the parameters embedded in the written image always match what has been rendered.
+#[derive(Debug, Clone)]
+pub struct ImageSettings {
+    /// Size of the image in pixels
+    pub size: Size2D<u32, StageSpace>,
+    /// Order in which the engine computes the points
+    pub strategy: ComputationStrategy,
+    /// Anti-aliasing by supersampling
+    pub supersampling: Supersampling,
+}
+
+impl ImageSettings {
+    /// Return the computation properties for the parameters in these settings.
+    pub fn comp_properties(
+        &self,
+        descr: &ParamDescription,
+    ) -> Result<ImageCompProperties, ParamDescriptionError> {
+        Ok(ImageCompProperties::new(
+            StageProperties::new(RasteredMathArea::new(descr.math_area()?, self.size)),
+            descr.max_iteration,
+            descr.fractal_type,
+            descr.bailout,
+            self.supersampling,
+        ))
+    }
+
+    /// Create the renderer for the parameters in these settings.
+    pub fn renderer(&self, descr: &ParamDescription) -> Renderer {
+        Renderer::new(
+            GradientColors::new(
+                &descr.color_scheme(),
+                descr.stripe_count as usize,
+                descr.stripe_offset as usize,
+            ),
+            descr.iteration_assignment,
+            descr.smooth_coloring,
+            descr.lighting,
+            GuessPolicy::Neutral,
+            descr.max_iteration,
+            self.supersampling,
+        )
+    }
+}
+
+/// Types which can be selected by their variant name on the command line
+pub(super) trait Named: Copy + Debug + 'static {
+    /// All selectable values
+    fn all_values() -> &'static [Self];
+}
+
+impl Named for ParamPreset {
+    fn all_values() -> &'static [Self] {
+        Self::all()
+    }
+}
+
+impl Named for ComputationStrategy {
+    fn all_values() -> &'static [Self] {
+        Self::all()
+    }
+}
+
+impl Named for GradientColorPreset {
+    fn all_values() -> &'static [Self] {
+        Self::all()
+    }
+}
+
+impl Named for InterpolationSpace {
+    fn all_values() -> &'static [Self] {
+        Self::all()
+    }
+}
+
+impl Named for HueDirection {
+    fn all_values() -> &'static [Self] {
+        Self::all()
+    }
+}
+
+impl Named for IterationAssignment {
+    fn all_values() -> &'static [Self] {
+        Self::all()
+    }
+}
+
+impl Named for Supersampling {
+    fn all_values() -> &'static [Self] {
+        Self::all()
+    }
+}
+
+/// Parse a value by its variant name, ignoring case.
+///
+/// The error message lists all valid names so that the user does not need to look them up.
+pub(super) fn parse_named<T: Named>(value: &str) -> Result<T, String> {
+    T::all_values()
+        .iter()
+        .find(|candidate| format!("{:?}", candidate).eq_ignore_ascii_case(value))
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<String> = T::all_values()
+                .iter()
+                .map(|candidate| format!("{:?}", candidate))
+                .collect();
+            format!(
+                "unknown name \"{}\", use one of: {}",
+                value,
+                names.join(", ")
+            )
+        })
+}
+
+/// Load a parameter file, PNG images are recognized by their extension.
+pub fn load_param_file(path: &Path) -> Result<ParamDescription, ParamDescriptionError> {
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+    {
+        read_param_description_file(path)
+    } else {
+        ParamDescription::load(path)
+    }
+}
+
+/// Load the parameters selected by the arguments and apply the overrides.
+fn param_description(args: &RenderArgs) -> Result<ParamDescription, Box<dyn Error>> {
+    let mut descr = match (&args.params, args.preset) {
+        (Some(path), _) => load_param_file(path)?,
+        (None, Some(preset)) => preset.preset(),
+        (None, None) => return Err("either a parameter file or a preset is needed".into()),
+    };
+    args.options.apply_overrides(&mut descr)?;
+    Ok(descr)
+}
+
+/// Compute the stage of an image completely.
+///
+/// # Arguments
+///
+/// * `properties` - Computation properties of the image
+/// * `strategy` - Computation strategy of the engine
+/// * `previous` - Storage of a similar image whose data is reused where possible,
+///   see `CompStorage::resampled_clone()`
+/// * `stop_flag` - Cancels the computation once set
+///
+/// # Returns
+///
+/// The storage with the completed stage, or an error if the computation ended unfinished
+pub fn compute(
+    properties: ImageCompProperties,
+    strategy: ComputationStrategy,
+    previous: Option<&CompStorage>,
+    stop_flag: &AtomicBool,
+) -> Result<Arc<CompStorage>, Box<dyn Error>> {
+    let storage = Arc::new(match previous {
+        Some(previous) => previous.resampled_clone(properties),
+        None => CompStorage::new(properties),
+    });
+    let engine = MandelbrotEngine::new(&storage, strategy);
+    engine.start();
+    engine.wait_or_stop(stop_flag);
+    let state = storage.stage.get_state();
+    if state != StageState::Completed {
+        return Err(format!("computation ended unfinished in state {:?}", state).into());
+    }
+    Ok(storage)
+}
+
+/// Render a computed stage and write it as PNG with the parameters embedded.
+///
+/// # Returns
+///
+/// Width and height of the written image in pixels
+pub fn write_image(
+    storage: &CompStorage,
+    descr: &ParamDescription,
+    settings: &ImageSettings,
+    path: &Path,
+) -> Result<(usize, usize), Box<dyn Error>> {
+    let stage = VizStage::new(&storage.stage, descr.max_iteration);
+    let renderer = settings.renderer(descr);
+    let (width, height) = renderer.output_size(&stage);
+    let rgba = renderer.render(&stage);
+    write_png_file(path, width as u32, height as u32, &rgba, descr)?;
+    Ok((width, height))
+}
+
+/// Compute an image tile by tile and write it as PNG with the parameters embedded.
+///
+/// Each tile is computed as an image of its own, whose math area is the tile's part
+/// of the image's area, see `RasteredMathArea::sub_area()`. Tiles are computed as squares
+/// and cropped, with adaptive supersampling including a margin of one pixel, so edges at the
+/// tile borders are refined as in the image computed at once. Finished tiles are moved into
+/// the spill directory while the iteration histogram of the whole image is collected.
+/// Afterwards, the tiles are rendered one row after the other and streamed into the
+/// PNG file. So only one tile is computed and one row of tiles is rendered at any time.
+///
+/// # Arguments
+///
+/// * `descr` - Parameters of the image
+/// * `settings` - Image settings, the size is that of the complete image
+/// * `tile_size` - Edge length of the tiles in (rendered) pixels
+/// * `spill_base` - Directory in which the directory for the finished tiles is created
+/// * `path` - Path of the PNG file to write
+///
+/// # Returns
+///
+/// Width and height of the written image in pixels
+pub fn render_tiled(
+    descr: &ParamDescription,
+    settings: &ImageSettings,
+    tile_size: u32,
+    spill_base: &Path,
+    path: &Path,
+) -> Result<(usize, usize), Box<dyn Error>> {
+    let properties = settings.comp_properties(descr)?.rectified();
+    let area = &properties.stage_properties.orig_area;
+    let factor = settings.supersampling.factor();
+    // The tiled stage holds the supersampled data, rendered pixels are factor times smaller
+    let tiles = TiledStage::new(settings.size * factor, tile_size * factor, Some(spill_base))?;
+    let tile_count = tiles.tile_count();
+    let output_rect = |tx: u32, ty: u32| {
+        let rect = tiles.tile_rect(tx, ty);
+        Rect::new(rect.origin / factor, rect.size / factor)
+    };
+
+    // Tiles are computed as squares, whose math area has an exact ratio, so that their
+    // pixels have the same coordinates as in the image. Adaptive refinement compares each
+    // pixel with its neighbours, so these squares include the adjacent pixels of the
+    // neighbouring tiles. The surplus pixels are cropped before the tile is stored.
+    let margin = match settings.supersampling.refinement_factor() {
+        Some(_) => 1,
+        None => 0,
+    };
+    let computed_rect = |rect: Rect<u32, StageSpace>| {
+        let origin = Point2D::new(
+            rect.min_x().saturating_sub(margin),
+            rect.min_y().saturating_sub(margin),
+        );
+        let side = (rect.max_x() + margin - origin.x).max(rect.max_y() + margin - origin.y);
+        Rect::new(origin, Size2D::new(side, side))
+    };
+
+    let mut histogram = IterationHistogram::new(descr.max_iteration);
+    for ty in 0..tile_count.height {
+        for tx in 0..tile_count.width {
+            let rect = output_rect(tx, ty);
+            let computed = computed_rect(rect);
+            let tile_properties = ImageCompProperties::new(
+                StageProperties::new(area.sub_area(computed)),
+                properties.max_iteration,
+                properties.fractal_type,
+                properties.bailout,
+                properties.supersampling,
+            );
+            let storage = compute(
+                tile_properties,
+                settings.strategy,
+                None,
+                &AtomicBool::new(false),
+            )?;
+            let stage = Arc::into_inner(storage)
+                .ok_or("tile storage is still in use")?
+                .into_stage()
+                .cropped_clone(Rect::new(
+                    ((rect.origin - computed.origin) * factor).to_point(),
+                    rect.size * factor,
+                ));
+            stage
+                .get_full_data()
+                .iter()
+                .flatten()
+                .for_each(|point| histogram.add(point.iteration_count));
+            tiles.insert_tile(tx, ty, stage);
+            tiles.spill_tile(tx, ty)?;
+        }
+    }
+
+    // Shared by the visualization stages of all tiles instead of copying it per tile
+    let histogram = Arc::new(histogram);
+    let renderer = settings.renderer(descr);
+    let (width, height) = (settings.size.width as usize, settings.size.height as usize);
+    let mut ty = 0;
+    let band = || -> std::io::Result<Vec<u8>> {
+        let band_height = output_rect(0, ty).size.height as usize;
+        let mut band = vec![0u8; width * band_height * 4];
+        for tx in 0..tile_count.width {
+            let stage = tiles
+                .tile(tx, ty)
+                .map_err(std::io::Error::other)?
+                .ok_or_else(|| std::io::Error::other("tile has not been computed"))?;
+            let rgba = renderer.render(&VizStage::with_histogram(&stage, Arc::clone(&histogram)));
+            let rect = output_rect(tx, ty);
+            let tile_row = rect.size.width as usize * 4;
+            for (y, row) in rgba.chunks(tile_row).enumerate() {
+                let start = (y * width + rect.origin.x as usize) * 4;
+                band[start..start + tile_row].copy_from_slice(row);
+            }
+        }
+        ty += 1;
+        Ok(band)
+    };
+    write_png_in_bands(
+        BufWriter::new(File::create(path)?),
+        width as u32,
+        height as u32,
+        band,
+        descr,
+    )?;
+    Ok((width, height))
+}
+
+/// Perform the `render` subcommand.
+///
+/// # Returns
+///
+/// `Ok(())` once the PNG file has been written, otherwise the error which prevented it
+pub fn run(args: &RenderArgs) -> Result<(), Box<dyn Error>> {
+    args.options.check()?;
+    let descr = param_description(args)?;
+    let settings = args.options.image_settings();
+
+    let start = Instant::now();
+    let (width, height) = match args.tile_size {
+        Some(0) => return Err("tile size must be positive".into()),
+        Some(tile_size) => {
+            let spill_base = args.spill_dir.clone().unwrap_or_else(std::env::temp_dir);
+            render_tiled(&descr, &settings, tile_size, &spill_base, &args.output)?
+        }
+        None => {
+            let storage = compute(
+                settings.comp_properties(&descr)?,
+                settings.strategy,
+                None,
+                &AtomicBool::new(false),
+            )?;
+            write_image(&storage, &descr, &settings, &args.output)?
+        }
+    };
+    println!(
+        "Rendered \"{}\" ({}×{}) into {} in {:.1} s",
+        descr.name,
+        width,
+        height,
+        args.output.display(),
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(path: &Path) -> Vec<u8> {
+        let mut reader = png::Decoder::new(std::io::BufReader::new(File::open(path).unwrap()))
+            .read_info()
+            .unwrap();
+        let mut rgba = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut rgba).unwrap();
+        rgba
+    }
+
+    #[test]
+    fn smooth_option_overrides_the_parameters() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            options: RenderOptions,
+        }
+        let smoothed = |args: &[&str]| {
+            let cli = Cli::try_parse_from(std::iter::once("mandel-rs").chain(args.iter().copied()))
+                .unwrap();
+            let mut descr = ParamDescription {
+                smooth_coloring: true,
+                ..ParamPreset::MandelbrotFull.preset()
+            };
+            cli.options.apply_overrides(&mut descr).unwrap();
+            descr.smooth_coloring
+        };
+        assert!(smoothed(&[]));
+        assert!(!smoothed(&["--smooth", "false"]));
+        assert!(smoothed(&["--smooth"]));
+        assert!(smoothed(&["--smooth=true"]));
+    }
+
+    #[test]
+    fn tiled_render_equals_render_at_once() {
+        let dir =
+            std::env::temp_dir().join(format!("mandel-rs-render-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let descr = ParamDescription {
+            smooth_coloring: true,
+            ..ParamPreset::MandelbrotFull.preset()
+        };
+        for supersampling in [Supersampling::Grid2x2, Supersampling::Adaptive3x3] {
+            let settings = ImageSettings {
+                size: Size2D::new(150, 100),
+                strategy: ComputationStrategy::Shuffled,
+                supersampling,
+            };
+            let (at_once, tiled) = (dir.join("at_once.png"), dir.join("tiled.png"));
+            let storage = compute(
+                settings.comp_properties(&descr).unwrap(),
+                settings.strategy,
+                None,
+                &AtomicBool::new(false),
+            )
+            .unwrap();
+            write_image(&storage, &descr, &settings, &at_once).unwrap();
+            assert_eq!(
+                (150, 100),
+                render_tiled(&descr, &settings, 64, &dir, &tiled).unwrap()
+            );
+            let (expected, actual) = (decode(&at_once), decode(&tiled));
+            assert!(
+                expected == actual,
+                "tiled image differs for {}",
+                supersampling
+            );
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+// end of file
@@ -1,4 +1,6 @@
-use bigdecimal::BigDecimal;
+use std::f64::consts::LN_10;
+
+use bigdecimal::{BigDecimal, ToPrimitive, num_bigint::BigInt};
 
 /// Module to contain functions performing mathematical operations on BigDecimals
 
@@ -13,6 +15,28 @@ pub fn magnitude(n: &BigDecimal) -> i64 {
         .unwrap()
 }
 
+/// Return the natural logarithm of a positive BigDecimal as f64
+///
+/// Other than `n.to_f64().ln()`, this also works for numbers beyond the f64 exponent range,
+/// e.g. radii of very deep zooms.
+pub fn ln(n: &BigDecimal) -> f64 {
+    let (digits, scale) = n.with_prec(17).as_bigint_and_exponent();
+    digits.to_f64().unwrap().ln() - scale as f64 * LN_10
+}
+
+/// Return `e` to the power of `ln` as BigDecimal, the inverse of `ln()`
+///
+/// The result has about 15 significant digits, also beyond the f64 exponent range.
+pub fn exp(ln: f64) -> BigDecimal {
+    let log10 = ln / LN_10;
+    let exponent = log10.floor();
+    let mantissa = 10f64.powf(log10 - exponent);
+    BigDecimal::new(
+        BigInt::from((mantissa * 1e15).round() as i64),
+        15 - exponent as i64,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -27,4 +51,15 @@ mod tests {
         assert_eq!(-2, magnitude(&BigDecimal::from_str("0.01").unwrap()));
         assert_eq!(-3, magnitude(&BigDecimal::from_str("0.00785637").unwrap()));
     }
+
+    #[test]
+    fn test_ln_exp() {
+        assert!((ln(&BigDecimal::from_str("2.5").unwrap()) - 2.5f64.ln()).abs() < 1e-12);
+        let tiny = BigDecimal::from_str("3.25e-400").unwrap();
+        let ln_tiny = ln(&tiny);
+        assert!((ln_tiny - (3.25f64.ln() - 400.0 * LN_10)).abs() < 1e-9);
+        let restored = exp(ln_tiny);
+        assert_eq!(-400, magnitude(&restored));
+        assert!(((restored / tiny).to_f64().unwrap() - 1.0).abs() < 1e-9);
+    }
 }
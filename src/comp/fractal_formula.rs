@@ -0,0 +1,429 @@
+//! Iteration formulas of the supported fractal types.
+//!
+//! Each fractal type is described by a `FractalFormula` which computes the
+//! `DataPoint` of a single point of the complex plane. The engine only knows
+//! about this trait and dispatches all point computations through it.
+//!
+//! # Supported Formulas
+//!
+//! - **Mandelbrot**: `z(n+1) = z(n)² + c`, `z(0) = 0`, `c` is the point
+//! - **Julia**: `z(n+1) = z(n)² + c`, `z(0)` is the point, `c` is a constant
+//! - **Burning Ship**: `z(n+1) = (|Re z(n)| + i·|Im z(n)|)² + c`
+//! - **Tricorn**: `z(n+1) = conj(z(n))² + c`
+//! - **Multibrot**: `z(n+1) = z(n)^d + c` for integer or real exponents `d`
+//!
+//! All formulas take the escape radius ("bailout") as parameter. Mathematically,
+//! 2.0 is sufficient, but larger radii give a smoother fractional iteration count.
+//!
+//! # Smooth Iteration Count
+//!
+//! Besides the integer iteration count, each formula computes the normalized
+//! fractional iteration count `μ = n + 1 - ln(ln|z|) / ln(d)` for exponent `d`,
+//! see `smooth_iteration()`. It is continuous across the iteration bands.
+//!
+//! # Distance Estimation
+//!
+//! Formulas which are analytic in `z` also track the derivative `dz/dc` (or `dz/dz(0)`
+//! for Julia sets) along the orbit. From it, `distance_and_normal()` computes the
+//! distance of an escaped point to the fractal and the direction of the surface normal
+//! used for 3D lighting. Burning Ship and Tricorn are not analytic, their points carry
+//! neither distance estimate nor normal.
+
+use euclid::{Point2D, Vector2D};
+
+use crate::storage::{coord_spaces::MathSpace, data_point::DataPoint};
+
+/// Compute the normalized fractional ("smooth") iteration count of an iteration result.
+///
+/// # Arguments
+///
+/// * `iteration` - Integer iteration count of the point
+/// * `x` - Real part of the final iteration value
+/// * `y` - Imaginary part of the final iteration value
+/// * `max_iteration` - Maximum number of iterations, points reaching it did not escape
+/// * `exponent` - Exponent `d` of the iteration formula
+///
+/// # Returns
+///
+/// `μ = n + 1 - ln(ln|z|) / ln(d)` for escaped points, the integer count for all others
+pub fn smooth_iteration(iteration: u32, x: f64, y: f64, max_iteration: u32, exponent: f64) -> f32 {
+    let norm = x * x + y * y;
+    if iteration >= max_iteration || norm <= 1.0 || !norm.is_finite() {
+        return iteration as f32;
+    }
+    let log_z = 0.5 * norm.ln();
+    (iteration as f64 + 1.0 - log_z.ln() / exponent.ln()).max(0.0) as f32
+}
+
+/// Compute the exterior distance estimate and the surface normal of an iteration result.
+///
+/// # Arguments
+///
+/// * `iteration` - Integer iteration count of the point
+/// * `max_iteration` - Maximum number of iterations, points reaching it did not escape
+/// * `x`, `y` - Final iteration value `z`
+/// * `dx`, `dy` - Derivative `dz/dc` of the final iteration value
+///
+/// # Returns
+///
+/// For escaped points the distance estimate `2·|z|·ln|z| / |dz|` in mathematical units
+/// and the unit surface normal `(z / dz) / |z / dz|`, `None` otherwise.
+/// The real distance lies between a quarter of the estimate and the estimate itself.
+pub fn distance_and_normal(
+    iteration: u32,
+    max_iteration: u32,
+    x: f64,
+    y: f64,
+    dx: f64,
+    dy: f64,
+) -> Option<(f64, Vector2D<f32, MathSpace>)> {
+    let z = x.hypot(y);
+    let dz = dx.hypot(dy);
+    if iteration >= max_iteration || z <= 1.0 || dz == 0.0 || !(z * dz).is_finite() {
+        None
+    } else {
+        // z / dz = z · conj(dz) / |dz|², only the direction is needed
+        let normal = Vector2D::new(x * dx + y * dy, y * dx - x * dy) / (z * dz);
+        Some((2.0 * z * z.ln() / dz, normal.cast()))
+    }
+}
+
+/// Iteration formula of a fractal, computing the data of single points.
+///
+/// Implementations must be thread-safe as the engine computes points in parallel.
+pub trait FractalFormula: Send + Sync {
+    /// Compute the data point for the point `(x, y)` of the complex plane.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Real component of the point
+    /// * `y` - Imaginary component of the point
+    /// * `max_iteration` - Maximum number of iterations to perform
+    /// * `bailout` - Escape radius, the iteration stops once `|z| >= bailout`
+    ///
+    /// # Returns
+    ///
+    /// `DataPoint` with the escape iteration count (or `max_iteration`),
+    /// the final value of the iteration, the smooth iteration count,
+    /// and the distance estimate if supported by the formula.
+    fn data_point_at(&self, x: f64, y: f64, max_iteration: u32, bailout: f64) -> DataPoint;
+}
+
+/// The classic Mandelbrot set: `z(n+1) = z(n)² + c`, `z(0) = 0`
+pub struct MandelbrotFormula;
+
+impl FractalFormula for MandelbrotFormula {
+    /// Computes Mandelbrot iteration data for a single complex point.
+    ///
+    /// This implementation uses an optimized straight algorithm, see
+    ///
+    /// https://en.wikipedia.org/wiki/Plotting_algorithms_for_the_Mandelbrot_set
+    ///
+    /// It needs only three multiplications per iteration for `z` and
+    /// four more for the derivative `dz(n+1) = 2·z(n)·dz(n) + 1`.
+    fn data_point_at(
+        &self,
+        c_real: f64,
+        c_imag: f64,
+        max_iteration: u32,
+        bailout: f64,
+    ) -> DataPoint {
+        let bailout_sqr = bailout * bailout;
+        let mut x = 0.0;
+        let mut y = 0.0;
+        let mut x2 = 0.0;
+        let mut y2 = 0.0;
+        let mut w = 0.0;
+        let mut dx = 0.0;
+        let mut dy = 0.0;
+        let mut iteration = 0;
+        while x2 + y2 < bailout_sqr && iteration < max_iteration {
+            (dx, dy) = (2.0 * (x * dx - y * dy) + 1.0, 2.0 * (x * dy + y * dx));
+            x = x2 - y2 + c_real;
+            y = w - x2 - y2 + c_imag;
+            x2 = x * x;
+            y2 = y * y;
+            w = (x + y) * (x + y);
+            iteration += 1;
+        }
+        let smooth = smooth_iteration(iteration, x, y, max_iteration, 2.0);
+        let distance = distance_and_normal(iteration, max_iteration, x, y, dx, dy);
+        DataPoint::computed(iteration, Point2D::new(x, y), smooth, distance)
+    }
+}
+
+/// Julia set of constant `c`: `z(n+1) = z(n)² + c`, `z(0)` is the point
+pub struct JuliaFormula {
+    /// Real part of the constant
+    pub c_real: f64,
+    /// Imaginary part of the constant
+    pub c_imag: f64,
+}
+
+impl FractalFormula for JuliaFormula {
+    /// The derivative is taken with respect to the start value: `dz(n+1) = 2·z(n)·dz(n)`, `dz(0) = 1`.
+    fn data_point_at(&self, x: f64, y: f64, max_iteration: u32, bailout: f64) -> DataPoint {
+        let bailout_sqr = bailout * bailout;
+        let mut x = x;
+        let mut y = y;
+        let mut x2 = x * x;
+        let mut y2 = y * y;
+        let mut dx = 1.0;
+        let mut dy = 0.0;
+        let mut iteration = 0;
+        while x2 + y2 < bailout_sqr && iteration < max_iteration {
+            (dx, dy) = (2.0 * (x * dx - y * dy), 2.0 * (x * dy + y * dx));
+            y = 2.0 * x * y + self.c_imag;
+            x = x2 - y2 + self.c_real;
+            x2 = x * x;
+            y2 = y * y;
+            iteration += 1;
+        }
+        let smooth = smooth_iteration(iteration, x, y, max_iteration, 2.0);
+        let distance = distance_and_normal(iteration, max_iteration, x, y, dx, dy);
+        DataPoint::computed(iteration, Point2D::new(x, y), smooth, distance)
+    }
+}
+
+/// Burning Ship fractal: `z(n+1) = (|Re z(n)| + i·|Im z(n)|)² + c`
+pub struct BurningShipFormula;
+
+impl FractalFormula for BurningShipFormula {
+    fn data_point_at(
+        &self,
+        c_real: f64,
+        c_imag: f64,
+        max_iteration: u32,
+        bailout: f64,
+    ) -> DataPoint {
+        let bailout_sqr = bailout * bailout;
+        let mut x: f64 = 0.0;
+        let mut y: f64 = 0.0;
+        let mut x2 = 0.0;
+        let mut y2 = 0.0;
+        let mut iteration = 0;
+        while x2 + y2 < bailout_sqr && iteration < max_iteration {
+            y = 2.0 * (x * y).abs() + c_imag;
+            x = x2 - y2 + c_real;
+            x2 = x * x;
+            y2 = y * y;
+            iteration += 1;
+        }
+        let smooth = smooth_iteration(iteration, x, y, max_iteration, 2.0);
+        DataPoint::computed(iteration, Point2D::new(x, y), smooth, None)
+    }
+}
+
+/// Tricorn (Mandelbar) fractal: `z(n+1) = conj(z(n))² + c`
+pub struct TricornFormula;
+
+impl FractalFormula for TricornFormula {
+    fn data_point_at(
+        &self,
+        c_real: f64,
+        c_imag: f64,
+        max_iteration: u32,
+        bailout: f64,
+    ) -> DataPoint {
+        let bailout_sqr = bailout * bailout;
+        let mut x = 0.0;
+        let mut y = 0.0;
+        let mut x2 = 0.0;
+        let mut y2 = 0.0;
+        let mut iteration = 0;
+        while x2 + y2 < bailout_sqr && iteration < max_iteration {
+            y = -2.0 * x * y + c_imag;
+            x = x2 - y2 + c_real;
+            x2 = x * x;
+            y2 = y * y;
+            iteration += 1;
+        }
+        let smooth = smooth_iteration(iteration, x, y, max_iteration, 2.0);
+        DataPoint::computed(iteration, Point2D::new(x, y), smooth, None)
+    }
+}
+
+/// Multibrot set with integer exponent: `z(n+1) = z(n)^d + c`
+///
+/// The power is computed by repeated complex multiplication which is
+/// faster and more precise than the polar form used for real exponents.
+pub struct IntegerMultibrotFormula {
+    /// The exponent `d`, at least 2
+    pub exponent: u32,
+}
+
+impl FractalFormula for IntegerMultibrotFormula {
+    /// The derivative is `dz(n+1) = d·z(n)^(d-1)·dz(n) + 1`.
+    fn data_point_at(
+        &self,
+        c_real: f64,
+        c_imag: f64,
+        max_iteration: u32,
+        bailout: f64,
+    ) -> DataPoint {
+        let bailout_sqr = bailout * bailout;
+        let d = self.exponent as f64;
+        let mut x = 0.0;
+        let mut y = 0.0;
+        let mut dx = 0.0;
+        let mut dy = 0.0;
+        let mut iteration = 0;
+        while x * x + y * y < bailout_sqr && iteration < max_iteration {
+            // z^(d-1)
+            let (mut px, mut py) = (x, y);
+            for _ in 2..self.exponent {
+                (px, py) = (px * x - py * y, px * y + py * x);
+            }
+            (dx, dy) = (d * (px * dx - py * dy) + 1.0, d * (px * dy + py * dx));
+            (x, y) = (px * x - py * y + c_real, px * y + py * x + c_imag);
+            iteration += 1;
+        }
+        let smooth = smooth_iteration(iteration, x, y, max_iteration, d);
+        let distance = distance_and_normal(iteration, max_iteration, x, y, dx, dy);
+        DataPoint::computed(iteration, Point2D::new(x, y), smooth, distance)
+    }
+}
+
+/// Multibrot set with real exponent: `z(n+1) = z(n)^d + c`
+///
+/// The power is computed in polar form: `z^d = |z|^d · (cos(d·φ) + i·sin(d·φ))`.
+pub struct RealMultibrotFormula {
+    /// The exponent `d`
+    pub exponent: f64,
+}
+
+impl FractalFormula for RealMultibrotFormula {
+    /// The derivative is `dz(n+1) = d·z(n)^(d-1)·dz(n) + 1`, `z^(d-1)` is computed in polar form, too.
+    fn data_point_at(
+        &self,
+        c_real: f64,
+        c_imag: f64,
+        max_iteration: u32,
+        bailout: f64,
+    ) -> DataPoint {
+        let bailout_sqr = bailout * bailout;
+        let d = self.exponent;
+        let mut x: f64 = 0.0;
+        let mut y: f64 = 0.0;
+        let mut dx = 0.0;
+        let mut dy = 0.0;
+        let mut iteration = 0;
+        while x * x + y * y < bailout_sqr && iteration < max_iteration {
+            // z^(d-1)
+            let r = (x * x + y * y).powf((d - 1.0) / 2.0);
+            let phi = y.atan2(x) * (d - 1.0);
+            let (px, py) = (r * phi.cos(), r * phi.sin());
+            (dx, dy) = (d * (px * dx - py * dy) + 1.0, d * (px * dy + py * dx));
+            (x, y) = (px * x - py * y + c_real, px * y + py * x + c_imag);
+            iteration += 1;
+        }
+        let smooth = smooth_iteration(iteration, x, y, max_iteration, d);
+        let distance = distance_and_normal(iteration, max_iteration, x, y, dx, dy);
+        DataPoint::computed(iteration, Point2D::new(x, y), smooth, distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::data_point::DataQuality;
+
+    #[test]
+    fn multibrot_two_is_mandelbrot() {
+        let points = [
+            (-0.75, 0.1),
+            (0.3, 0.5),
+            (-1.2, 0.2),
+            (0.0, 0.0),
+            (-0.1, 0.9),
+        ];
+        for (x, y) in points {
+            let expected = MandelbrotFormula
+                .data_point_at(x, y, 300, 2.0)
+                .iteration_count;
+            let integer = IntegerMultibrotFormula { exponent: 2 };
+            let real = RealMultibrotFormula { exponent: 2.0 };
+            assert_eq!(
+                expected,
+                integer.data_point_at(x, y, 300, 2.0).iteration_count
+            );
+            assert!(expected.abs_diff(real.data_point_at(x, y, 300, 2.0).iteration_count) <= 1);
+        }
+    }
+
+    #[test]
+    fn julia_of_own_constant_is_mandelbrot() {
+        // Starting the Julia iteration at z(0)=c equals the Mandelbrot iteration
+        // of c after its first step, so the Julia set needs one iteration less.
+        for (c_real, c_imag) in [(0.5, 0.5), (-1.5, 0.5), (0.26, 0.0)] {
+            let julia = JuliaFormula { c_real, c_imag };
+            let mandelbrot = MandelbrotFormula.data_point_at(c_real, c_imag, 500, 2.0);
+            assert!(mandelbrot.iteration_count < 500);
+            assert_eq!(
+                mandelbrot.iteration_count,
+                julia
+                    .data_point_at(c_real, c_imag, 500, 2.0)
+                    .iteration_count
+                    + 1
+            );
+        }
+    }
+
+    #[test]
+    fn smooth_iteration_is_continuous() {
+        // Along a ray leaving the set, μ must not jump by whole bands like the integer count
+        let mut last = MandelbrotFormula
+            .data_point_at(-2.5, 0.0, 100, 256.0)
+            .smooth_iteration;
+        for step in 1..=160 {
+            let x = -2.5 + step as f64 * 0.0025;
+            let point = MandelbrotFormula.data_point_at(x, 0.0, 100, 256.0);
+            assert!((point.smooth_iteration - last).abs() < 0.5, "jump at {x}");
+            last = point.smooth_iteration;
+        }
+    }
+
+    #[test]
+    fn distance_estimate_bounds_real_distance() {
+        // The set lies within the disk of radius 2, its leftmost point is -2
+        for c_real in [-2.25, -2.5, -3.0] {
+            let point = MandelbrotFormula.data_point_at(c_real, 0.0, 1000, 1e6);
+            assert_eq!(DataQuality::Computed, point.distance_estimate_quality);
+            let distance = -2.0 - c_real;
+            assert!(point.distance_estimate >= distance);
+            assert!(point.distance_estimate <= 4.0 * distance);
+        }
+        let inner = MandelbrotFormula.data_point_at(-0.1, 0.1, 1000, 1e6);
+        assert_eq!(DataQuality::Unknown, inner.distance_estimate_quality);
+    }
+
+    #[test]
+    fn tricorn_and_burning_ship_escape() {
+        assert_eq!(
+            1,
+            TricornFormula
+                .data_point_at(3.0, 0.0, 100, 2.0)
+                .iteration_count
+        );
+        assert_eq!(
+            100,
+            TricornFormula
+                .data_point_at(0.0, 0.0, 100, 2.0)
+                .iteration_count
+        );
+        assert_eq!(
+            1,
+            BurningShipFormula
+                .data_point_at(0.0, 3.0, 100, 2.0)
+                .iteration_count
+        );
+        assert_eq!(
+            100,
+            BurningShipFormula
+                .data_point_at(-0.5, 0.0, 100, 2.0)
+                .iteration_count
+        );
+    }
+}
+
+// end of file
@@ -9,28 +9,108 @@
 //! - `MandelbrotEngine`: Thread-safe computation controller
 //! - Atomic state management for concurrent access
 //! - Interruptible computation with graceful stopping
-//! - Multiple computation strategies (shuffled vs linear)
+//! - Multiple computation strategies (shuffled, Mariani-Silver, boundary tracing, linear),
+//!   selectable at runtime
 //!
 //! # Algorithm
 //!
-//! Uses the classic Mandelbrot iteration: `z(n+1) = z(n)² + c`
-//! - Escape radius: 2.0 (squared: 4.0)
+//! The iteration formula is taken from the fractal type of the storage's properties,
+//! see `comp::fractal_formula`. The classic case is the Mandelbrot iteration `z(n+1) = z(n)² + c`.
+//! - Configurable escape radius ("bailout"), larger than 2.0 for smooth coloring
 //! - Configurable maximum iteration count
 //! - Returns both iteration count and final z-value for enhanced coloring
+//!
+//! # Deep Zooms
+//!
+//! Plain `f64` coordinates stop resolving neighbouring pixels at radii around 1e-13.
+//! Below `PERTURBATION_RADIUS_MAGNITUDE`, the engine switches to perturbation
+//! theory (see `comp::perturbation`): One reference orbit is computed with
+//! `BigDecimal` precision, all pixels iterate only their `f64` difference to it.
+//!
+//! # Adaptive Anti-Aliasing
+//!
+//! With `Supersampling::Adaptive3x3`, all strategies finish with a refinement pass
+//! (see `refine_edges()`): Pixels whose iteration count differs strongly from a neighbour
+//! get additional sub-pixel samples. Strongest edges are refined first and each sample
+//! is sent as event, so the visualization sharpens progressively. The stage is only
+//! reported `Completed` after the refinement.
 
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
+use bigdecimal::ToPrimitive;
 use euclid::Point2D;
 use rand::rng;
 use rand::seq::SliceRandom;
 use rayon::prelude::*;
 
+use crate::comp::fractal_formula::FractalFormula;
+use crate::comp::perturbation::ReferenceOrbit;
 use crate::storage::computation::comp_storage::CompStorage;
 use crate::storage::coord_spaces::MathSpace;
 use crate::storage::data_point::DataPoint;
 use crate::storage::image_comp_properties::StageState;
+use crate::storage::param_presets::FractalType;
+
+/// Interval in which `MandelbrotEngine::wait_or_stop()` checks the external stop flag
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Radius magnitude below which the engine uses perturbation instead of direct `f64` iteration
+const PERTURBATION_RADIUS_MAGNITUDE: i64 = -10;
+
+/// Minimum rectangle area (in pixels) which the Mariani-Silver strategy still subdivides
+const MARIANI_SILVER_MIN_AREA: u32 = 64;
+
+/// Height (in pixels) of the horizontal bands the boundary tracing strategy processes in parallel
+const BOUNDARY_TRACE_BAND_HEIGHT: u32 = 32;
+
+/// Minimum iteration count difference to a neighbour which makes a pixel an edge pixel
+const EDGE_MIN_DIFFERENCE: u32 = 3;
+
+/// Fraction (as divisor) of the iteration count which makes a pixel an edge pixel if exceeded
+const EDGE_RELATIVE_DIVISOR: u32 = 32;
+
+/// Number of edge pixels refined in parallel before the next, weaker edges are started
+const REFINEMENT_CHUNK_SIZE: usize = 256;
+
+/// Order in which the engine computes the points of the stage.
+///
+/// All strategies produce the same image (up to derived points), they differ
+/// in speed and in the way the image evolves during computation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComputationStrategy {
+    /// All pixels in shuffled order, gives a quickly refining preview
+    Shuffled,
+    /// Recursive rectangle subdivision, rectangles with uniform border are filled without iteration
+    MarianiSilver,
+    /// Follow the contours of equal iteration count and fill their interiors
+    BoundaryTrace,
+}
+
+impl ComputationStrategy {
+    /// Returns all available strategies, e.g. for UI selection lists.
+    pub fn all() -> &'static [Self] {
+        &[Self::Shuffled, Self::MarianiSilver, Self::BoundaryTrace]
+    }
+
+    /// Returns the human-readable name of the strategy.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Shuffled => "Shuffled pixels",
+            Self::MarianiSilver => "Mariani-Silver",
+            Self::BoundaryTrace => "Boundary tracing",
+        }
+    }
+}
+
+impl std::fmt::Display for ComputationStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
 
 /// Current state of the Mandelbrot computation engine.
 ///
@@ -64,7 +144,7 @@ pub enum EngineState {
 /// # Usage
 ///
 /// ```rust
-/// let engine = MandelbrotEngine::new(&comp_storage);
+/// let engine = MandelbrotEngine::new(&comp_storage, ComputationStrategy::Shuffled);
 /// engine.start(); // Begins computation in background thread
 /// // ... do other work ...
 /// engine.stop();  // Gracefully stops computation
@@ -74,6 +154,8 @@ pub struct MandelbrotEngine {
     pub state: Arc<Mutex<EngineState>>,
     /// Shared reference to computation storage for result persistence
     storage: Arc<CompStorage>,
+    /// Strategy defining the order in which points are computed
+    strategy: ComputationStrategy,
     /// Handle to the computation thread, None when not running
     thread_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
     /// Atomic flag for signaling computation cancellation
@@ -89,14 +171,16 @@ impl MandelbrotEngine {
     /// # Arguments
     ///
     /// * `storage` - Shared computation storage for results and configuration
+    /// * `strategy` - Order in which the points of the stage are computed
     ///
     /// # Returns
     ///
     /// A new engine instance ready to begin computation
-    pub fn new(storage: &Arc<CompStorage>) -> Self {
+    pub fn new(storage: &Arc<CompStorage>, strategy: ComputationStrategy) -> Self {
         MandelbrotEngine {
             state: Arc::new(Mutex::new(EngineState::PreStart)),
             storage: storage.clone(),
+            strategy,
             thread_handle: Arc::new(Mutex::new(None)),
             stop_flag: Arc::new(AtomicBool::new(false)),
         }
@@ -113,8 +197,8 @@ impl MandelbrotEngine {
     /// Starts Mandelbrot computation in a background thread.
     ///
     /// This method is idempotent - calling it multiple times while computation
-    /// is running has no effect. The computation uses a shuffled algorithm
-    /// for better visual progress indication.
+    /// is running has no effect. The computation uses the strategy given
+    /// at construction time.
     ///
     /// # Thread Safety
     ///
@@ -124,10 +208,16 @@ impl MandelbrotEngine {
     ///
     /// # Computation Algorithm
     ///
-    /// Uses `stoppable_compute_mandelbrot_shuffled` which:
+    /// `ComputationStrategy::Shuffled` uses `stoppable_compute_mandelbrot_shuffled` which:
     /// - Randomizes pixel computation order for visual appeal
     /// - Sorts by coordinate bit patterns for cache efficiency
-    /// - Checks cancellation every 1000 iterations
+    /// - Checks cancellation for every pixel
+    ///
+    /// `ComputationStrategy::MarianiSilver` uses `stoppable_compute_mariani_silver`
+    /// which subdivides the stage recursively into rectangles.
+    ///
+    /// `ComputationStrategy::BoundaryTrace` uses `stoppable_compute_boundary_trace`
+    /// which computes only the contours of equal iteration count.
     pub fn start(&self) {
         // Check if computation is already running
         // This block can only be entered _once_ at the same time, so the state test and change is atomic from the outside.
@@ -147,12 +237,23 @@ impl MandelbrotEngine {
         let storage_for_thread = self.storage.clone();
         let state_for_thread = self.state.clone();
         let stop_flag_for_thread = self.stop_flag.clone();
+        let strategy = self.strategy;
 
         // Now spawn the computation thread
         let handle = thread::spawn(move || {
             // Perform the computation
-            let result =
-                stoppable_compute_mandelbrot_shuffled(&storage_for_thread, &stop_flag_for_thread);
+            let result = match strategy {
+                ComputationStrategy::Shuffled => stoppable_compute_mandelbrot_shuffled(
+                    &storage_for_thread,
+                    &stop_flag_for_thread,
+                ),
+                ComputationStrategy::MarianiSilver => {
+                    stoppable_compute_mariani_silver(&storage_for_thread, &stop_flag_for_thread)
+                }
+                ComputationStrategy::BoundaryTrace => {
+                    stoppable_compute_boundary_trace(&storage_for_thread, &stop_flag_for_thread)
+                }
+            };
             // Update the state once computation is either finished or aborted
             let mut state = state_for_thread.lock().unwrap();
             *state = if result {
@@ -167,6 +268,50 @@ impl MandelbrotEngine {
         *thread_handle = Some(handle);
     }
 
+    /// Waits until the computation thread has finished without stopping it.
+    ///
+    /// Used by headless renderers which have nothing else to do while the engine runs.
+    /// Returns immediately if the engine has not been started or has already been stopped.
+    ///
+    /// # Returns
+    ///
+    /// The engine state after the computation thread has terminated
+    pub fn wait(&self) -> EngineState {
+        let handle = self.thread_handle.lock().unwrap().take();
+        if let Some(handle) = handle {
+            handle.join().unwrap();
+        }
+        self.state()
+    }
+
+    /// Waits until the computation thread has finished, stopping it once `stop_flag` is set.
+    ///
+    /// Used by headless renderers whose caller can cancel them through a flag of its own,
+    /// e.g. the animation rendering started from the GUI. The flag is checked in short
+    /// intervals, the engine's own stop flag then cancels the computation within one pixel.
+    ///
+    /// # Returns
+    ///
+    /// The engine state after the computation thread has terminated
+    pub fn wait_or_stop(&self, stop_flag: &AtomicBool) -> EngineState {
+        loop {
+            let running = self
+                .thread_handle
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|handle| !handle.is_finished());
+            if !running {
+                return self.wait();
+            }
+            if stop_flag.load(Ordering::Relaxed) {
+                self.stop();
+                return self.state();
+            }
+            thread::sleep(STOP_POLL_INTERVAL);
+        }
+    }
+
     /// Stops the computation and waits for thread completion.
     ///
     /// Signals the computation thread to stop and blocks until it finishes.
@@ -196,6 +341,162 @@ impl MandelbrotEngine {
     }
 }
 
+/// Method used to compute the data of single points of a stage.
+///
+/// The computer is created once per computation run and prepares everything
+/// which is shared by the pixels: the pixel coordinates and, for deep zooms,
+/// the high-precision reference orbit.
+struct PointComputer {
+    /// Iteration method and its prepared per-pixel data
+    method: PointMethod,
+    /// Mathematical distance between neighbouring pixels in x and y direction
+    step: (f64, f64),
+    /// Whether points are moved by a pseudo-random offset within their pixel
+    jittered: bool,
+}
+
+/// Iteration method of a `PointComputer`
+enum PointMethod {
+    /// Direct iteration of the fractal's formula with `f64` coordinates, sufficient for shallow zooms
+    Direct {
+        formula: Box<dyn FractalFormula>,
+        xcoo: Vec<f64>,
+        ycoo: Vec<f64>,
+        bailout: f64,
+    },
+    /// Perturbation against a high-precision reference orbit, needed for deep Mandelbrot zooms
+    Perturbation {
+        orbit: ReferenceOrbit,
+        dxcoo: Vec<f64>,
+        dycoo: Vec<f64>,
+        bailout: f64,
+    },
+}
+
+impl PointComputer {
+    /// Prepare the point computer for the given storage.
+    ///
+    /// The computation method is selected by the magnitude of the area's radius.
+    /// Perturbation is only available for the Mandelbrot set, all other fractal types
+    /// always iterate directly.
+    /// With jittered supersampling, each point is moved by a pseudo-random offset
+    /// of up to half a pixel, see `jitter_offset()`.
+    /// Returns `None` if the preparation has been interrupted through `stop_flag`.
+    fn for_storage(storage: &CompStorage, stop_flag: &AtomicBool) -> Option<Self> {
+        let stage_properties = &storage.properties.stage_properties;
+        let width = stage_properties.area.size().width as i32;
+        let height = stage_properties.area.size().height as i32;
+        let fractal_type = storage.properties.fractal_type;
+        // Computed from the exact coordinates, as the f64 values of neighbouring pixels
+        // might be identical in deep zooms
+        let step = (
+            (stage_properties.x(1) - stage_properties.x(0))
+                .to_f64()
+                .unwrap(),
+            (stage_properties.y(1) - stage_properties.y(0))
+                .to_f64()
+                .unwrap(),
+        );
+        let method = if fractal_type != FractalType::Mandelbrot
+            || stage_properties.area.math_area().radius_magnitude() >= PERTURBATION_RADIUS_MAGNITUDE
+        {
+            PointMethod::Direct {
+                formula: fractal_type.formula(),
+                xcoo: (0..width).map(|x| stage_properties.x_f64(x)).collect(),
+                ycoo: (0..height).map(|y| stage_properties.y_f64(y)).collect(),
+                bailout: storage.properties.bailout,
+            }
+        } else {
+            let orbit = ReferenceOrbit::for_stage(
+                stage_properties,
+                storage.properties.max_iteration,
+                stop_flag,
+            )?;
+            let reference = orbit.reference().clone();
+            let dxcoo = (0..width)
+                .map(|x| (stage_properties.x(x) - &reference.x).to_f64().unwrap())
+                .collect();
+            let dycoo = (0..height)
+                .map(|y| (stage_properties.y(y) - &reference.y).to_f64().unwrap())
+                .collect();
+            PointMethod::Perturbation {
+                orbit,
+                dxcoo,
+                dycoo,
+                bailout: storage.properties.bailout,
+            }
+        };
+        Some(PointComputer {
+            method,
+            step,
+            jittered: storage.properties.supersampling.is_jittered(),
+        })
+    }
+
+    /// Compute the data point at pixel position `x`, `y`
+    fn data_point_at(&self, x: u32, y: u32, max_iteration: u32) -> DataPoint {
+        let offset = if self.jittered {
+            jitter_offset(x, y)
+        } else {
+            (0.0, 0.0)
+        };
+        self.sample_at(x, y, offset, max_iteration)
+    }
+
+    /// Compute the data point at an offset from the center of pixel `x`, `y`
+    ///
+    /// # Arguments
+    ///
+    /// * `x`, `y` - Pixel position
+    /// * `offset` - Offset from the pixel's center in pixels, `-0.5..0.5` stays within the pixel
+    /// * `max_iteration` - Maximum iteration count
+    fn sample_at(&self, x: u32, y: u32, offset: (f64, f64), max_iteration: u32) -> DataPoint {
+        let (dx, dy) = (offset.0 * self.step.0, offset.1 * self.step.1);
+        match &self.method {
+            PointMethod::Direct {
+                formula,
+                xcoo,
+                ycoo,
+                bailout,
+            } => formula.data_point_at(
+                xcoo[x as usize] + dx,
+                ycoo[y as usize] + dy,
+                max_iteration,
+                *bailout,
+            ),
+            PointMethod::Perturbation {
+                orbit,
+                dxcoo,
+                dycoo,
+                bailout,
+            } => orbit.data_point_at(
+                dxcoo[x as usize] + dx,
+                dycoo[y as usize] + dy,
+                max_iteration,
+                *bailout,
+            ),
+        }
+    }
+}
+
+/// Calculates the jitter of the point at pixel position `x`, `y`.
+///
+/// The offset is derived from a hash of the position, so recomputing a point
+/// always gives the same result.
+///
+/// # Returns
+///
+/// Offsets in both directions in pixels, in `-0.5 .. 0.5`
+fn jitter_offset(x: u32, y: u32) -> (f64, f64) {
+    // SplitMix64 finalizer, mixes all bits of the position into the hash
+    let mut hash = ((x as u64) << 32 | y as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^= hash >> 31;
+    let unit = |bits: u64| (bits & 0xFF_FFFF) as f64 / (1 << 24) as f64 - 0.5;
+    (unit(hash >> 32), unit(hash))
+}
+
 /// Calculates sort index for coordinate ordering optimization.
 ///
 /// Uses bit manipulation to determine the minimum number of trailing zeros
@@ -228,6 +529,7 @@ fn order_coords<T>(p: &Point2D<u32, T>, q: &Point2D<u32, T>) -> std::cmp::Orderi
 /// # Algorithm Steps
 ///
 /// 1. **Coordinate Generation**: Creates all pixel coordinates
+///    and prepares the point computer (direct or perturbation)
 /// 2. **Shuffling**: Randomizes order for visual appeal
 /// 3. **Cache Optimization**: Sorts by memory access patterns
 /// 4. **Computation**: Iterates through pixels with periodic cancellation checks
@@ -252,13 +554,7 @@ fn stoppable_compute_mandelbrot_shuffled(storage: &CompStorage, stop_flag: &Atom
     let height = storage.properties.stage_properties.area.size().height as i32;
     let width = storage.properties.stage_properties.area.size().width as i32;
     let mut coords: Vec<Point2D<u32, MathSpace>> = Vec::with_capacity((height * width) as usize);
-    let mut ycoo = Vec::with_capacity(height as usize);
-    let mut xcoo = Vec::with_capacity(width as usize);
-    for x in 0..width {
-        xcoo.push(storage.properties.stage_properties.x_f64(x));
-    }
     for y in 0..height {
-        ycoo.push(storage.properties.stage_properties.y_f64(y));
         for x in 0..width {
             coords.push(Point2D::new(x as u32, y as u32));
         }
@@ -266,25 +562,275 @@ fn stoppable_compute_mandelbrot_shuffled(storage: &CompStorage, stop_flag: &Atom
     coords.shuffle(&mut rng());
     coords.sort_by(order_coords); // Needs appropriate presentation code, otherwise looks a bit strange
     storage.stage.set_state(StageState::Evolving);
+    let Some(computer) = PointComputer::for_storage(storage, stop_flag) else {
+        storage.stage.set_state(StageState::Stalled);
+        return false;
+    };
     coords.into_par_iter().for_each(|point| {
         if !stop_flag.load(Ordering::Relaxed) && !storage.stage.is_computed(point.x, point.y) {
             storage.stage.set(
                 point.x,
                 point.y,
-                data_point_at(
-                    *(xcoo.get(point.x as usize).unwrap()),
-                    *(ycoo.get(point.y as usize).unwrap()),
-                    max_iteration,
-                ),
+                computer.data_point_at(point.x, point.y, max_iteration),
             );
         }
     });
-    if stop_flag.load(Ordering::Relaxed) {
+    finish_stage(storage, &computer, stop_flag);
+    true // Computation ended successfully
+}
+
+/// Computes the stage by recursive rectangle subdivision (Mariani-Silver algorithm).
+///
+/// The Mandelbrot set and its level sets are connected. So if the whole border of a rectangle
+/// has the same iteration count, the complete interior has it, too. This allows to fill
+/// large areas, especially the set's interior, without iterating them.
+///
+/// # Algorithm Steps
+///
+/// 1. **Border**: Compute (or reuse) the iteration counts of the rectangle's border
+/// 2. **Uniform Border**: Fill the interior with `DataQuality::Derived` points
+/// 3. **Small Rectangle**: Compute all interior points directly
+/// 4. **Otherwise**: Split into four quadrants sharing their borders and recurse in parallel
+///
+/// Note that the connectivity argument does not hold for all fractal types,
+/// e.g. disconnected Julia sets, which might show artifacts with this strategy.
+///
+/// # Arguments
+///
+/// * `storage` - Computation storage containing configuration and results
+/// * `stop_flag` - Atomic flag for graceful cancellation
+///
+/// # Returns
+///
+/// `true` if computation completed successfully, `false` if cancelled during preparation
+fn stoppable_compute_mariani_silver(storage: &CompStorage, stop_flag: &AtomicBool) -> bool {
+    let size = *storage.properties.stage_properties.area.size();
+    storage.stage.set_state(StageState::Evolving);
+    let Some(computer) = PointComputer::for_storage(storage, stop_flag) else {
         storage.stage.set_state(StageState::Stalled);
+        return false;
+    };
+    if size.width > 0 && size.height > 0 {
+        mariani_silver_rect(
+            storage,
+            &computer,
+            stop_flag,
+            (0, 0),
+            (size.width - 1, size.height - 1),
+        );
+    }
+    finish_stage(storage, &computer, stop_flag);
+    true
+}
+
+/// Return the iteration count of a point, computing and storing it if not computed yet.
+fn iteration_count_at(storage: &CompStorage, computer: &PointComputer, x: u32, y: u32) -> u32 {
+    if let Some(point) = storage.stage.get(x, y)
+        && point.iteration_count_quality.is_accurate()
+    {
+        point.iteration_count
     } else {
-        storage.stage.set_state(StageState::Completed);
+        let point = computer.data_point_at(x, y, storage.properties.max_iteration);
+        storage.stage.set(x, y, point);
+        point.iteration_count
+    }
+}
+
+/// Process one rectangle of the Mariani-Silver algorithm, corners `min` and `max` are inclusive.
+fn mariani_silver_rect(
+    storage: &CompStorage,
+    computer: &PointComputer,
+    stop_flag: &AtomicBool,
+    min: (u32, u32),
+    max: (u32, u32),
+) {
+    if stop_flag.load(Ordering::Relaxed) {
+        return;
+    }
+    let ((x0, y0), (x1, y1)) = (min, max);
+    let border = (x0..=x1)
+        .flat_map(|x| [(x, y0), (x, y1)])
+        .chain((y0 + 1..y1).flat_map(|y| [(x0, y), (x1, y)]));
+    let mut uniform_count = None;
+    let mut uniform = true;
+    for (x, y) in border {
+        let count = iteration_count_at(storage, computer, x, y);
+        if *uniform_count.get_or_insert(count) != count {
+            uniform = false;
+        }
+    }
+    if x1 - x0 < 2 || y1 - y0 < 2 {
+        return; // No interior left
+    }
+    if uniform {
+        let derived = DataPoint::derived(uniform_count.unwrap());
+        for y in y0 + 1..y1 {
+            for x in x0 + 1..x1 {
+                if !storage.stage.is_computed(x, y) {
+                    storage.stage.set(x, y, derived);
+                }
+            }
+        }
+    } else if (x1 - x0) * (y1 - y0) <= MARIANI_SILVER_MIN_AREA {
+        for y in y0 + 1..y1 {
+            if stop_flag.load(Ordering::Relaxed) {
+                return;
+            }
+            for x in x0 + 1..x1 {
+                iteration_count_at(storage, computer, x, y);
+            }
+        }
+    } else {
+        let xm = (x0 + x1) / 2;
+        let ym = (y0 + y1) / 2;
+        rayon::join(
+            || {
+                rayon::join(
+                    || mariani_silver_rect(storage, computer, stop_flag, (x0, y0), (xm, ym)),
+                    || mariani_silver_rect(storage, computer, stop_flag, (xm, y0), (x1, ym)),
+                )
+            },
+            || {
+                rayon::join(
+                    || mariani_silver_rect(storage, computer, stop_flag, (x0, ym), (xm, y1)),
+                    || mariani_silver_rect(storage, computer, stop_flag, (xm, ym), (x1, y1)),
+                )
+            },
+        );
+    }
+}
+
+/// Computes the stage by tracing the boundaries of areas with equal iteration count.
+///
+/// Like Mariani-Silver, this relies on the connectivity of the level sets: An area
+/// completely enclosed by points of one iteration count has that iteration count.
+/// Other than Mariani-Silver, it follows the actual contours instead of rectangles.
+///
+/// # Algorithm Steps
+///
+/// The stage is split into horizontal bands of `BOUNDARY_TRACE_BAND_HEIGHT` rows
+/// which are processed in parallel. For each band:
+///
+/// 1. **Edges**: All edge pixels of the band are queued
+/// 2. **Tracing**: A queued pixel is compared to its four neighbours (computing them as needed).
+///    If a neighbour differs, the boundary continues there and the neighbour
+///    (and the diagonal pixels next to it) are queued.
+/// 3. **Filling**: Each row is scanned. All pixels not computed now lie within a contour
+///    and get the iteration count of their left neighbour. They are set with one
+///    `DataPointChange::Run` event per run.
+///
+/// The fill step is skipped if the computation is cancelled, as the contours are incomplete then.
+///
+/// # Arguments
+///
+/// * `storage` - Computation storage containing configuration and results
+/// * `stop_flag` - Atomic flag for graceful cancellation
+///
+/// # Returns
+///
+/// `true` if computation completed successfully, `false` if cancelled during preparation
+fn stoppable_compute_boundary_trace(storage: &CompStorage, stop_flag: &AtomicBool) -> bool {
+    let size = *storage.properties.stage_properties.area.size();
+    storage.stage.set_state(StageState::Evolving);
+    let Some(computer) = PointComputer::for_storage(storage, stop_flag) else {
+        storage.stage.set_state(StageState::Stalled);
+        return false;
+    };
+    if size.width > 0 && size.height > 0 {
+        (0..size.height)
+            .step_by(BOUNDARY_TRACE_BAND_HEIGHT as usize)
+            .collect::<Vec<_>>()
+            .par_iter()
+            .for_each(|&y_start| {
+                let y_end = (y_start + BOUNDARY_TRACE_BAND_HEIGHT).min(size.height);
+                boundary_trace_band(storage, &computer, stop_flag, y_start, y_end);
+            });
+    }
+    finish_stage(storage, &computer, stop_flag);
+    true
+}
+
+/// Trace and fill the band of rows `y_start..y_end` of the stage.
+fn boundary_trace_band(
+    storage: &CompStorage,
+    computer: &PointComputer,
+    stop_flag: &AtomicBool,
+    y_start: u32,
+    y_end: u32,
+) {
+    let width = storage.properties.stage_properties.area.size().width;
+    let height = y_end - y_start;
+    let mut queued = vec![false; (width * height) as usize];
+    let mut queue = VecDeque::new();
+    let mut enqueue = |x: u32, y: u32, queue: &mut VecDeque<(u32, u32)>| {
+        let index = ((y - y_start) * width + x) as usize;
+        if !queued[index] {
+            queued[index] = true;
+            queue.push_back((x, y));
+        }
+    };
+    for x in 0..width {
+        enqueue(x, y_start, &mut queue);
+        enqueue(x, y_end - 1, &mut queue);
+    }
+    for y in y_start..y_end {
+        enqueue(0, y, &mut queue);
+        enqueue(width - 1, y, &mut queue);
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        if stop_flag.load(Ordering::Relaxed) {
+            return;
+        }
+        let count = iteration_count_at(storage, computer, x, y);
+        let differs = |nx: u32, ny: u32| iteration_count_at(storage, computer, nx, ny) != count;
+        let left = x > 0 && differs(x - 1, y);
+        let right = x < width - 1 && differs(x + 1, y);
+        let up = y > y_start && differs(x, y - 1);
+        let down = y < y_end - 1 && differs(x, y + 1);
+        if left {
+            enqueue(x - 1, y, &mut queue);
+        }
+        if right {
+            enqueue(x + 1, y, &mut queue);
+        }
+        if up {
+            enqueue(x, y - 1, &mut queue);
+        }
+        if down {
+            enqueue(x, y + 1, &mut queue);
+        }
+        // Diagonal neighbours continue the boundary around corners
+        if (left || up) && x > 0 && y > y_start {
+            enqueue(x - 1, y - 1, &mut queue);
+        }
+        if (right || up) && x < width - 1 && y > y_start {
+            enqueue(x + 1, y - 1, &mut queue);
+        }
+        if (left || down) && x > 0 && y < y_end - 1 {
+            enqueue(x - 1, y + 1, &mut queue);
+        }
+        if (right || down) && x < width - 1 && y < y_end - 1 {
+            enqueue(x + 1, y + 1, &mut queue);
+        }
+    }
+    // All uncomputed pixels are enclosed by a contour now, fill them row by row
+    for y in y_start..y_end {
+        let mut x = 1;
+        while x < width {
+            if storage.stage.is_computed(x, y) {
+                x += 1;
+                continue;
+            }
+            let count = iteration_count_at(storage, computer, x - 1, y);
+            let x_start = x;
+            while x < width && !storage.stage.is_computed(x, y) {
+                x += 1;
+            }
+            storage
+                .stage
+                .set_run(x_start, x, y, DataPoint::derived(count));
+        }
     }
-    true // Computation ended successfully
 }
 
 /// Computes Mandelbrot set using linear pixel order with cancellation support.
@@ -312,77 +858,246 @@ fn stoppable_compute_mandelbrot_shuffled(storage: &CompStorage, stop_flag: &Atom
 fn stoppable_compute_mandelbrot_linear(storage: &CompStorage, stop_flag: &AtomicBool) -> bool {
     let max_iteration = storage.properties.max_iteration;
     storage.stage.set_state(StageState::Evolving);
+    let Some(computer) = PointComputer::for_storage(storage, stop_flag) else {
+        storage.stage.set_state(StageState::Stalled);
+        return false;
+    };
     for y in 0..storage.properties.stage_properties.area.size().height {
         // Check for cancellation every row, this is only interim as way too inflexible!
         if stop_flag.load(Ordering::Relaxed) {
             storage.stage.set_state(StageState::Stalled);
             return false; // Computation was aborted
         }
-        let y_coo = storage.properties.stage_properties.y_f64(y as i32);
         for x in 0..storage.properties.stage_properties.area.size().width {
-            let x_coo = storage.properties.stage_properties.x_f64(x as i32);
             if !storage.stage.is_computed(x, y) {
                 storage
                     .stage
-                    .set(x, y, data_point_at(x_coo, y_coo, max_iteration));
+                    .set(x, y, computer.data_point_at(x, y, max_iteration));
             }
         }
     }
-    storage.stage.set_state(StageState::Completed);
+    finish_stage(storage, &computer, stop_flag);
     true // Computation ended successfully
 }
 
-/// Computes Mandelbrot iteration data for a single complex point.
+/// Finish a computation run by refining edges if requested and setting the final stage state.
 ///
-/// This implementation uses an optimized straight algorithm, see
-///
-/// https://en.wikipedia.org/wiki/Plotting_algorithms_for_the_Mandelbrot_set
-///
-/// This is the core mathematical algorithm implementing the classic Mandelbrot
-/// iteration: `z(n+1) = z(n)² + c`. The function tracks both escape iteration
-/// and final z-value for enhanced visualization possibilities.
-///
-/// # Algorithm Details
+/// The state becomes `Stalled` if the computation has been stopped, `Completed` otherwise.
+fn finish_stage(storage: &CompStorage, computer: &PointComputer, stop_flag: &AtomicBool) {
+    if !stop_flag.load(Ordering::Relaxed) {
+        refine_edges(storage, computer, stop_flag);
+    }
+    if stop_flag.load(Ordering::Relaxed) {
+        storage.stage.set_state(StageState::Stalled);
+    } else {
+        storage.stage.set_state(StageState::Completed);
+    }
+}
+
+/// Return the contrast between two neighbouring points if it marks an edge.
 ///
-/// - **Iteration**: `z(n+1) = z(n)² + c` starting with `z(0) = 0`
-/// - **Escape Condition**: `|z|² > 4.0` (equivalent to `|z| > 2.0`)
-/// - **Maximum Iterations**: Configurable limit to bound computation time
-/// - **Final Value**: Always computes one additional iteration for smoother coloring
+/// Points differ strongly if one belongs to the set and the other does not, or if their
+/// iteration counts differ by at least `EDGE_MIN_DIFFERENCE` and `1/EDGE_RELATIVE_DIVISOR`
+/// of the smaller count. The relative part keeps the smooth, but steep bands of deep zooms
+/// from being regarded as edges everywhere.
 ///
-/// # Arguments
+/// # Returns
 ///
-/// * `c_real` - Real component of the complex number c
-/// * `c_imag` - Imaginary component of the complex number c
-/// * `max_iteration` - Maximum number of iterations to perform
+/// - `Some(contrast)` for an edge, larger values denote stronger edges
+/// - `None` if the points are similar
+fn edge_contrast(a: &DataPoint, b: &DataPoint, max_iteration: u32) -> Option<u32> {
+    let (a, b) = (a.iteration_count, b.iteration_count);
+    if (a >= max_iteration) != (b >= max_iteration) {
+        return Some(max_iteration);
+    }
+    let difference = a.abs_diff(b);
+    (difference >= EDGE_MIN_DIFFERENCE.max(a.min(b) / EDGE_RELATIVE_DIVISOR)).then_some(difference)
+}
+
+/// Find all pixels which are not refined yet and have a strong edge to one of their neighbours.
 ///
 /// # Returns
 ///
-/// `DataPoint` containing:
-/// - Iteration count when escape occurred (or max_iteration)
-/// - Final z-value for potential smooth coloring algorithms
-///
-/// # Mathematical Background
-///
-/// The Mandelbrot set consists of complex numbers c for which the iteration
-/// `z(n+1) = z(n)² + c` remains bounded. Points that escape to infinity
-/// (|z| > 2) are not in the set, and the iteration count indicates how
-/// quickly they diverge.
-fn data_point_at(c_real: f64, c_imag: f64, max_iteration: u32) -> DataPoint {
-    let mut x = 0.0;
-    let mut y = 0.0;
-    let mut x2 = 0.0;
-    let mut y2 = 0.0;
-    let mut w = 0.0;
-    let mut iteration = 0;
-    while x2 + y2 < 4.0 && iteration < max_iteration {
-        x = x2 - y2 + c_real;
-        y = w - x2 - y2 + c_imag;
-        x2 = x * x;
-        y2 = y * y;
-        w = (x + y) * (x + y);
-        iteration += 1;
-    }
-    DataPoint::computed(iteration, Point2D::new(x, y))
+/// Pixel positions ordered by descending contrast, see `edge_contrast()`
+fn edge_pixels(storage: &CompStorage) -> Vec<(u32, u32)> {
+    let max_iteration = storage.properties.max_iteration;
+    let size = *storage.properties.stage_properties.area.size();
+    let (width, height) = (size.width as usize, size.height as usize);
+    let mut contrast = vec![0u32; width * height];
+    for y in 0..size.height {
+        for x in 0..size.width {
+            let Some(point) = storage.stage.get(x, y) else {
+                continue;
+            };
+            for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                if nx < size.width
+                    && ny < size.height
+                    && let Some(neighbour) = storage.stage.get(nx, ny)
+                    && let Some(edge) = edge_contrast(&point, &neighbour, max_iteration)
+                {
+                    for idx in [
+                        y as usize * width + x as usize,
+                        ny as usize * width + nx as usize,
+                    ] {
+                        contrast[idx] = contrast[idx].max(edge);
+                    }
+                }
+            }
+        }
+    }
+    let mut edges: Vec<(u32, u32, u32)> = (0..height * width)
+        .filter(|&idx| contrast[idx] > 0)
+        .map(|idx| ((idx % width) as u32, (idx / width) as u32, contrast[idx]))
+        .filter(|&(x, y, _)| !storage.stage.has_samples(x, y))
+        .collect();
+    edges.sort_by_key(|&(_, _, contrast)| std::cmp::Reverse(contrast));
+    edges.into_iter().map(|(x, y, _)| (x, y)).collect()
+}
+
+/// Add sub-pixel samples to the edge pixels of the stage (adaptive anti-aliasing).
+///
+/// Does nothing if the supersampling has no refinement factor. Otherwise, each edge pixel
+/// gets the samples of a regular `n × n` sub-pixel grid, except the one at its center which
+/// is the pixel's own data point. The edges are processed in chunks, strongest first,
+/// so that the most visible improvements appear first.
+fn refine_edges(storage: &CompStorage, computer: &PointComputer, stop_flag: &AtomicBool) {
+    let Some(factor) = storage.properties.supersampling.refinement_factor() else {
+        return;
+    };
+    let max_iteration = storage.properties.max_iteration;
+    let sub_pixel = |i: u32| (i as f64 + 0.5) / factor as f64 - 0.5;
+    let offsets: Vec<(f64, f64)> = (0..factor * factor)
+        .map(|i| (sub_pixel(i % factor), sub_pixel(i / factor)))
+        .filter(|&offset| offset != (0.0, 0.0))
+        .collect();
+    for chunk in edge_pixels(storage).chunks(REFINEMENT_CHUNK_SIZE) {
+        chunk.par_iter().for_each(|&(x, y)| {
+            if !stop_flag.load(Ordering::Relaxed) {
+                let samples: Vec<DataPoint> = offsets
+                    .iter()
+                    .map(|&offset| computer.sample_at(x, y, offset, max_iteration))
+                    .collect();
+                storage.stage.add_samples(x, y, &samples);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use euclid::Size2D;
+
+    use super::*;
+    use crate::comp::math_area::{MathArea, RasteredMathArea};
+    use crate::storage::data_point::DataQuality;
+    use crate::storage::image_comp_properties::{
+        DEFAULT_BAILOUT, ImageCompProperties, StageProperties, Supersampling,
+    };
+
+    fn storage(width: u32, height: u32) -> CompStorage {
+        supersampled_storage(width, height, Supersampling::Off)
+    }
+
+    fn supersampled_storage(width: u32, height: u32, supersampling: Supersampling) -> CompStorage {
+        let area = MathArea::from_str("-0.5", "0", "1.5", "1").unwrap();
+        let properties = ImageCompProperties::new(
+            StageProperties::new(RasteredMathArea::new(area, Size2D::new(width, height))),
+            200,
+            FractalType::Mandelbrot,
+            DEFAULT_BAILOUT,
+            supersampling,
+        );
+        CompStorage::new(properties)
+    }
+
+    /// Compute a stage with a strategy deriving points and compare it with the shuffled result.
+    ///
+    /// Computed points must match exactly, fewer than 1% of the derived points may differ.
+    fn assert_matches_shuffled(compute: fn(&CompStorage, &AtomicBool) -> bool) {
+        let stop_flag = AtomicBool::new(false);
+        let shuffled = storage(64, 48);
+        let derived_storage = storage(64, 48);
+        assert!(stoppable_compute_mandelbrot_shuffled(&shuffled, &stop_flag));
+        assert!(compute(&derived_storage, &stop_flag));
+        assert_eq!(StageState::Completed, derived_storage.stage.get_state());
+        let (mut derived, mut mismatches) = (0, 0);
+        for y in 0..48 {
+            for x in 0..64 {
+                let expected = shuffled.stage.get(x, y).unwrap();
+                let actual = derived_storage.stage.get(x, y).unwrap();
+                if actual.iteration_count_quality == DataQuality::Derived {
+                    derived += 1;
+                    if expected.iteration_count != actual.iteration_count {
+                        mismatches += 1;
+                    }
+                } else {
+                    assert_eq!(expected.iteration_count, actual.iteration_count);
+                }
+            }
+        }
+        assert!(derived > 0);
+        assert!(mismatches * 100 < derived, "{mismatches} of {derived}");
+    }
+
+    #[test]
+    fn mariani_silver_matches_shuffled() {
+        assert_matches_shuffled(stoppable_compute_mariani_silver);
+    }
+
+    #[test]
+    fn boundary_trace_matches_shuffled() {
+        assert_matches_shuffled(stoppable_compute_boundary_trace);
+    }
+
+    #[test]
+    fn adaptive_supersampling_refines_only_edges() {
+        let stop_flag = AtomicBool::new(false);
+        let plain = storage(64, 48);
+        let adaptive = supersampled_storage(64, 48, Supersampling::Adaptive3x3);
+        assert!(stoppable_compute_mandelbrot_shuffled(&plain, &stop_flag));
+        assert!(stoppable_compute_mariani_silver(&adaptive, &stop_flag));
+        assert_eq!(StageState::Completed, adaptive.stage.get_state());
+        assert!(plain.stage.get_full_samples().is_empty());
+        let samples = adaptive.stage.get_full_samples();
+        assert!(!samples.is_empty() && samples.len() < 64 * 48 / 2);
+        assert!(
+            samples
+                .values()
+                .all(|pixel_samples| pixel_samples.len() == 8)
+        );
+        // The center of the area is inside the set, far from any edge
+        assert!(!adaptive.stage.has_samples(32, 24));
+        // Once refined, pixels are not refined again
+        assert!(edge_pixels(&adaptive).is_empty());
+    }
+
+    #[test]
+    fn mariani_silver_honours_stop_flag() {
+        let stop_flag = AtomicBool::new(true);
+        let subdivided = storage(32, 32);
+        stoppable_compute_mariani_silver(&subdivided, &stop_flag);
+        assert_eq!(StageState::Stalled, subdivided.stage.get_state());
+        assert!(!subdivided.stage.is_computed(0, 0));
+    }
+
+    #[test]
+    fn wait_or_stop_honours_external_stop_flag() {
+        let stop_flag = AtomicBool::new(true);
+        let storage = Arc::new(storage(1024, 1024));
+        let engine = MandelbrotEngine::new(&storage, ComputationStrategy::Shuffled);
+        engine.start();
+        engine.wait_or_stop(&stop_flag);
+        assert_eq!(StageState::Stalled, storage.stage.get_state());
+
+        let completed = Arc::new(self::storage(16, 16));
+        let engine = MandelbrotEngine::new(&completed, ComputationStrategy::Shuffled);
+        engine.start();
+        assert_eq!(
+            EngineState::Finished,
+            engine.wait_or_stop(&AtomicBool::new(false))
+        );
+        assert_eq!(StageState::Completed, completed.stage.get_state());
+    }
 }
 
 // end of file
@@ -71,6 +71,7 @@ impl MathArea {
     /// Return a new instance of math area using string representations of BigDecimal values
     ///
     /// If any of the strings cannot be converted into a big decimal, return None
+    #[cfg(test)]
     pub fn from_str(center_x: &str, center_y: &str, radius: &str, ratio: &str) -> Option<Self> {
         if let Ok(center_x) = BigDecimal::from_str(center_x)
             && let Ok(center_y) = BigDecimal::from_str(center_y)
@@ -348,7 +349,18 @@ impl RasteredMathArea {
     /// Note that some schemes from the Euclid library, namely to_vector() and friends, do not work here
     /// as BigDecimal does not implement the Copy trait. Too bad…
     pub fn zoom_at_pixel(&self, origin: Point2D<i32, StageSpace>, factor: BigDecimal) -> Self {
-        let new_origin = self.coo_pix(origin);
+        self.zoom_at_math(self.coo_pix(origin), factor)
+    }
+
+    /// Return a zoomed version with a certain factor at a certain mathematical coordinate
+    ///
+    /// The coordinate keeps its relative position in the area, see `zoom_at_pixel()`.
+    /// Other than a pixel, a coordinate means the same point in areas of any raster size.
+    pub fn zoom_at_math(
+        &self,
+        new_origin: Point2D<BigDecimal, MathSpace>,
+        factor: BigDecimal,
+    ) -> Self {
         let old_center = &self.math_area.center;
         let orig_to_old_center: Vector2D<BigDecimal, MathSpace> =
             Vector2D::new(&new_origin.x - &old_center.x, &new_origin.y - &old_center.y);
@@ -382,6 +394,26 @@ impl RasteredMathArea {
             )
         }
     }
+
+    /// Return the rastered area of a rectangular part of the raster, e.g. one tile of an image
+    ///
+    /// The pixels of the part have the same mathematical coordinates as the corresponding
+    /// pixels of this area, up to the rounding of the part's radius to the relevant precision.
+    pub fn sub_area(&self, rect: Rect<u32, StageSpace>) -> Self {
+        let two = BigDecimal::from(2);
+        let left = self.coo_pix_x(rect.min_x() as i32);
+        let right = self.coo_pix_x(rect.max_x() as i32);
+        let top = self.coo_pix_y(rect.min_y() as i32);
+        let bottom = self.coo_pix_y(rect.max_y() as i32);
+        let radius = if rect.size.width >= rect.size.height {
+            (&top - &bottom) / &two
+        } else {
+            (&right - &left) / &two
+        };
+        let center = Point2D::new((left + right) / &two, (top + bottom) / &two);
+        let ratio = BigDecimal::from(rect.size.width) / BigDecimal::from(rect.size.height);
+        Self::new(MathArea::new(center, radius, ratio), rect.size)
+    }
 }
 
 #[cfg(test)]
@@ -719,6 +751,35 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn raster_sub_area() {
+        let area = RasteredMathArea::new(
+            MathArea::from_str("-0.7435669", "0.1314023", "0.0022878", "1.5").unwrap(),
+            Size2D::new(1500, 1000),
+        );
+        for rect in [
+            Rect::new(Point2D::new(500, 256), Size2D::new(512, 256)),
+            Rect::new(Point2D::new(1024, 768), Size2D::new(476, 232)),
+        ] {
+            let part = area.sub_area(rect);
+            assert_eq!(rect.size, *part.size());
+            let pix_width = area.pix_size().width.to_f64().unwrap();
+            for (x, y) in [
+                (0, 0),
+                (17, 9),
+                (rect.size.width as i32, rect.size.height as i32),
+            ] {
+                let expected = area.coo_pix(Point2D::new(
+                    rect.min_x() as i32 + x,
+                    rect.min_y() as i32 + y,
+                ));
+                let actual = part.coo_pix(Point2D::new(x, y));
+                assert!(((expected.x - actual.x).to_f64().unwrap() / pix_width).abs() < 1e-4);
+                assert!(((expected.y - actual.y).to_f64().unwrap() / pix_width).abs() < 1e-4);
+            }
+        }
+    }
 }
 
 // end of file
@@ -1,4 +1,6 @@
 // Module containing the algorithms to actually compute graphics
 pub mod bd_math;
+pub mod fractal_formula;
 pub mod mandelbrot_engine;
 pub mod math_area;
+pub mod perturbation;
@@ -0,0 +1,332 @@
+//! Perturbation-theory computation for deep zooms.
+//!
+//! Plain `f64` iteration breaks down once the pixel distance falls below
+//! the resolution of `f64` relative to the coordinate values, i.e. at radii
+//! of roughly 1e-13 and below. Neighbouring pixels then collapse onto the same
+//! floating point number and the image degenerates into blocks.
+//!
+//! Perturbation theory avoids this by computing only _one_ orbit in high precision,
+//! the so-called reference orbit `Z(n)` at a reference point `C`. Every pixel `c = C + δc`
+//! is then described by its (small) difference `δ(n) = z(n) - Z(n)` from that reference,
+//! which obeys
+//!
+//! ```text
+//! δ(n+1) = 2·Z(n)·δ(n) + δ(n)² + δc
+//! ```
+//!
+//! This difference iteration runs in plain `f64` as only relative precision is needed.
+//!
+//! # Glitches and rebasing
+//!
+//! The difference iteration loses precision ("glitches") when the full value
+//! `z(n) = Z(n) + δ(n)` becomes smaller than the difference `δ(n)` itself.
+//! This is detected in every iteration step. The pixel is then rebased onto the
+//! start of the reference orbit: `δ` is set to the full value `z(n)` and the
+//! reference index restarts at 0 (where `Z(0) = 0`). The same happens when the
+//! reference orbit has escaped before the pixel did.
+//!
+//! The reference point itself is chosen so that its orbit is as long as possible:
+//! The area center is tried first, if it escapes early, some further candidates
+//! within the area are examined.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive, Zero};
+use euclid::Point2D;
+
+use crate::comp::fractal_formula::{distance_and_normal, smooth_iteration};
+use crate::storage::{
+    coord_spaces::MathSpace, data_point::DataPoint, image_comp_properties::StageProperties,
+};
+
+/// Number of decimal digits used in the reference orbit beyond the precision of the area
+const GUARD_DIGITS: u64 = 12;
+
+/// Number of iterations between two checks of the stop flag during reference orbit computation
+const STOP_CHECK_INTERVAL: u32 = 1000;
+
+/// Relative positions (as fraction of the area's radius) of reference point candidates
+const REFERENCE_CANDIDATES: [(f64, f64); 9] = [
+    (0.0, 0.0),
+    (0.5, 0.0),
+    (-0.5, 0.0),
+    (0.0, 0.5),
+    (0.0, -0.5),
+    (0.5, 0.5),
+    (-0.5, -0.5),
+    (0.5, -0.5),
+    (-0.5, 0.5),
+];
+
+/// Round `value` to `precision` significant digits, leaving shorter values untouched.
+///
+/// `BigDecimal::with_prec()` also _extends_ shorter values with zeros which only costs time here.
+fn rounded(value: BigDecimal, precision: u64) -> BigDecimal {
+    if value.digits() > precision {
+        value.with_prec(precision)
+    } else {
+        value
+    }
+}
+
+/// High-precision orbit of a reference point, stored as `f64` values for the difference iteration.
+#[derive(Debug, Clone)]
+pub struct ReferenceOrbit {
+    /// Reference point the orbit belongs to
+    reference: Point2D<BigDecimal, MathSpace>,
+    /// Orbit values `Z(0)…Z(n)`, `Z(0)` is always the origin
+    orbit: Vec<Point2D<f64, MathSpace>>,
+    /// Whether the orbit escaped before reaching the maximum iteration count
+    escaped: bool,
+}
+
+impl ReferenceOrbit {
+    /// Compute the reference orbit of point `reference` with `precision` significant digits.
+    ///
+    /// Returns `None` if the computation has been interrupted through `stop_flag`.
+    pub fn compute(
+        reference: Point2D<BigDecimal, MathSpace>,
+        precision: u64,
+        max_iteration: u32,
+        stop_flag: &AtomicBool,
+    ) -> Option<Self> {
+        let mut orbit = Vec::with_capacity(max_iteration as usize + 1);
+        orbit.push(Point2D::new(0.0, 0.0));
+        let mut x = BigDecimal::zero();
+        let mut y = BigDecimal::zero();
+        let mut escaped = false;
+        for iteration in 1..=max_iteration {
+            if iteration % STOP_CHECK_INTERVAL == 0 && stop_flag.load(Ordering::Relaxed) {
+                return None;
+            }
+            let x2 = rounded(&x * &x, precision);
+            let y2 = rounded(&y * &y, precision);
+            let xy = rounded(&x * &y, precision);
+            x = rounded(x2 - y2 + &reference.x, precision);
+            y = rounded(&xy + &xy + &reference.y, precision);
+            let xf = x.to_f64().unwrap_or(f64::INFINITY);
+            let yf = y.to_f64().unwrap_or(f64::INFINITY);
+            orbit.push(Point2D::new(xf, yf));
+            if xf * xf + yf * yf >= 4.0 {
+                escaped = true;
+                break;
+            }
+        }
+        Some(ReferenceOrbit {
+            reference,
+            orbit,
+            escaped,
+        })
+    }
+
+    /// Find a suitable reference orbit for the given stage.
+    ///
+    /// Candidates are checked in order, the first one not escaping within `max_iteration`
+    /// iterations is taken. If all candidates escape, the longest orbit wins.
+    /// Returns `None` if the computation has been interrupted through `stop_flag`.
+    pub fn for_stage(
+        stage_properties: &StageProperties,
+        max_iteration: u32,
+        stop_flag: &AtomicBool,
+    ) -> Option<Self> {
+        let math_area = stage_properties.area.math_area();
+        let precision = math_area.precision() + GUARD_DIGITS;
+        let center = math_area.center();
+        let radius = math_area.radius();
+        let mut best: Option<ReferenceOrbit> = None;
+        for (fx, fy) in REFERENCE_CANDIDATES {
+            let candidate = Point2D::new(
+                &center.x + radius * BigDecimal::from_f64(fx).unwrap(),
+                &center.y + radius * BigDecimal::from_f64(fy).unwrap(),
+            );
+            let orbit = Self::compute(candidate, precision, max_iteration, stop_flag)?;
+            if !orbit.escaped {
+                return Some(orbit);
+            }
+            if best.as_ref().is_none_or(|b| b.len() < orbit.len()) {
+                best = Some(orbit);
+            }
+        }
+        best
+    }
+
+    /// Return the reference point of this orbit
+    pub fn reference(&self) -> &Point2D<BigDecimal, MathSpace> {
+        &self.reference
+    }
+
+    /// Return the number of stored orbit points, including `Z(0)`
+    pub fn len(&self) -> usize {
+        self.orbit.len()
+    }
+
+    /// Compute a pixel by difference iteration against this reference orbit.
+    ///
+    /// # Arguments
+    ///
+    /// * `dc_real` - Real part of the difference between pixel and reference point
+    /// * `dc_imag` - Imaginary part of the difference between pixel and reference point
+    /// * `max_iteration` - Maximum number of iterations to perform
+    /// * `bailout` - Escape radius of the iteration
+    ///
+    /// # Returns
+    ///
+    /// `DataPoint` with iteration count, final value, smooth iteration count
+    /// and distance estimate, exactly like the direct computation.
+    pub fn data_point_at(
+        &self,
+        dc_real: f64,
+        dc_imag: f64,
+        max_iteration: u32,
+        bailout: f64,
+    ) -> DataPoint {
+        let bailout_sqr = bailout * bailout;
+        let last = self.orbit.len() - 1;
+        let mut dx = 0.0;
+        let mut dy = 0.0;
+        let mut x = 0.0;
+        let mut y = 0.0;
+        let mut der_x = 0.0;
+        let mut der_y = 0.0;
+        let mut ref_index = 0;
+        let mut iteration = 0;
+        while iteration < max_iteration {
+            // The derivative needs the full value z(n), no perturbation involved
+            (der_x, der_y) = (
+                2.0 * (x * der_x - y * der_y) + 1.0,
+                2.0 * (x * der_y + y * der_x),
+            );
+            let z_ref = self.orbit[ref_index];
+            let tx = 2.0 * z_ref.x + dx;
+            let ty = 2.0 * z_ref.y + dy;
+            (dx, dy) = (tx * dx - ty * dy + dc_real, tx * dy + ty * dx + dc_imag);
+            ref_index += 1;
+            iteration += 1;
+            let z_ref = self.orbit[ref_index];
+            x = z_ref.x + dx;
+            y = z_ref.y + dy;
+            let z_norm = x * x + y * y;
+            if z_norm >= bailout_sqr {
+                break;
+            }
+            // Glitch detection and rebasing onto the start of the reference orbit
+            if z_norm < dx * dx + dy * dy || ref_index == last {
+                dx = x;
+                dy = y;
+                ref_index = 0;
+            }
+        }
+        let smooth = smooth_iteration(iteration, x, y, max_iteration, 2.0);
+        let distance = distance_and_normal(iteration, max_iteration, x, y, der_x, der_y);
+        DataPoint::computed(iteration, Point2D::new(x, y), smooth, distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use euclid::Size2D;
+
+    use super::*;
+    use crate::comp::mandelbrot_engine::{ComputationStrategy, EngineState, MandelbrotEngine};
+    use crate::comp::math_area::{MathArea, RasteredMathArea};
+    use crate::storage::computation::comp_storage::CompStorage;
+    use crate::storage::image_comp_properties::{
+        ImageCompProperties, StageProperties, StageState, Supersampling,
+    };
+    use crate::storage::param_presets::FractalType;
+
+    /// Direct f64 iteration as comparison base
+    fn direct(c_real: f64, c_imag: f64, max_iteration: u32) -> u32 {
+        let (mut x, mut y, mut iteration) = (0.0f64, 0.0f64, 0);
+        while x * x + y * y < 4.0 && iteration < max_iteration {
+            (x, y) = (x * x - y * y + c_real, 2.0 * x * y + c_imag);
+            iteration += 1;
+        }
+        iteration
+    }
+
+    #[test]
+    fn matches_direct_computation() {
+        let stop_flag = AtomicBool::new(false);
+        let reference = Point2D::new(
+            BigDecimal::from_str("-0.75").unwrap(),
+            BigDecimal::from_str("0.1").unwrap(),
+        );
+        let orbit = ReferenceOrbit::compute(reference, 30, 500, &stop_flag).unwrap();
+        for (dx, dy) in [(0.0, 0.0), (0.01, 0.0), (-0.003, 0.02), (0.05, -0.05)] {
+            let expected = direct(-0.75 + dx, 0.1 + dy, 500);
+            let actual = orbit.data_point_at(dx, dy, 500, 2.0).iteration_count;
+            assert!(
+                expected.abs_diff(actual) <= 1,
+                "({dx},{dy}): {expected} vs {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn deep_zoom_renders_through_engine() {
+        const MAX_ITERATION: u32 = 5000;
+        // The Misiurewicz point i shows structure at every scale
+        let area = MathArea::from_str("0", "1", "0.000000000000001", "1").unwrap();
+        let properties = ImageCompProperties::new(
+            StageProperties::new(RasteredMathArea::new(area, Size2D::new(32, 24))),
+            MAX_ITERATION,
+            FractalType::Mandelbrot,
+            2.0,
+            Supersampling::Off,
+        );
+        let storage = Arc::new(CompStorage::new(properties));
+        let engine = MandelbrotEngine::new(&storage, ComputationStrategy::Shuffled);
+        engine.start();
+        assert_eq!(EngineState::Finished, engine.wait());
+        assert_eq!(StageState::Completed, storage.stage.get_state());
+
+        let stop_flag = AtomicBool::new(false);
+        let stage_properties = &storage.properties.stage_properties;
+        let mut counts = Vec::new();
+        for (x, y) in [(0, 0), (31, 0), (16, 12), (5, 17), (31, 23)] {
+            // Each pixel as reference of its own orbit is iterated with full precision
+            let pixel = Point2D::new(stage_properties.x(x), stage_properties.y(y));
+            let orbit = ReferenceOrbit::compute(pixel, 40, MAX_ITERATION, &stop_flag).unwrap();
+            let expected = orbit.len() as u32 - 1;
+            let actual = storage
+                .stage
+                .get(x as u32, y as u32)
+                .unwrap()
+                .iteration_count;
+            assert!(
+                expected.abs_diff(actual) <= 1,
+                "({x},{y}): {expected} vs {actual}"
+            );
+            counts.push(actual);
+        }
+        // Pixels are closer than the f64 resolution at 1.0i, yet the computed counts differ
+        assert!(counts.iter().any(|count| *count != counts[0]));
+    }
+
+    #[test]
+    fn escaping_reference_is_rebased() {
+        let stop_flag = AtomicBool::new(false);
+        let reference = Point2D::new(
+            BigDecimal::from_str("0.5").unwrap(),
+            BigDecimal::from_str("0.5").unwrap(),
+        );
+        let orbit = ReferenceOrbit::compute(reference, 30, 200, &stop_flag).unwrap();
+        assert!(orbit.escaped);
+        // Point well within the set, the reference escapes after a few iterations
+        let point = orbit.data_point_at(-0.6, -0.5, 200, 2.0);
+        assert_eq!(200, point.iteration_count);
+    }
+
+    #[test]
+    fn stop_flag_interrupts_reference() {
+        let stop_flag = AtomicBool::new(true);
+        let reference = Point2D::new(BigDecimal::zero(), BigDecimal::zero());
+        assert!(ReferenceOrbit::compute(reference, 30, 5000, &stop_flag).is_none());
+    }
+}
+
+// end of file
@@ -11,20 +11,32 @@
 //! The application uses a dual-storage architecture where CompStorage handles
 //! parallel computation access while VizStorage manages sequential visualization.
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::Receiver;
 use std::time::{Duration, Instant};
 
 use euclid::Size2D;
 use iced::Point;
 use iced::widget::canvas::Cache;
 
-use crate::comp::mandelbrot_engine::MandelbrotEngine;
+use crate::batch::animation::{AnimationProgress, Easing};
+use crate::comp::mandelbrot_engine::{ComputationStrategy, MandelbrotEngine};
 use crate::comp::math_area::MathArea;
 use crate::storage::computation::comp_storage::CompStorage;
 use crate::storage::coord_spaces::StageSpace;
-use crate::storage::param_description::ParamDescription;
-use crate::storage::param_presets::ParamPreset;
+use crate::storage::image_comp_properties::{DEFAULT_BAILOUT, Supersampling};
+use crate::storage::param_description::{ParamDescription, ParamDescriptionError};
+use crate::storage::param_presets::{FractalType, ParamPreset};
+use crate::storage::visualization::coloring::base::GradientColorScheme;
+use crate::storage::visualization::coloring::cycle::ColorCycle;
+use crate::storage::visualization::coloring::interpolation::ColorInterpolation;
+use crate::storage::visualization::coloring::lighting::NormalMapLighting;
 use crate::storage::visualization::coloring::presets::{GradientColorPreset, IterationAssignment};
+use crate::storage::visualization::coloring::user_palette::{
+    NamedColorScheme, UserPalette, color_from_hex, color_to_hex,
+};
 use crate::storage::visualization::viz_storage::VizStorage;
 
 /// Mathematical configuration and computation parameters.
@@ -49,6 +61,14 @@ pub struct MathState {
     pub area: MathArea,
     /// Maximum iteration count for fractal computation
     pub max_iteration: u32,
+    /// Fractal type and formula parameters to compute
+    pub fractal_type: FractalType,
+    /// Order in which the engine computes the points
+    pub strategy: ComputationStrategy,
+    /// Escape radius of the iteration
+    pub bailout: f64,
+    /// Anti-aliasing by supersampling, used for the next started computation
+    pub supersampling: Supersampling,
 }
 
 impl Default for MathState {
@@ -58,12 +78,88 @@ impl Default for MathState {
         let default_preset = ParamPreset::preset(&ParamPreset::MandelbrotFull);
         MathState {
             pixel_size: Size2D::new(800, 600),
-            area: default_preset.math_area(),
+            area: default_preset
+                .math_area()
+                .expect("built-in presets have valid areas"),
             max_iteration: default_preset.max_iteration,
+            fractal_type: default_preset.fractal_type,
+            strategy: ComputationStrategy::Shuffled,
+            bailout: DEFAULT_BAILOUT,
+            supersampling: Supersampling::Off,
         }
     }
 }
 
+/// Parameter of a fractal type which is entered in a text input of its own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FractalParameter {
+    /// Real part of the Julia set constant c
+    JuliaReal,
+    /// Imaginary part of the Julia set constant c
+    JuliaImag,
+    /// Exponent d of the Multibrot formula
+    MultibrotExponent,
+}
+
+impl FractalParameter {
+    /// Returns the human-readable name of the parameter, e.g. for error messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::JuliaReal => "real part of c",
+            Self::JuliaImag => "imaginary part of c",
+            Self::MultibrotExponent => "exponent",
+        }
+    }
+
+    /// Returns the value of this parameter in `fractal_type`, `None` if there is no such parameter.
+    pub fn value_in(&self, fractal_type: FractalType) -> Option<f64> {
+        match (self, fractal_type) {
+            (Self::JuliaReal, FractalType::Julia { c_real, .. }) => Some(c_real),
+            (Self::JuliaImag, FractalType::Julia { c_imag, .. }) => Some(c_imag),
+            (Self::MultibrotExponent, FractalType::Multibrot { exponent }) => Some(exponent),
+            _ => None,
+        }
+    }
+
+    /// Returns `fractal_type` with this parameter set to `value`.
+    ///
+    /// # Returns
+    ///
+    /// The changed fractal type, `None` if the type has no such parameter or the value
+    /// is invalid for it: not finite, or an exponent not greater than 1
+    pub fn applied_to(&self, fractal_type: FractalType, value: f64) -> Option<FractalType> {
+        if !value.is_finite() {
+            return None;
+        }
+        match (self, fractal_type) {
+            (Self::JuliaReal, FractalType::Julia { c_imag, .. }) => Some(FractalType::Julia {
+                c_real: value,
+                c_imag,
+            }),
+            (Self::JuliaImag, FractalType::Julia { c_real, .. }) => Some(FractalType::Julia {
+                c_real,
+                c_imag: value,
+            }),
+            (Self::MultibrotExponent, FractalType::Multibrot { .. }) if value > 1.0 => {
+                Some(FractalType::Multibrot { exponent: value })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Fractal parameter whose text input is being edited.
+///
+/// The text is kept as typed, so incomplete numbers like `-` or `1.` can be edited further.
+/// It is parsed and applied when the input is submitted or a computation is started.
+#[derive(Debug, Clone)]
+pub struct ParameterEdit {
+    /// The edited parameter
+    pub parameter: FractalParameter,
+    /// Text of the input as typed by the user
+    pub text: String,
+}
+
 /// Defines how the computed fractal image is rendered within the available canvas space.
 ///
 /// Different schemes handle aspect ratio mismatches between the computed image
@@ -136,6 +232,45 @@ impl std::fmt::Display for ImageRenderScheme {
     }
 }
 
+/// Entry of the color scheme selection: a built-in preset or a user-defined scheme.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorSchemeChoice {
+    /// One of the compiled-in gradient presets
+    Preset(GradientColorPreset),
+    /// A scheme of the user palette, given by its name
+    User(String),
+}
+
+impl std::fmt::Display for ColorSchemeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Preset(preset) => write!(f, "{}", preset.name()),
+            Self::User(name) => write!(f, "{} (user)", name),
+        }
+    }
+}
+
+/// Coloring options which shade the gradient colors of the escaped points.
+///
+/// The light setup is kept while the lighting is disabled, so it is restored
+/// when the lighting is enabled again.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShadingSettings {
+    /// Whether to color by the smooth iteration count instead of the integer one
+    pub smooth_coloring: bool,
+    /// Whether to shade the colors with normal-map lighting
+    pub lighting_enabled: bool,
+    /// Light setup of the normal-map lighting
+    pub lighting: NormalMapLighting,
+}
+
+impl ShadingSettings {
+    /// Returns the light setup if the lighting is enabled, `None` otherwise.
+    pub fn active_lighting(&self) -> Option<NormalMapLighting> {
+        self.lighting_enabled.then_some(self.lighting)
+    }
+}
+
 /// Visual configuration and user interface settings.
 ///
 /// Manages all aspects of how the fractal is displayed including
@@ -157,8 +292,20 @@ pub struct VizState {
     pub gradient_color_offset: u32,
     /// Function mapping iteration count to color position
     pub iteration_assignment: IterationAssignment,
+    /// Smooth coloring and normal-map lighting of the colors
+    pub shading: ShadingSettings,
     /// How the computed image fits within the display canvas
     pub render_scheme: ImageRenderScheme,
+    /// Whether the stripe offset is currently advanced by color cycling
+    pub color_cycling: bool,
+    /// Speed and direction of the color cycling
+    pub color_cycle: ColorCycle,
+    /// User-defined color scheme used instead of the preset, None to use the preset
+    pub custom_scheme: Option<NamedColorScheme>,
+    /// User-defined color schemes available for selection
+    pub user_palette: UserPalette,
+    /// Color space and hue direction the gradient colors are interpolated in
+    pub color_interpolation: ColorInterpolation,
 }
 
 impl VizState {
@@ -190,7 +337,46 @@ impl VizState {
             gradient_color_stripes,
             gradient_color_offset,
             iteration_assignment,
+            shading: ShadingSettings::default(),
             render_scheme,
+            color_cycling: false,
+            color_cycle: ColorCycle::default(),
+            custom_scheme: None,
+            user_palette: UserPalette::default(),
+            color_interpolation: ColorInterpolation::default(),
+        }
+    }
+
+    /// Returns the gradient color scheme to render with, the custom scheme if there is one.
+    pub fn color_scheme(&self) -> GradientColorScheme {
+        self.custom_scheme
+            .as_ref()
+            .map_or_else(
+                || self.gradient_color_preset.scheme(),
+                |custom| custom.scheme.clone(),
+            )
+            .with_interpolation(self.color_interpolation)
+    }
+
+    /// Returns all entries of the color scheme selection, the presets first.
+    pub fn color_scheme_choices(&self) -> Vec<ColorSchemeChoice> {
+        GradientColorPreset::all()
+            .iter()
+            .map(|preset| ColorSchemeChoice::Preset(*preset))
+            .chain(
+                self.user_palette
+                    .schemes
+                    .iter()
+                    .map(|scheme| ColorSchemeChoice::User(scheme.name.clone())),
+            )
+            .collect()
+    }
+
+    /// Returns the currently selected entry of the color scheme selection.
+    pub fn color_scheme_choice(&self) -> ColorSchemeChoice {
+        match &self.custom_scheme {
+            Some(custom) => ColorSchemeChoice::User(custom.name.clone()),
+            None => ColorSchemeChoice::Preset(self.gradient_color_preset),
         }
     }
 }
@@ -214,6 +400,85 @@ impl Default for VizState {
     }
 }
 
+/// One anchor color in the gradient editor, both values as entered by the user.
+#[derive(Debug, Clone)]
+pub struct AnchorEdit {
+    /// Position in the gradient, `0.0..1.0`
+    pub position: String,
+    /// Color as `#rrggbb` hex string
+    pub color: String,
+}
+
+/// State of the gradient editor dialog.
+///
+/// The entries are kept as typed, so incomplete input can be edited further.
+/// Entries which cannot be parsed are left out of the edited scheme.
+pub struct GradientEditorState {
+    /// Name under which the scheme is saved in the user palette
+    pub name: String,
+    /// Body color as `#rrggbb` hex string
+    pub body: String,
+    /// Anchor colors in the order shown in the editor
+    pub anchors: Vec<AnchorEdit>,
+    /// Custom scheme selected before the editor has been opened, restored on cancel
+    pub previous: Option<NamedColorScheme>,
+}
+
+impl GradientEditorState {
+    /// Creates the editor state for editing the given scheme.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the edited scheme
+    /// * `scheme` - Scheme to start with
+    /// * `previous` - Custom scheme to restore if the editor is cancelled
+    pub fn new(
+        name: String,
+        scheme: &GradientColorScheme,
+        previous: Option<NamedColorScheme>,
+    ) -> Self {
+        GradientEditorState {
+            name,
+            body: color_to_hex(scheme.body_color()),
+            anchors: scheme
+                .anchors()
+                .into_iter()
+                .map(|(position, color)| AnchorEdit {
+                    position: format!("{:.3}", position),
+                    color: color_to_hex(color),
+                })
+                .collect(),
+            previous,
+        }
+    }
+
+    /// Returns the scheme described by the valid entries of the editor.
+    pub fn scheme(&self) -> NamedColorScheme {
+        let anchors = self
+            .anchors
+            .iter()
+            .filter_map(|anchor| {
+                Some((
+                    anchor
+                        .position
+                        .trim()
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|position| position.is_finite())?,
+                    color_from_hex(&anchor.color)?,
+                ))
+            })
+            .collect();
+        NamedColorScheme {
+            name: self.name.trim().to_string(),
+            scheme: GradientColorScheme::with_positions(
+                color_from_hex(&self.body).unwrap_or(palette::Srgb::new(0.0, 0.0, 0.0)),
+                anchors,
+            ),
+        }
+    }
+}
+
 /// Manages zoom operation state during interactive zooming.
 ///
 /// Tracks accumulated scroll wheel input and timing to implement
@@ -286,6 +551,60 @@ impl ZoomState {
     }
 }
 
+/// Event sent by the background thread rendering an animation.
+pub enum AnimationEvent {
+    /// Another frame has been written
+    Progress(AnimationProgress),
+    /// Rendering has ended, with the number of written frames or an error message
+    Finished(Result<u32, String>),
+}
+
+/// An animation currently rendered in a background thread.
+pub struct AnimationRun {
+    /// Set to stop the rendering before the next frame
+    pub stop_flag: Arc<AtomicBool>,
+    /// Receives the events of the rendering thread
+    pub events: Receiver<AnimationEvent>,
+    /// Progress reported last, None before the first frame has been written
+    pub progress: Option<AnimationProgress>,
+}
+
+/// State of the animation export dialog.
+///
+/// The animation zooms from the currently shown area to the end keyframe,
+/// all frames are rendered with the current image and coloring settings.
+pub struct AnimationDialogState {
+    /// Preset selected as end keyframe, None if the end keyframe has been loaded from a file
+    pub end_preset: Option<ParamPreset>,
+    /// End keyframe of the animation
+    pub end: ParamDescription,
+    /// Number of frames to render
+    pub frame_count: u32,
+    /// Easing curve of the animation
+    pub easing: Easing,
+    /// Directory to write the frames into, None until one has been selected
+    pub directory: Option<PathBuf>,
+    /// Rendering in progress, None while the dialog is idle
+    pub run: Option<AnimationRun>,
+    /// Result of the last rendering for display
+    pub status: Option<String>,
+}
+
+impl AnimationDialogState {
+    /// Creates the dialog state with the given preset as end keyframe.
+    pub fn new(end_preset: ParamPreset) -> Self {
+        AnimationDialogState {
+            end_preset: Some(end_preset),
+            end: end_preset.preset(),
+            frame_count: 100,
+            easing: Easing::Linear,
+            directory: None,
+            run: None,
+            status: None,
+        }
+    }
+}
+
 /// Dynamic runtime state of the application.
 ///
 /// Tracks temporary state that changes during application execution,
@@ -300,6 +619,16 @@ pub struct RuntimeState {
     pub zoom: Option<ZoomState>,
     /// Flag whether the FractalCanvas is currently dragging (controlled by canvas), this should be unified with the zoom stuff
     pub canvas_is_dragging: bool,
+    /// Animation export dialog, None while the dialog is closed
+    pub animation: Option<AnimationDialogState>,
+    /// Fraction of a stripe the color cycling has advanced beyond the current offset
+    pub color_cycle_phase: f32,
+    /// Gradient editor dialog, None while the editor is closed
+    pub gradient_editor: Option<GradientEditorState>,
+    /// Message of the last failed operation, shown until the user dismisses it
+    pub error: Option<String>,
+    /// Fractal parameter input with unsubmitted text, None if no input is being edited
+    pub parameter_edit: Option<ParameterEdit>,
 }
 
 impl RuntimeState {
@@ -314,6 +643,11 @@ impl RuntimeState {
             canvas_cache: Cache::new(),
             zoom: None,
             canvas_is_dragging: false,
+            animation: None,
+            color_cycle_phase: 0.0,
+            gradient_editor: None,
+            error: None,
+            parameter_edit: None,
         }
     }
 }
@@ -355,13 +689,51 @@ pub struct AppState {
 
 impl AppState {
     /// Update the App state from some param description
-    pub fn update_from_param_description(&mut self, descr: ParamDescription) {
-        self.math.area = descr.math_area();
+    ///
+    /// The state is left unchanged if the description's math area is invalid.
+    pub fn update_from_param_description(
+        &mut self,
+        descr: ParamDescription,
+    ) -> Result<(), ParamDescriptionError> {
+        self.math.area = descr.math_area()?;
         self.math.max_iteration = descr.max_iteration;
+        self.math.fractal_type = descr.fractal_type;
+        self.runtime.parameter_edit = None;
         self.viz.iteration_assignment = descr.iteration_assignment;
         self.viz.gradient_color_preset = descr.color_preset;
         self.viz.gradient_color_stripes = descr.stripe_count;
         self.viz.gradient_color_offset = descr.stripe_offset;
+        self.viz.custom_scheme = descr.custom_scheme;
+        self.viz.color_interpolation = descr.color_interpolation;
+        self.math.bailout = descr.bailout;
+        self.viz.shading.smooth_coloring = descr.smooth_coloring;
+        self.viz.shading.lighting_enabled = descr.lighting.is_some();
+        if let Some(lighting) = descr.lighting {
+            self.viz.shading.lighting = lighting;
+        }
+        Ok(())
+    }
+
+    /// Create a param description of the current parameters, e.g. for saving them
+    pub fn to_param_description(&self, name: String) -> ParamDescription {
+        ParamDescription {
+            name,
+            fractal_type: self.math.fractal_type,
+            center_x: self.math.area.center().x.to_string(),
+            center_y: self.math.area.center().y.to_string(),
+            radius: self.math.area.radius().to_string(),
+            ratio: self.math.area.ratio().to_string(),
+            max_iteration: self.math.max_iteration,
+            iteration_assignment: self.viz.iteration_assignment,
+            color_preset: self.viz.gradient_color_preset,
+            stripe_count: self.viz.gradient_color_stripes,
+            stripe_offset: self.viz.gradient_color_offset,
+            custom_scheme: self.viz.custom_scheme.clone(),
+            color_interpolation: self.viz.color_interpolation,
+            bailout: self.math.bailout,
+            smooth_coloring: self.viz.shading.smooth_coloring,
+            lighting: self.viz.shading.active_lighting(),
+        }
     }
 }
 
@@ -370,15 +742,23 @@ impl Default for AppState {
     ///
     /// Storage components are None initially and will be created
     /// when the application starts computation.
+    /// The user palette is read from the user's palette file, a damaged file is reported.
     fn default() -> Self {
-        AppState {
+        let mut state = AppState {
             storage: None,
             comp_storage: None,
             engine: None,
             math: MathState::default(),
             viz: VizState::default(),
             runtime: RuntimeState::default(),
+        };
+        match UserPalette::load_default() {
+            Ok(palette) => state.viz.user_palette = palette,
+            Err(error) => {
+                state.runtime.error = Some(format!("Cannot load user palette: {}", error))
+            }
         }
+        state
     }
 }
 
@@ -1,32 +1,67 @@
 // For reading and opening files
+use std::path::{Path, PathBuf};
+
 use std::fs::File;
-use std::io::BufWriter;
-use std::path::Path;
+use std::io::{BufWriter, Write};
 
-use crate::gui::iced::pixels::Pixels;
+use crate::gui::iced::app::AppState;
+use crate::gui::iced::pixels::{Pixels, create_pixels_with_offset};
+use crate::storage::computation::stage_snapshot::SNAPSHOT_EXTENSION;
+use crate::storage::param_description::ParamDescription;
+use crate::storage::png_file::{write_apng, write_png_file};
 
 /// Write the given Pixels data into a PNG file with the given name
-pub fn write_image_png(name: String, pixels: Pixels) {
-    let path = Path::new(&name);
-    let file = File::create(path).unwrap();
-    let ref mut w = BufWriter::new(file);
-
-    let mut encoder = png::Encoder::new(w, pixels.size.width as u32, pixels.size.height as u32); // Width is 2 pixels and height is 1.
-    encoder.set_color(png::ColorType::Rgba);
-    encoder.set_depth(png::BitDepth::Eight);
-    //    encoder.set_source_gamma(png::ScaledFloat::from_scaled(45455)); // 1.0 / 2.2, scaled by 100000
-    encoder.set_source_gamma(png::ScaledFloat::new(1.0 / 2.2)); // 1.0 / 2.2, unscaled, but rounded
-    let source_chromaticities = png::SourceChromaticities::new(
-        // Using unscaled instantiation here
-        (0.31270, 0.32900),
-        (0.64000, 0.33000),
-        (0.30000, 0.60000),
-        (0.15000, 0.06000),
-    );
-    encoder.set_source_chromaticities(source_chromaticities);
-    let mut writer = encoder.write_header().unwrap();
-
-    writer.write_image_data(&pixels.pixels).unwrap(); // Save
+///
+/// The parameter description is embedded into the file so that the image can be re-opened.
+pub fn write_image_png(
+    name: String,
+    pixels: Pixels,
+    descr: &ParamDescription,
+) -> std::io::Result<()> {
+    write_png_file(
+        Path::new(&name),
+        pixels.size.width as u32,
+        pixels.size.height as u32,
+        &pixels.pixels,
+        descr,
+    )
+}
+
+/// Write one full color cycle of the current image into an animated PNG file with the given name
+///
+/// The cycle starts at the current stripe offset and uses the current cycle speed and direction.
+/// Nothing is written if there is no image yet.
+pub fn write_color_cycle_apng(
+    name: String,
+    app_state: &AppState,
+    descr: &ParamDescription,
+) -> std::io::Result<()> {
+    let Some(first) = create_pixels_with_offset(app_state, app_state.viz.gradient_color_offset)
+    else {
+        return Ok(());
+    };
+    let cycle = app_state.viz.color_cycle;
+    let stripe_count = app_state.viz.gradient_color_stripes;
+    let offsets = cycle.frame_offsets(app_state.viz.gradient_color_offset, stripe_count);
+    let (width, height) = (first.size.width as u32, first.size.height as u32);
+    // The first frame shows the current offset, which has been rendered already
+    let mut first = Some(first.pixels);
+    let mut w = BufWriter::new(File::create(Path::new(&name))?);
+    write_apng(
+        &mut w,
+        width,
+        height,
+        offsets.len() as u32,
+        cycle.frame_delay(stripe_count),
+        |frame| match first.take() {
+            Some(pixels) if frame == 0 => Ok(pixels),
+            _ => create_pixels_with_offset(app_state, offsets[frame as usize])
+                .map(|pixels| pixels.pixels)
+                .ok_or_else(|| std::io::Error::other("the image is no longer available")),
+        },
+        descr,
+    )?;
+    w.flush()
 }
 
 /// Show a file name selection dialog and return the selected file name if one is given, None otherwise
@@ -41,4 +76,111 @@ pub fn show_save_file_dialog() -> Option<String> {
         .and_then(|r| r.ok())
 }
 
+/// Return the name of an image or parameter file without directory and extension
+pub fn name_of(path: &Path) -> String {
+    path.file_stem()
+        .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned())
+}
+
+/// Show a file selection dialog for PNG images and return the selected path, None if cancelled
+pub fn show_open_image_dialog() -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter("PNG images", &["png"])
+        .pick_file()
+}
+
+/// Show a file selection dialog for parameter files and PNG images written by mandel.rs
+///
+/// Returns the selected path, None if cancelled.
+pub fn show_open_keyframe_dialog() -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter("mandel.rs parameters", &[PARAMS_EXTENSION, "png"])
+        .pick_file()
+}
+
+/// Show a directory selection dialog and return the selected directory, None if cancelled
+pub fn show_pick_directory_dialog() -> Option<PathBuf> {
+    rfd::FileDialog::new().pick_folder()
+}
+
+/// Show a file selection dialog for gradient files of other programs
+///
+/// Returns the selected path, None if cancelled.
+pub fn show_open_gradient_dialog() -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter("Gradients", &["map", "ggr", "ugr"])
+        .add_filter("Fractint color maps", &["map"])
+        .add_filter("GIMP gradients", &["ggr"])
+        .add_filter("Ultra Fractal gradients", &["ugr"])
+        .pick_file()
+}
+
+/// Show a file name selection dialog for GIMP gradients and return the selected path
+///
+/// Returns None if the dialog is cancelled. The `.ggr` extension is added if the name has none.
+pub fn show_save_ggr_dialog(name: &str) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter("GIMP gradients", &["ggr"])
+        .set_file_name(format!("{}.ggr", name))
+        .save_file()
+        .map(|path| {
+            if path.extension().is_none() {
+                path.with_extension("ggr")
+            } else {
+                path
+            }
+        })
+}
+
+/// Show a file selection dialog for computation snapshots and return the selected path
+///
+/// Returns None if the dialog is cancelled.
+pub fn show_open_snapshot_dialog() -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter("mandel.rs snapshots", &[SNAPSHOT_EXTENSION])
+        .pick_file()
+}
+
+/// Show a file name selection dialog for computation snapshots and return the selected path
+///
+/// Returns None if the dialog is cancelled. The snapshot extension is added if the name has none.
+pub fn show_save_snapshot_dialog() -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter("mandel.rs snapshots", &[SNAPSHOT_EXTENSION])
+        .save_file()
+        .map(|path| {
+            if path.extension().is_none() {
+                path.with_extension(SNAPSHOT_EXTENSION)
+            } else {
+                path
+            }
+        })
+}
+
+/// File name extension of parameter files
+const PARAMS_EXTENSION: &str = "toml";
+
+/// Show a file selection dialog for parameter files and return the selected path, None if cancelled
+pub fn show_open_params_dialog() -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter("mandel.rs parameters", &[PARAMS_EXTENSION])
+        .pick_file()
+}
+
+/// Show a file name selection dialog for parameter files and return the selected path
+///
+/// Returns None if the dialog is cancelled. The parameter file extension is added if the given name has no extension.
+pub fn show_save_params_dialog() -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter("mandel.rs parameters", &[PARAMS_EXTENSION])
+        .save_file()
+        .map(|path| {
+            if path.extension().is_none() {
+                path.with_extension(PARAMS_EXTENSION)
+            } else {
+                path
+            }
+        })
+}
+
 // end of file
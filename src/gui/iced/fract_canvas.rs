@@ -191,9 +191,14 @@ impl ImageInCanvas {
         canvas_bounds: Rectangle,
     ) -> Option<Self> {
         if let Some(storage) = &app_state.storage {
+            // A supersampled stage holds several samples for each shown pixel
+            let factor = storage.properties.supersampling.factor() as f32;
             Some(ImageInCanvas::init(
                 canvas_bounds,
-                Size::new(storage.stage.width() as f32, storage.stage.height() as f32),
+                Size::new(
+                    (storage.stage.width() as f32 / factor).floor(),
+                    (storage.stage.height() as f32 / factor).floor(),
+                ),
                 app_state.viz.render_scheme,
             ))
         } else {
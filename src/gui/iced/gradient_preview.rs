@@ -0,0 +1,74 @@
+//! Preview strip of a gradient color scheme for the gradient editor
+
+use iced::{
+    Color, Point, Size,
+    widget::canvas::{self, Frame},
+};
+use palette::Srgb;
+
+use crate::gui::iced::message::Message;
+use crate::storage::visualization::coloring::base::GradientColorScheme;
+
+/// Number of stripes the previewed gradient is interpolated to
+const PREVIEW_STRIPES: usize = 256;
+
+/// Canvas program drawing one full cycle of a gradient from left to right.
+///
+/// The body color is shown as a small square at the right end.
+pub struct GradientPreview {
+    /// Interpolated stripes of the gradient
+    stripes: Vec<Srgb<u8>>,
+    /// Color for points that never escape
+    body_color: Srgb<u8>,
+}
+
+impl GradientPreview {
+    /// Creates the preview of the given scheme.
+    pub fn new(scheme: &GradientColorScheme) -> Self {
+        GradientPreview {
+            stripes: scheme.stripes(PREVIEW_STRIPES),
+            body_color: scheme.body_color().into_format(),
+        }
+    }
+}
+
+/// Convert an 8 bit sRGB color into an iced color
+fn iced_color(color: Srgb<u8>) -> Color {
+    Color::from_rgb8(color.red, color.green, color.blue)
+}
+
+impl canvas::Program<Message> for GradientPreview {
+    type State = ();
+
+    fn draw(
+        &self,
+        _: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &iced::Theme,
+        bounds: iced::Rectangle,
+        _cursor: iced::mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let body_width = bounds.height;
+        let gradient_width = (bounds.width - body_width * 1.5).max(0.0);
+        if !self.stripes.is_empty() {
+            let stripe_width = gradient_width / self.stripes.len() as f32;
+            for (i, stripe) in self.stripes.iter().enumerate() {
+                frame.fill_rectangle(
+                    Point::new(i as f32 * stripe_width, 0.0),
+                    // Slightly wider than the stripe to avoid gaps from anti-aliasing
+                    Size::new(stripe_width + 0.5, bounds.height),
+                    iced_color(*stripe),
+                );
+            }
+        }
+        frame.fill_rectangle(
+            Point::new(bounds.width - body_width, 0.0),
+            Size::new(body_width, bounds.height),
+            iced_color(self.body_color),
+        );
+        vec![frame.into_geometry()]
+    }
+}
+
+// end of file
@@ -37,11 +37,18 @@ use euclid::Vector2D;
 use iced::Point;
 
 use crate::{
-    gui::iced::app::ImageRenderScheme,
+    batch::animation::Easing,
+    comp::mandelbrot_engine::ComputationStrategy,
+    gui::iced::app::{ColorSchemeChoice, FractalParameter, ImageRenderScheme},
     storage::{
         coord_spaces::StageSpace,
-        param_presets::ParamPreset,
-        visualization::coloring::presets::{GradientColorPreset, IterationAssignment},
+        image_comp_properties::Supersampling,
+        param_presets::{FractalType, ParamPreset},
+        visualization::coloring::{
+            cycle::CycleDirection,
+            interpolation::{HueDirection, InterpolationSpace},
+            presets::IterationAssignment,
+        },
     },
 };
 
@@ -76,6 +83,10 @@ pub enum Message {
     /// Triggered by: Sidebar toggle button
     ToggleSidebar,
 
+    /// Close the dialog showing the last error
+    /// Triggered by: Error dialog "OK" button click
+    ErrorDismissed,
+
     // === Mathematical Parameter Messages ===
     /// Mathematical preset selection changed
     /// Triggered by: Preset dropdown selection
@@ -86,6 +97,52 @@ pub enum Message {
     /// Triggered by: "Apply Preset" button click
     PresetClicked,
 
+    /// Load parameters from a parameter file and compute them
+    /// Triggered by: "Open" button click
+    OpenParamsClicked,
+
+    /// Save the current parameters into a parameter file
+    /// Triggered by: "Save" button click
+    SaveParamsClicked,
+
+    /// Save the data computed so far into a snapshot file, also while computing
+    /// Triggered by: Computation "Save" button click
+    SaveSnapshotClicked,
+
+    /// Load a snapshot file and continue its computation
+    /// Triggered by: Computation "Resume" button click
+    ResumeSnapshotClicked,
+
+    /// Fractal type selection changed
+    /// Triggered by: Fractal type dropdown
+    /// Data: New fractal type with its default parameters
+    FractalTypeChanged(FractalType),
+
+    /// Computation strategy selection changed
+    /// Triggered by: Strategy dropdown
+    /// Effect: Used for the next started computation
+    StrategyChanged(ComputationStrategy),
+
+    /// Bailout (escape) radius changed
+    /// Triggered by: Bailout text input
+    /// Effect: Used for the next started computation, ignored if below 2.0
+    BailoutChanged(String),
+
+    /// Supersampling mode changed
+    /// Triggered by: Anti-aliasing dropdown
+    /// Effect: Used for the next started computation
+    SupersamplingChanged(Supersampling),
+
+    /// Text of a fractal parameter input changed
+    /// Triggered by: Julia constant and Multibrot exponent text inputs
+    /// Effect: Text is kept as typed, the parameter is not changed yet
+    FractalParameterChanged(FractalParameter, String),
+
+    /// Fractal parameter input submitted
+    /// Triggered by: Enter in a Julia constant or Multibrot exponent text input
+    /// Effect: Parses and applies the edited parameter, shows an error if it is invalid
+    FractalParameterSubmitted,
+
     /// Image width in pixels changed
     /// Triggered by: Width text input
     /// Data: New width value as string
@@ -116,21 +173,155 @@ pub enum Message {
     /// Save the content of the current image to the save file
     SaveImageClicked,
 
+    /// Restore the parameters embedded into a saved image and compute them
+    /// Triggered by: "Open PNG" button click
+    OpenImageClicked,
+
     /// Update visualization with new data
     /// Triggered by: Async computation progress events
     UpdateViz,
 
+    // === Animation Export Messages ===
+    /// Open the animation export dialog
+    /// Triggered by: "Animation…" button click
+    AnimationDialogOpened,
+
+    /// Close the animation export dialog
+    /// Triggered by: "Close" button click, disabled while rendering
+    AnimationDialogClosed,
+
+    /// Preset used as end keyframe changed
+    /// Triggered by: End keyframe dropdown
+    AnimationEndPresetChanged(ParamPreset),
+
+    /// Load the end keyframe from a parameter file or PNG image
+    /// Triggered by: "Load…" button click
+    AnimationEndFileClicked,
+
+    /// Number of frames changed
+    /// Triggered by: Frames text input
+    AnimationFramesChanged(String),
+
+    /// Easing curve changed
+    /// Triggered by: Easing dropdown
+    AnimationEasingChanged(Easing),
+
+    /// Select the directory to write the frames into
+    /// Triggered by: "Folder…" button click
+    AnimationDirectoryClicked,
+
+    /// Start rendering the animation in a background thread
+    /// Triggered by: "Render" button click
+    AnimationStartClicked,
+
+    /// Stop rendering the animation after the current frame
+    /// Triggered by: "Stop" button click in the dialog
+    AnimationStopClicked,
+
+    /// Process progress events of the animation rendering
+    /// Triggered by: Scheduled polling while rendering
+    UpdateAnimation,
+
     // === Visual Configuration Messages ===
     /// Color gradient scheme changed
     /// Triggered by: Color scheme dropdown
-    /// Data: New color preset (Sunrise, Ocean, etc.)
-    ColorSchemeChanged(GradientColorPreset),
+    /// Data: New color preset (Sunrise, Ocean, etc.) or user-defined scheme
+    ColorSchemeChanged(ColorSchemeChoice),
+
+    /// Import the gradients of a Fractint, GIMP or Ultra Fractal file into the user palette
+    /// Triggered by: "Import…" button beside the color scheme dropdown
+    GradientImportClicked,
+
+    // === Gradient Editor Messages ===
+    /// Open the gradient editor with the current color scheme
+    /// Triggered by: "Edit…" button beside the color scheme dropdown
+    GradientEditorOpened,
+
+    /// Close the gradient editor and restore the previous color scheme
+    /// Triggered by: "Cancel" button click
+    GradientEditorCancelled,
+
+    /// Store the edited scheme in the user palette and keep it selected
+    /// Triggered by: "Save" button click
+    GradientEditorSaved,
+
+    /// Remove the scheme of the edited name from the user palette
+    /// Triggered by: "Delete" button click
+    GradientEditorDeleted,
+
+    /// Write the edited scheme into a GIMP gradient file
+    /// Triggered by: "Export GGR…" button click
+    GradientExportClicked,
+
+    /// Name of the edited scheme changed
+    /// Triggered by: Name text input
+    GradientNameChanged(String),
+
+    /// Body color changed
+    /// Triggered by: Body color text input (hex)
+    GradientBodyChanged(String),
+
+    /// Color of an anchor changed
+    /// Data: (anchor index, hex color as typed)
+    GradientAnchorColorChanged(usize, String),
+
+    /// Position of an anchor changed
+    /// Data: (anchor index, position as typed)
+    GradientAnchorPositionChanged(usize, String),
+
+    /// Insert a copy of an anchor halfway to its successor
+    /// Data: Index of the copied anchor
+    GradientAnchorAdded(usize),
+
+    /// Remove an anchor, the last one is kept
+    /// Data: Index of the removed anchor
+    GradientAnchorRemoved(usize),
+
+    /// Swap the color of an anchor with its predecessor, positions stay in place
+    /// Data: Index of the moved anchor
+    GradientAnchorMovedUp(usize),
+
+    /// Spread all anchors evenly over the gradient
+    /// Triggered by: "Even" button click
+    GradientAnchorsSpread,
+
+    /// Color space of the gradient interpolation changed
+    /// Triggered by: Interpolation dropdown
+    /// Data: New color space (Linear RGB, Oklch, etc.)
+    ColorSpaceChanged(InterpolationSpace),
+
+    /// Hue direction of cylindrical color spaces changed
+    /// Triggered by: Hue direction dropdown, shown for Oklch and HSLuv
+    /// Data: New hue direction (Shorter, Longer, etc.)
+    HueDirectionChanged(HueDirection),
 
     /// Iteration-to-color mapping function changed
     /// Triggered by: Iteration assignment dropdown
     /// Data: New assignment function (Linear, Logarithmic, etc.)
     IterationAssignmentChanged(IterationAssignment),
 
+    /// Smooth coloring switched on or off
+    /// Triggered by: Smooth coloring checkbox
+    /// Effect: Canvas re-rendered with smooth or integer iteration counts
+    SmoothColoringToggled(bool),
+
+    /// Normal-map lighting switched on or off
+    /// Triggered by: 3D lighting checkbox
+    /// Effect: Canvas re-rendered with or without shading
+    LightingToggled(bool),
+
+    /// Light direction changed
+    /// Triggered by: Light angle text input (degrees)
+    LightAngleChanged(String),
+
+    /// Light height changed
+    /// Triggered by: Light height text input
+    LightHeightChanged(String),
+
+    /// Specular highlight strength changed
+    /// Triggered by: Specular text input (0 to 1)
+    LightSpecularChanged(String),
+
     /// Image rendering scheme changed
     /// Triggered by: Render scheme dropdown
     /// Data: New rendering mode (Cropped, Fitted, Centered)
@@ -142,6 +333,27 @@ pub enum Message {
     /// Offset for stripe selection changed
     RenderOffsetChanged(String),
 
+    /// Color cycling switched on or off
+    /// Triggered by: Color cycling checkbox
+    /// Effect: Stripe offset advanced periodically, no recomputation
+    ColorCyclingToggled(bool),
+
+    /// Color cycling speed changed
+    /// Triggered by: Cycle speed text input (stripes per second)
+    ColorCycleSpeedChanged(String),
+
+    /// Color cycling direction changed
+    /// Triggered by: Cycle direction dropdown
+    ColorCycleDirectionChanged(CycleDirection),
+
+    /// Advance the stripe offset of the color cycling
+    /// Triggered by: Timer subscription while color cycling is enabled
+    ColorCycleTick,
+
+    /// Save one full color cycle as animated PNG
+    /// Triggered by: "Save cycle" button click
+    SaveColorCycleClicked,
+
     /// Button clicked to copy coordinates to clipboard
     CopyCoordinatesToClipboard,
 
@@ -1,6 +1,7 @@
 pub mod app;
 mod file_save;
 pub mod fract_canvas;
+mod gradient_preview;
 mod message;
 mod overlay_canvas;
 mod pixels;
@@ -2,9 +2,10 @@ use iced::Size;
 
 use crate::{
     gui::iced::app::{AppState, ZoomState},
-    storage::{
-        data_point::DataPoint,
-        visualization::{coloring::base::GradientColors, viz_storage::VizStorage},
+    storage::visualization::{
+        coloring::base::GradientColors,
+        renderer::{GuessPolicy, Renderer},
+        viz_storage::VizStorage,
     },
 };
 
@@ -258,81 +259,13 @@ impl Pixels {
     }
 }
 
-/// Estimates pixel data from nearby computed values.
-///
-/// Uses a progressive sampling strategy to find the nearest computed
-/// pixel and use its value as an estimate. This provides better visual
-/// continuity during progressive computation.
-///
-/// # Algorithm
-///
-/// 1. Start with small sampling grid (2x2)
-/// 2. Look for computed pixels at grid intersections
-/// 3. Progressively increase grid size (4x4, 8x8, ...)
-/// 4. Return first found value marked as "guessed" quality
-///
-/// # Arguments
-///
-/// * `storage` - Visualization storage containing fractal data
-/// * `x` - Pixel X coordinate
-/// * `y` - Pixel Y coordinate
-///
-/// # Returns
-///
-/// - `Some(DataPoint)` with estimated value and `Guessed` quality
-/// - `None` if no nearby computed pixels found
-///
-/// # Usage
-///
-/// Only call this if `get_pixel()` returned `None`. Provides better
-/// visual appearance during progressive fractal computation.
-fn guess_pixel(storage: &VizStorage, x: usize, y: usize) -> Option<DataPoint> {
-    let mut modrest = 2;
-    while modrest < x || modrest < y {
-        if let Some(guesspix) = storage.stage.get(x - (x % modrest), y - (y % modrest)) {
-            return Some(guesspix.as_guessed());
-        }
-        modrest *= 2;
-    }
-    None
-}
-/// Converts fractal data point to RGBA pixel color.
-///
-/// Applies the current color scheme and iteration assignment function
-/// to transform mathematical fractal data into display colors.
-///
-/// # Arguments
-///
-/// * `storage` - Visualization storage (for max iteration reference)
-/// * `color_scheme` - Gradient color mapping system
-/// * `point` - Fractal computation result to colorize
-///
-/// # Returns
-///
-/// RGBA pixel data as `[red, green, blue, alpha]` bytes
-///
-/// # Color Mapping Process
-///
-/// 1. Apply iteration assignment function (linear, logarithmic, etc.)
-/// 2. Map result to color gradient position
-/// 3. Extract RGBA values from gradient
-fn generate_pixel(
-    app_state: &AppState,
-    storage: &VizStorage,
-    color_scheme: &GradientColors,
-    point: &DataPoint,
-) -> [u8; 4] {
-    color_scheme.iteration_to_color(
-        point.iteration_count,
-        app_state.viz.iteration_assignment.assignment_function(),
-        storage.properties.max_iteration,
-    )
-}
 /// Generates the complete RGBA pixel buffer for canvas rendering.
 ///
 /// This is the core rendering method that converts the entire fractal
 /// computation state into a displayable pixel buffer. Handles computed,
 /// estimated, and uncomputed pixels with appropriate visual representation.
+/// The colors are produced by the shared `Renderer`, so they are identical
+/// to those of exported and headless renders.
 ///
 /// # Rendering Pipeline
 ///
@@ -359,46 +292,38 @@ fn generate_pixel(
 /// - **Estimated pixels**: Smooth approximation from neighbors
 /// - **Uncomputed pixels**: Neutral gray (128, 128, 128, 255)
 pub fn create_pixels_from_app_state(app_state: &AppState) -> Option<Pixels> {
-    if let Some(storage) = app_state.storage.as_ref() {
-        let width = storage.stage.width();
-        let height = storage.stage.height();
+    create_pixels_with_offset(app_state, app_state.viz.gradient_color_offset)
+}
 
-        // TODO: Move color_scheme to the app_state to prevent permanent recomputation
-        let color_scheme = GradientColors::new(
-            &app_state.viz.gradient_color_preset.scheme(),
-            app_state.viz.gradient_color_stripes as usize,
-            app_state.viz.gradient_color_offset as usize,
-        );
+/// Renders like `create_pixels_from_app_state()`, but with another stripe offset.
+///
+/// Used to render the frames of a color cycle.
+pub fn create_pixels_with_offset(app_state: &AppState, offset: u32) -> Option<Pixels> {
+    app_state.storage.as_ref().map(|storage| {
+        let renderer = renderer_from_app_state(app_state, storage, offset);
+        let (width, height) = renderer.output_size(&storage.stage);
+        Pixels::new(Size::new(width, height), renderer.render(&storage.stage))
+    })
+}
 
-        let mut pixels = Vec::with_capacity(width * height * 4);
-        for y in 0..height {
-            for x in 0..width {
-                if let Some(point) = storage.stage.get(x, y) {
-                    // computed points: handled as reference in the storage
-                    pixels.extend_from_slice(&generate_pixel(
-                        app_state,
-                        storage,
-                        &color_scheme,
-                        point,
-                    ));
-                } else if let Some(point) = guess_pixel(storage, x, y) {
-                    // guessed points: Have to be generated on the fly
-                    pixels.extend_from_slice(&generate_pixel(
-                        app_state,
-                        storage,
-                        &color_scheme,
-                        &point,
-                    ));
-                } else {
-                    // unknown points: A nice neutral grey…
-                    let pix = 128;
-                    pixels.extend_from_slice(&[pix, pix, pix, 255]);
-                }
-            }
-        }
-        Some(Pixels::new(Size::new(width, height), pixels))
-    } else {
-        None
-    }
+/// Creates the renderer for the visualization settings of the application state.
+///
+/// Points which are not computed yet are guessed from coarser grids for progressive display.
+fn renderer_from_app_state(app_state: &AppState, storage: &VizStorage, offset: u32) -> Renderer {
+    // TODO: Move color_scheme to the app_state to prevent permanent recomputation
+    let color_scheme = GradientColors::new(
+        &app_state.viz.color_scheme(),
+        app_state.viz.gradient_color_stripes as usize,
+        offset as usize,
+    );
+    Renderer::new(
+        color_scheme,
+        app_state.viz.iteration_assignment,
+        app_state.viz.shading.smooth_coloring,
+        app_state.viz.shading.active_lighting(),
+        GuessPolicy::CoarserGrid,
+        storage.properties.max_iteration,
+        storage.properties.supersampling,
+    )
 }
 // end of file
@@ -17,6 +17,11 @@
 //! has stopped. This enables the "zoom with timeout" interaction pattern
 //! where accumulated scroll events are committed after a pause.
 //!
+//! ## Color Cycling
+//! While color cycling is enabled, a timer periodically advances the stripe
+//! offset. It runs in addition to the other subscriptions, as cycling goes on
+//! during computation and navigation.
+//!
 //! # Architecture
 //!
 //! ```text
@@ -43,6 +48,9 @@ use std::time::Duration;
 use crate::gui::iced::app::AppState;
 use crate::gui::iced::message::Message;
 
+/// Interval between two steps of the color cycling
+pub const COLOR_CYCLE_TICK: Duration = Duration::from_millis(40);
+
 /// Creates the appropriate subscription based on current application state.
 ///
 /// Analyzes the application state to determine which async operations are
@@ -66,6 +74,9 @@ use crate::gui::iced::message::Message;
 /// - **Zoom timer subscription**: Periodic `ZoomEndCheck` messages (20Hz)
 /// - **No subscription**: When no background operations are needed
 ///
+/// Each of them is combined with the color cycling timer, periodic
+/// `ColorCycleTick` messages (25Hz), while color cycling is enabled.
+///
 /// # Subscription Lifecycle
 ///
 /// Subscriptions are automatically created/destroyed as state changes:
@@ -79,7 +90,7 @@ use crate::gui::iced::message::Message;
 /// - **Zoom timer**: Minimal CPU (50ms sleep cycles)
 /// - **None**: Zero overhead
 pub fn subscription(state: &AppState) -> iced::Subscription<Message> {
-    if state.viz.auto_start_computation {
+    let main = if state.viz.auto_start_computation {
         // Auto-computation: Trigger immediate computation startup
         // This subscription sends a single message and then terminates
         iced::Subscription::run(|| {
@@ -101,6 +112,20 @@ pub fn subscription(state: &AppState) -> iced::Subscription<Message> {
     } else {
         // No active subscription: Default state with no background operations
         iced::Subscription::none()
+    };
+    if state.viz.color_cycling {
+        // Color cycling: Periodic offset advance, independent of everything else
+        let cycling = iced::Subscription::run(|| {
+            async_stream::stream! {
+                loop {
+                    tokio::time::sleep(COLOR_CYCLE_TICK).await;
+                    yield Message::ColorCycleTick;
+                }
+            }
+        });
+        iced::Subscription::batch([main, cycling])
+    } else {
+        main
     }
 }
 
@@ -57,17 +57,33 @@
 //! - **Input Validation**: Parse and validate user-entered parameters
 //! - **State Consistency**: Ensure valid state transitions
 //! - **Fallback Behavior**: Graceful handling of invalid operations
+//! - **Error Reporting**: Failed file operations are shown in a dialog until dismissed
 
+use crate::batch::animation::{Animation, render_animation};
+use crate::batch::render::{ImageSettings, load_param_file};
 use crate::comp::mandelbrot_engine::{EngineState, MandelbrotEngine};
 use crate::comp::math_area::RasteredMathArea;
-use crate::gui::iced::app::{AppState, ZoomState};
+use crate::gui::iced::app::{
+    AnchorEdit, AnimationDialogState, AnimationEvent, AnimationRun, AppState, ColorSchemeChoice,
+    GradientEditorState, ParameterEdit, ZoomState,
+};
 use crate::gui::iced::message::Message;
+use crate::gui::iced::subscription::COLOR_CYCLE_TICK;
 use crate::storage::computation::comp_storage::CompStorage;
+use crate::storage::computation::stage_snapshot::{load_snapshot, save_snapshot};
 use crate::storage::image_comp_properties::{ImageCompProperties, StageProperties};
+use crate::storage::param_description::ParamDescription;
+use crate::storage::png_file::read_param_description_file;
+use crate::storage::visualization::coloring::cycle::ColorCycle;
+use crate::storage::visualization::coloring::gradient_files::{export_ggr, import_file};
+use crate::storage::visualization::coloring::lighting::NormalMapLighting;
 use crate::storage::visualization::viz_storage::{EventProcessResult, VizStorage};
 use euclid::{Point2D, Size2D};
 use iced::{Task, clipboard};
+use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::TryRecvError;
 use std::time::Duration;
 
 /// Core state update function implementing Iced's message-driven architecture.
@@ -131,12 +147,105 @@ use std::time::Duration;
 pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
     match message {
         Message::ToggleSidebar => state.viz.sidebar_visible = !state.viz.sidebar_visible,
+        Message::ErrorDismissed => state.runtime.error = None,
         Message::PresetChanged(value) => state.viz.math_preset = value,
         Message::PresetClicked => {
-            state.update_from_param_description(state.viz.math_preset.preset());
-            // Auto-trigger computation with preset parameters
-            return Task::perform(async {}, |_| Message::ComputeClicked);
+            if let Err(error) = state.update_from_param_description(state.viz.math_preset.preset())
+            {
+                state.runtime.error = Some(format!("Cannot apply preset: {}", error));
+            } else {
+                // Auto-trigger computation with preset parameters
+                return Task::perform(async {}, |_| Message::ComputeClicked);
+            }
+        }
+        Message::OpenParamsClicked => {
+            if let Some(path) = super::file_save::show_open_params_dialog() {
+                match ParamDescription::load(&path)
+                    .and_then(|descr| state.update_from_param_description(descr))
+                {
+                    // Auto-trigger computation with loaded parameters
+                    Ok(()) => return Task::perform(async {}, |_| Message::ComputeClicked),
+                    Err(error) => {
+                        state.runtime.error =
+                            Some(format!("Cannot open {}: {}", path.display(), error))
+                    }
+                }
+            }
+        }
+        Message::SaveParamsClicked => {
+            if let Some(path) = super::file_save::show_save_params_dialog() {
+                let name = super::file_save::name_of(&path);
+                if let Err(error) = state.to_param_description(name).save(&path) {
+                    state.runtime.error =
+                        Some(format!("Cannot save {}: {}", path.display(), error));
+                }
+            }
+        }
+        Message::SaveSnapshotClicked => {
+            if let Some(comp_storage) = &state.comp_storage
+                && let Some(path) = super::file_save::show_save_snapshot_dialog()
+                && let Err(error) = save_snapshot(comp_storage, &path)
+            {
+                state.runtime.error = Some(format!("Cannot save {}: {}", path.display(), error));
+            }
+        }
+        Message::ResumeSnapshotClicked => {
+            if let Some(path) = super::file_save::show_open_snapshot_dialog() {
+                match load_snapshot(&path) {
+                    Ok(comp_storage) => {
+                        if let Some(engine) = &state.engine {
+                            engine.stop();
+                        }
+                        // Take over the parameters of the snapshot so that they are shown and saved
+                        let properties = &comp_storage.original_properties;
+                        let area = &properties.stage_properties.orig_area;
+                        state.math.area = area.math_area().clone();
+                        state.math.pixel_size = *area.size();
+                        state.math.max_iteration = properties.max_iteration;
+                        state.math.fractal_type = properties.fractal_type;
+                        state.runtime.parameter_edit = None;
+                        state.math.bailout = properties.bailout;
+                        state.math.supersampling = properties.supersampling;
+
+                        // Continue the computation, the engine skips all computed points
+                        state.comp_storage = Some(Arc::new(comp_storage));
+                        state.engine = Some(MandelbrotEngine::new(
+                            state.comp_storage.as_ref().unwrap(),
+                            state.math.strategy,
+                        ));
+                        state.storage = Some(VizStorage::new(state.comp_storage.as_ref().unwrap()));
+                        state.engine.as_ref().unwrap().start();
+                        state.runtime.canvas_cache.clear();
+                        state.runtime.computing = true;
+                        return Task::perform(async {}, |_| Message::UpdateViz);
+                    }
+                    Err(error) => {
+                        state.runtime.error =
+                            Some(format!("Cannot resume {}: {}", path.display(), error))
+                    }
+                }
+            }
         }
+        Message::FractalTypeChanged(value) => {
+            // Keep the current parameters if only the same type has been selected again
+            if !state.math.fractal_type.same_kind(&value) {
+                state.math.fractal_type = value;
+                state.runtime.parameter_edit = None;
+            }
+        }
+        Message::StrategyChanged(value) => state.math.strategy = value,
+        Message::BailoutChanged(value) => {
+            if let Ok(bailout) = value.parse::<f64>()
+                && bailout >= 2.0
+            {
+                state.math.bailout = bailout;
+            }
+        }
+        Message::SupersamplingChanged(value) => state.math.supersampling = value,
+        Message::FractalParameterChanged(parameter, text) => {
+            state.runtime.parameter_edit = Some(ParameterEdit { parameter, text });
+        }
+        Message::FractalParameterSubmitted => apply_parameter_edit(state),
         Message::WidthChanged(width) => {
             if let Ok(width) = width.parse::<u32>() {
                 state.math.pixel_size = Size2D::new(width, state.math.pixel_size.height);
@@ -169,7 +278,10 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
 
                 // Rebuild complete computation pipeline with new coordinates
                 state.comp_storage = Some(Arc::new(new_storage));
-                state.engine = Some(MandelbrotEngine::new(&state.comp_storage.as_ref().unwrap()));
+                state.engine = Some(MandelbrotEngine::new(
+                    state.comp_storage.as_ref().unwrap(),
+                    state.math.strategy,
+                ));
                 state.storage = Some(VizStorage::new(state.comp_storage.as_ref().unwrap()));
 
                 // Start computation and schedule visualization updates
@@ -182,13 +294,36 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
             if let Some(savename) = super::file_save::show_save_file_dialog()
                 && let Some(rawpixels) = super::pixels::create_pixels_from_app_state(&state)
             {
-                super::file_save::write_image_png(savename, rawpixels);
+                let descr =
+                    state.to_param_description(super::file_save::name_of(Path::new(&savename)));
+                if let Err(error) =
+                    super::file_save::write_image_png(savename.clone(), rawpixels, &descr)
+                {
+                    state.runtime.error = Some(format!("Cannot save {}: {}", savename, error));
+                }
+            }
+        }
+        Message::OpenImageClicked => {
+            if let Some(path) = super::file_save::show_open_image_dialog() {
+                match read_param_description_file(&path)
+                    .and_then(|descr| state.update_from_param_description(descr))
+                {
+                    // Auto-trigger computation with restored parameters
+                    Ok(()) => return Task::perform(async {}, |_| Message::ComputeClicked),
+                    Err(error) => {
+                        state.runtime.error =
+                            Some(format!("Cannot open {}: {}", path.display(), error))
+                    }
+                }
             }
         }
         Message::ComputeClicked => {
             // Disable auto-computation to prevent loops
             state.viz.auto_start_computation = false;
 
+            // Take over a parameter which has been typed but not submitted
+            apply_parameter_edit(state);
+
             // Stop any existing computation to prevent resource conflicts
             if let Some(engine) = &state.engine {
                 engine.stop();
@@ -202,13 +337,19 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
                     state.math.pixel_size.clone(),
                 )),
                 state.math.max_iteration,
+                state.math.fractal_type,
+                state.math.bailout,
+                state.math.supersampling,
             );
 
             // Initialize complete computation pipeline:
             // 1. CompStorage: Parallel-access computation data
             state.comp_storage = Some(Arc::new(CompStorage::new(comp_props)));
             // 2. MandelbrotEngine: Computation thread management
-            state.engine = Some(MandelbrotEngine::new(&state.comp_storage.as_ref().unwrap()));
+            state.engine = Some(MandelbrotEngine::new(
+                state.comp_storage.as_ref().unwrap(),
+                state.math.strategy,
+            ));
             // 3. VizStorage: Sequential-access visualization data
             state.storage = Some(VizStorage::new(&state.comp_storage.as_ref().unwrap()));
 
@@ -255,6 +396,151 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
                 }
             }
         }
+        Message::AnimationDialogOpened => {
+            if state.runtime.animation.is_none() {
+                state.runtime.animation = Some(AnimationDialogState::new(state.viz.math_preset));
+            }
+        }
+        Message::AnimationDialogClosed => {
+            // The dialog stays open while an animation is rendered
+            if state
+                .runtime
+                .animation
+                .as_ref()
+                .is_some_and(|dialog| dialog.run.is_none())
+            {
+                state.runtime.animation = None;
+            }
+        }
+        Message::AnimationEndPresetChanged(value) => {
+            if let Some(dialog) = &mut state.runtime.animation {
+                dialog.end_preset = Some(value);
+                dialog.end = value.preset();
+            }
+        }
+        Message::AnimationEndFileClicked => {
+            if let Some(dialog) = &mut state.runtime.animation
+                && let Some(path) = super::file_save::show_open_keyframe_dialog()
+            {
+                match load_param_file(&path) {
+                    Ok(descr) => {
+                        dialog.end_preset = None;
+                        dialog.end = descr;
+                    }
+                    Err(error) => {
+                        dialog.status = Some(format!("Cannot open {}: {}", path.display(), error))
+                    }
+                }
+            }
+        }
+        Message::AnimationFramesChanged(value) => {
+            if let Some(dialog) = &mut state.runtime.animation
+                && let Ok(frame_count) = value.parse::<u32>()
+            {
+                dialog.frame_count = frame_count;
+            }
+        }
+        Message::AnimationEasingChanged(value) => {
+            if let Some(dialog) = &mut state.runtime.animation {
+                dialog.easing = value;
+            }
+        }
+        Message::AnimationDirectoryClicked => {
+            if let Some(dialog) = &mut state.runtime.animation
+                && let Some(directory) = super::file_save::show_pick_directory_dialog()
+            {
+                dialog.directory = Some(directory);
+            }
+        }
+        Message::AnimationStartClicked => {
+            // Animate from the current parameters to the end keyframe
+            let start = state.to_param_description("zoom".to_string());
+            let settings = ImageSettings {
+                size: state.math.pixel_size,
+                strategy: state.math.strategy,
+                supersampling: state.math.supersampling,
+            };
+            if let Some(dialog) = &mut state.runtime.animation
+                && dialog.run.is_none()
+                && let Some(directory) = dialog.directory.clone()
+            {
+                let animation = match Animation::new(
+                    vec![start, dialog.end.clone()],
+                    dialog.frame_count,
+                    dialog.easing,
+                ) {
+                    Ok(animation) => animation,
+                    Err(error) => {
+                        dialog.status = Some(format!("Cannot animate: {}", error));
+                        return Task::none();
+                    }
+                };
+
+                // Render in a background thread which reports via a channel
+                let stop_flag = Arc::new(AtomicBool::new(false));
+                let (sender, events) = std::sync::mpsc::channel();
+                let thread_stop_flag = stop_flag.clone();
+                std::thread::spawn(move || {
+                    let result = render_animation(
+                        &animation,
+                        &settings,
+                        &directory,
+                        "frame",
+                        &thread_stop_flag,
+                        |progress| {
+                            let _ = sender.send(AnimationEvent::Progress(progress));
+                        },
+                    )
+                    .map_err(|error| error.to_string());
+                    let _ = sender.send(AnimationEvent::Finished(result));
+                });
+                dialog.run = Some(AnimationRun {
+                    stop_flag,
+                    events,
+                    progress: None,
+                });
+                dialog.status = None;
+                return Task::perform(async {}, |_| Message::UpdateAnimation);
+            }
+        }
+        Message::AnimationStopClicked => {
+            if let Some(dialog) = &state.runtime.animation
+                && let Some(run) = &dialog.run
+            {
+                run.stop_flag.store(true, Ordering::Relaxed);
+            }
+        }
+        Message::UpdateAnimation => {
+            if let Some(dialog) = &mut state.runtime.animation
+                && let Some(run) = &mut dialog.run
+            {
+                loop {
+                    match run.events.try_recv() {
+                        Ok(AnimationEvent::Progress(progress)) => run.progress = Some(progress),
+                        Ok(AnimationEvent::Finished(result)) => {
+                            dialog.status = Some(match result {
+                                Ok(frames) => format!("✓ {} frames written", frames),
+                                Err(error) => format!("Failed: {}", error),
+                            });
+                            dialog.run = None;
+                            return Task::none();
+                        }
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Disconnected) => {
+                            dialog.status = Some("Failed: rendering thread ended".to_string());
+                            dialog.run = None;
+                            return Task::none();
+                        }
+                    }
+                }
+                return Task::perform(
+                    async {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    },
+                    |_| Message::UpdateAnimation,
+                );
+            }
+        }
         Message::StopClicked => {
             if let Some(_) = state.engine {
                 state.engine.as_ref().unwrap().stop();
@@ -263,13 +549,210 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
             }
         }
         Message::ColorSchemeChanged(value) => {
-            state.viz.gradient_color_preset = value;
+            match value {
+                ColorSchemeChoice::Preset(preset) => {
+                    state.viz.gradient_color_preset = preset;
+                    state.viz.custom_scheme = None;
+                }
+                ColorSchemeChoice::User(name) => {
+                    state.viz.custom_scheme = state.viz.user_palette.find(&name).cloned();
+                }
+            }
+            state.runtime.canvas_cache.clear();
+        }
+        Message::GradientImportClicked => {
+            if let Some(path) = super::file_save::show_open_gradient_dialog() {
+                match import_file(&path) {
+                    Ok(schemes) => {
+                        // Select the first imported scheme so that the import is visible at once
+                        state.viz.custom_scheme = schemes.first().cloned();
+                        for scheme in schemes {
+                            state.viz.user_palette.insert(scheme);
+                        }
+                        if let Err(error) = state.viz.user_palette.save_default() {
+                            state.runtime.error =
+                                Some(format!("Cannot save palette file: {}", error));
+                        }
+                        state.runtime.canvas_cache.clear();
+                    }
+                    Err(error) => {
+                        state.runtime.error =
+                            Some(format!("Cannot import {}: {}", path.display(), error))
+                    }
+                }
+            }
+        }
+        Message::GradientEditorOpened => {
+            let name = match &state.viz.custom_scheme {
+                Some(custom) => custom.name.clone(),
+                None => format!("My {}", state.viz.gradient_color_preset.name()),
+            };
+            state.runtime.gradient_editor = Some(GradientEditorState::new(
+                name,
+                &state.viz.color_scheme(),
+                state.viz.custom_scheme.clone(),
+            ));
+            preview_gradient(state);
+        }
+        Message::GradientEditorCancelled => {
+            if let Some(editor) = state.runtime.gradient_editor.take() {
+                state.viz.custom_scheme = editor.previous;
+                state.runtime.canvas_cache.clear();
+            }
+        }
+        Message::GradientEditorSaved => {
+            if let Some(editor) = state.runtime.gradient_editor.take() {
+                let scheme = editor.scheme();
+                state.viz.user_palette.insert(scheme.clone());
+                if let Err(error) = state.viz.user_palette.save_default() {
+                    state.runtime.error = Some(format!("Cannot save palette file: {}", error));
+                }
+                state.viz.custom_scheme = Some(scheme);
+                state.runtime.canvas_cache.clear();
+            }
+        }
+        Message::GradientEditorDeleted => {
+            if let Some(editor) = state.runtime.gradient_editor.take() {
+                let name = editor.scheme().name;
+                if state.viz.user_palette.remove(&name)
+                    && let Err(error) = state.viz.user_palette.save_default()
+                {
+                    state.runtime.error = Some(format!("Cannot save palette file: {}", error));
+                }
+                // Fall back to the preset if the deleted scheme was selected before
+                state.viz.custom_scheme = editor.previous.filter(|previous| previous.name != name);
+                state.runtime.canvas_cache.clear();
+            }
+        }
+        Message::GradientExportClicked => {
+            if let Some(editor) = &state.runtime.gradient_editor {
+                let scheme = editor.scheme();
+                if let Some(path) = super::file_save::show_save_ggr_dialog(&scheme.name)
+                    && let Err(error) = std::fs::write(&path, export_ggr(&scheme))
+                {
+                    state.runtime.error =
+                        Some(format!("Cannot save {}: {}", path.display(), error));
+                }
+            }
+        }
+        Message::GradientNameChanged(value) => {
+            if let Some(editor) = &mut state.runtime.gradient_editor {
+                editor.name = value;
+            }
+        }
+        Message::GradientBodyChanged(value) => {
+            if let Some(editor) = &mut state.runtime.gradient_editor {
+                editor.body = value;
+                preview_gradient(state);
+            }
+        }
+        Message::GradientAnchorColorChanged(index, value) => {
+            if let Some(editor) = &mut state.runtime.gradient_editor
+                && let Some(anchor) = editor.anchors.get_mut(index)
+            {
+                anchor.color = value;
+                preview_gradient(state);
+            }
+        }
+        Message::GradientAnchorPositionChanged(index, value) => {
+            if let Some(editor) = &mut state.runtime.gradient_editor
+                && let Some(anchor) = editor.anchors.get_mut(index)
+            {
+                anchor.position = value;
+                preview_gradient(state);
+            }
+        }
+        Message::GradientAnchorAdded(index) => {
+            if let Some(editor) = &mut state.runtime.gradient_editor
+                && let Some(anchor) = editor.anchors.get(index)
+            {
+                // The new anchor goes halfway to the next one, or to the end of the gradient
+                let position_of = |anchor: Option<&AnchorEdit>, default: f32| {
+                    anchor.map_or(default, |a| a.position.trim().parse().unwrap_or(default))
+                };
+                let position = position_of(Some(anchor), 0.0);
+                let next = position_of(editor.anchors.get(index + 1), 1.0);
+                let added = AnchorEdit {
+                    position: format!("{:.3}", (position + next) / 2.0),
+                    color: anchor.color.clone(),
+                };
+                editor.anchors.insert(index + 1, added);
+                preview_gradient(state);
+            }
+        }
+        Message::GradientAnchorRemoved(index) => {
+            if let Some(editor) = &mut state.runtime.gradient_editor
+                && editor.anchors.len() > 1
+                && index < editor.anchors.len()
+            {
+                editor.anchors.remove(index);
+                preview_gradient(state);
+            }
+        }
+        Message::GradientAnchorMovedUp(index) => {
+            if let Some(editor) = &mut state.runtime.gradient_editor
+                && index > 0
+                && index < editor.anchors.len()
+            {
+                let color = std::mem::take(&mut editor.anchors[index].color);
+                editor.anchors[index].color =
+                    std::mem::replace(&mut editor.anchors[index - 1].color, color);
+                preview_gradient(state);
+            }
+        }
+        Message::GradientAnchorsSpread => {
+            if let Some(editor) = &mut state.runtime.gradient_editor {
+                let count = editor.anchors.len();
+                for (i, anchor) in editor.anchors.iter_mut().enumerate() {
+                    anchor.position = format!("{:.3}", i as f32 / count as f32);
+                }
+                preview_gradient(state);
+            }
+        }
+        Message::ColorSpaceChanged(value) => {
+            state.viz.color_interpolation.space = value;
+            state.runtime.canvas_cache.clear();
+        }
+        Message::HueDirectionChanged(value) => {
+            state.viz.color_interpolation.hue_direction = value;
             state.runtime.canvas_cache.clear();
         }
         Message::IterationAssignmentChanged(value) => {
             state.viz.iteration_assignment = value;
             state.runtime.canvas_cache.clear();
         }
+        Message::SmoothColoringToggled(value) => {
+            state.viz.shading.smooth_coloring = value;
+            state.runtime.canvas_cache.clear();
+        }
+        Message::LightingToggled(value) => {
+            state.viz.shading.lighting_enabled = value;
+            state.runtime.canvas_cache.clear();
+        }
+        Message::LightAngleChanged(value) => {
+            if let Ok(angle) = value.parse::<f32>() {
+                let lighting = state.viz.shading.lighting;
+                state.viz.shading.lighting =
+                    NormalMapLighting::new(angle, lighting.height, lighting.specular);
+                state.runtime.canvas_cache.clear();
+            }
+        }
+        Message::LightHeightChanged(value) => {
+            if let Ok(height) = value.parse::<f32>() {
+                let lighting = state.viz.shading.lighting;
+                state.viz.shading.lighting =
+                    NormalMapLighting::new(lighting.angle, height, lighting.specular);
+                state.runtime.canvas_cache.clear();
+            }
+        }
+        Message::LightSpecularChanged(value) => {
+            if let Ok(specular) = value.parse::<f32>() {
+                let lighting = state.viz.shading.lighting;
+                state.viz.shading.lighting =
+                    NormalMapLighting::new(lighting.angle, lighting.height, specular);
+                state.runtime.canvas_cache.clear();
+            }
+        }
         Message::RenderSchemeChanged(value) => {
             state.viz.render_scheme = value;
             state.runtime.canvas_cache.clear();
@@ -286,6 +769,46 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
                 state.runtime.canvas_cache.clear();
             }
         }
+        Message::ColorCyclingToggled(value) => {
+            state.viz.color_cycling = value;
+            state.runtime.color_cycle_phase = 0.0;
+        }
+        Message::ColorCycleSpeedChanged(value) => {
+            if let Ok(speed) = value.parse::<u32>() {
+                state.viz.color_cycle = ColorCycle::new(speed, state.viz.color_cycle.direction);
+            }
+        }
+        Message::ColorCycleDirectionChanged(value) => {
+            state.viz.color_cycle = ColorCycle::new(state.viz.color_cycle.speed, value);
+        }
+        Message::ColorCycleTick => {
+            if state.viz.color_cycling {
+                // Accumulate fractions of stripes so that slow speeds advance as well
+                state.runtime.color_cycle_phase +=
+                    state.viz.color_cycle.speed as f32 * COLOR_CYCLE_TICK.as_secs_f32();
+                let steps = state.runtime.color_cycle_phase.floor();
+                state.runtime.color_cycle_phase -= steps;
+                if steps >= 1.0 {
+                    state.viz.gradient_color_offset = state.viz.color_cycle.advance(
+                        state.viz.gradient_color_offset,
+                        steps as u32,
+                        state.viz.gradient_color_stripes,
+                    );
+                    state.runtime.canvas_cache.clear();
+                }
+            }
+        }
+        Message::SaveColorCycleClicked => {
+            if let Some(savename) = super::file_save::show_save_file_dialog() {
+                let descr =
+                    state.to_param_description(super::file_save::name_of(Path::new(&savename)));
+                if let Err(error) =
+                    super::file_save::write_color_cycle_apng(savename.clone(), state, &descr)
+                {
+                    state.runtime.error = Some(format!("Cannot save {}: {}", savename, error));
+                }
+            }
+        }
         Message::CopyCoordinatesToClipboard => {
             let rep = format!(
                 "center: ({},{}), radius: {}",
@@ -306,12 +829,10 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
 
             // Create new storage with translated coordinates
             // This preserves any computed data that's still valid after translation
-            let new_storage = state
-                .comp_storage
-                .as_ref()
-                .unwrap()
-                .as_ref()
-                .shifted_clone_by_pixels(offset);
+            // The offset is given in image pixels, the stage may hold several samples per pixel
+            let comp_storage = state.comp_storage.as_ref().unwrap().as_ref();
+            let factor = comp_storage.properties.supersampling.factor() as i32;
+            let new_storage = comp_storage.shifted_clone_by_pixels(offset * factor);
 
             // Update UI coordinate display to reflect new mathematical region
             state.math.area = new_storage
@@ -323,7 +844,10 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
 
             // Rebuild complete computation pipeline with new coordinates
             state.comp_storage = Some(Arc::new(new_storage));
-            state.engine = Some(MandelbrotEngine::new(&state.comp_storage.as_ref().unwrap()));
+            state.engine = Some(MandelbrotEngine::new(
+                state.comp_storage.as_ref().unwrap(),
+                state.math.strategy,
+            ));
             state.storage = Some(VizStorage::new(state.comp_storage.as_ref().unwrap()));
             state.runtime.canvas_is_dragging = false;
 
@@ -358,15 +882,17 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
 
                     // Create new storage with zoomed coordinates
                     // Preserves computed data that remains valid after zoom
-                    let new_storage = state
-                        .comp_storage
-                        .as_ref()
-                        .unwrap()
-                        .as_ref()
-                        .zoomed_clone_by_pixels(
-                            Point2D::new(zoom.origin.x as i32, zoom.origin.y as i32),
-                            zoom.factor,
-                        );
+                    // The origin is given in image pixels, it is moved to the center
+                    // of the pixel's samples in a supersampled stage
+                    let comp_storage = state.comp_storage.as_ref().unwrap().as_ref();
+                    let factor = comp_storage.properties.supersampling.factor() as i32;
+                    let new_storage = comp_storage.zoomed_clone_by_pixels(
+                        Point2D::new(
+                            zoom.origin.x as i32 * factor + factor / 2,
+                            zoom.origin.y as i32 * factor + factor / 2,
+                        ),
+                        zoom.factor,
+                    );
 
                     // Update UI coordinate display for new mathematical region
                     state.math.area = new_storage
@@ -378,8 +904,10 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
 
                     // Rebuild computation pipeline with new coordinates
                     state.comp_storage = Some(Arc::new(new_storage));
-                    state.engine =
-                        Some(MandelbrotEngine::new(&state.comp_storage.as_ref().unwrap()));
+                    state.engine = Some(MandelbrotEngine::new(
+                        state.comp_storage.as_ref().unwrap(),
+                        state.math.strategy,
+                    ));
                     state.storage = Some(VizStorage::new(state.comp_storage.as_ref().unwrap()));
 
                     // Start computation and schedule updates
@@ -400,4 +928,38 @@ pub fn update(state: &mut AppState, message: Message) -> Task<Message> {
     Task::none()
 }
 
+/// Parses and applies the edited fractal parameter, if any.
+///
+/// The edit is finished in any case, so the input shows the current parameter again.
+/// Text which is no valid value for the parameter is reported as error.
+fn apply_parameter_edit(state: &mut AppState) {
+    let Some(edit) = state.runtime.parameter_edit.take() else {
+        return;
+    };
+    match edit
+        .text
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .and_then(|value| edit.parameter.applied_to(state.math.fractal_type, value))
+    {
+        Some(fractal_type) => state.math.fractal_type = fractal_type,
+        None => {
+            state.runtime.error = Some(format!(
+                "Invalid {}: {}",
+                edit.parameter.name(),
+                edit.text.trim()
+            ))
+        }
+    }
+}
+
+/// Shows the scheme of the gradient editor on the canvas for a live preview.
+fn preview_gradient(state: &mut AppState) {
+    if let Some(editor) = &state.runtime.gradient_editor {
+        state.viz.custom_scheme = Some(editor.scheme());
+        state.runtime.canvas_cache.clear();
+    }
+}
+
 // end of file
@@ -60,14 +60,23 @@
 //! - **State-Driven Rendering**: Efficient re-rendering based on state changes
 //! - **Minimal Overhead**: Direct widget creation without unnecessary abstractions
 
-use crate::gui::iced::app::{AppState, ImageRenderScheme};
+use crate::batch::animation::Easing;
+use crate::comp::mandelbrot_engine::ComputationStrategy;
+use crate::gui::iced::app::{
+    AnimationDialogState, AppState, FractalParameter, GradientEditorState, ImageRenderScheme,
+};
 use crate::gui::iced::fract_canvas::FractalCanvas;
+use crate::gui::iced::gradient_preview::GradientPreview;
 use crate::gui::iced::message::Message;
 use crate::gui::iced::overlay_canvas::OverlayCanvas;
-use crate::storage::param_presets::ParamPreset;
-use crate::storage::visualization::coloring::presets::{GradientColorPreset, IterationAssignment};
+use crate::storage::image_comp_properties::Supersampling;
+use crate::storage::param_presets::{FractalType, ParamPreset};
+use crate::storage::visualization::coloring::cycle::CycleDirection;
+use crate::storage::visualization::coloring::interpolation::{HueDirection, InterpolationSpace};
+use crate::storage::visualization::coloring::presets::IterationAssignment;
 use iced::widget::{
-    Canvas, Stack, button, column, container, pick_list, progress_bar, row, text, text_input,
+    Canvas, Stack, button, center, checkbox, column, container, opaque, pick_list, progress_bar,
+    row, scrollable, text, text_input,
 };
 use iced::{Element, Length};
 
@@ -148,6 +157,132 @@ fn open_coordinates_area(state: &AppState) -> Element<'_, Message> {
     .into()
 }
 
+/// Creates the input fields for the parameters of the current fractal type.
+///
+/// Julia sets need their constant c, Multibrot sets their exponent.
+/// All other fractal types have no parameters, so nothing is shown.
+fn fractal_parameters(state: &AppState) -> Element<'_, Message> {
+    // Shows the text being edited, the current value otherwise
+    let input = |parameter: FractalParameter| {
+        let value = match &state.runtime.parameter_edit {
+            Some(edit) if edit.parameter == parameter => edit.text.clone(),
+            _ => parameter
+                .value_in(state.math.fractal_type)
+                .map_or_else(String::new, |value| value.to_string()),
+        };
+        text_input("", &value)
+            .on_input(move |text| Message::FractalParameterChanged(parameter, text))
+            .on_submit(Message::FractalParameterSubmitted)
+    };
+    match state.math.fractal_type {
+        FractalType::Julia { .. } => row![
+            text("c:"),
+            input(FractalParameter::JuliaReal).width(80),
+            text("+"),
+            input(FractalParameter::JuliaImag).width(80),
+            text("i")
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center)
+        .into(),
+        FractalType::Multibrot { .. } => row![
+            text("Exponent:"),
+            input(FractalParameter::MultibrotExponent).width(60),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center)
+        .into(),
+        _ => column![].into(),
+    }
+}
+
+/// Creates the input fields for speed and direction of the color cycling.
+///
+/// Only shown while the color cycling is enabled.
+fn color_cycle_parameters(state: &AppState) -> Element<'_, Message> {
+    if !state.viz.color_cycling {
+        return column![].into();
+    }
+    row![
+        text("Speed:"),
+        text_input("", &state.viz.color_cycle.speed.to_string())
+            .width(50)
+            .on_input(Message::ColorCycleSpeedChanged),
+        pick_list(
+            CycleDirection::all(),
+            Some(state.viz.color_cycle.direction),
+            Message::ColorCycleDirectionChanged,
+        )
+        .width(100),
+    ]
+    .spacing(6)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
+/// Creates the selection of the color space the gradient is interpolated in.
+///
+/// The hue direction is only offered for cylindrical color spaces.
+fn color_interpolation_parameters(state: &AppState) -> Element<'_, Message> {
+    let interpolation = state.viz.color_interpolation;
+    let mut parameters = row![
+        text("Interpolation:"),
+        pick_list(
+            InterpolationSpace::all(),
+            Some(interpolation.space),
+            Message::ColorSpaceChanged,
+        )
+        .width(110),
+    ]
+    .spacing(6)
+    .align_y(iced::Alignment::Center);
+    if interpolation.space.is_cylindrical() {
+        parameters = parameters.push(
+            pick_list(
+                HueDirection::all(),
+                Some(interpolation.hue_direction),
+                Message::HueDirectionChanged,
+            )
+            .width(110),
+        );
+    }
+    parameters.into()
+}
+
+/// Creates the input fields for the light setup of the 3D lighting.
+///
+/// Only shown while the lighting is enabled.
+fn lighting_parameters(state: &AppState) -> Element<'_, Message> {
+    if !state.viz.shading.lighting_enabled {
+        return column![].into();
+    }
+    let lighting = state.viz.shading.lighting;
+    column![
+        row![
+            text("Angle:"),
+            text_input("", &lighting.angle.to_string())
+                .width(50)
+                .on_input(Message::LightAngleChanged),
+            text("Height:"),
+            text_input("", &lighting.height.to_string())
+                .width(50)
+                .on_input(Message::LightHeightChanged),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+        row![
+            text("Specular:"),
+            text_input("", &lighting.specular.to_string())
+                .width(50)
+                .on_input(Message::LightSpecularChanged),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(6)
+    .into()
+}
+
 fn collapsed_coordinates_area(_state: &AppState) -> Element<'_, Message> {
     container(text("")).width(Length::Shrink).height(0).into()
 }
@@ -191,7 +326,7 @@ fn collapsed_coordinates_area(_state: &AppState) -> Element<'_, Message> {
 /// - **Appropriate Sizing**: Optimal widget dimensions for usability
 /// - **Consistent Spacing**: Uniform visual rhythm throughout
 fn open_sidebar(state: &AppState) -> Element<'_, Message> {
-    container(
+    container(scrollable(
         column![
             // === Sidebar Header with Toggle ===
             row![
@@ -239,6 +374,47 @@ fn open_sidebar(state: &AppState) -> Element<'_, Message> {
             ]
             .spacing(6)
             .align_y(iced::Alignment::Center),
+            // === Parameter Files ===
+            row![
+                text("Parameters:"),
+                button("Open").on_press_maybe(if state.runtime.computing {
+                    None
+                } else {
+                    Some(Message::OpenParamsClicked)
+                }),
+                button("Save").on_press(Message::SaveParamsClicked),
+            ]
+            .spacing(6)
+            .align_y(iced::Alignment::Center),
+            // === Snapshots of the Computation ===
+            row![
+                text("Computation:"),
+                button("Resume").on_press_maybe(if state.runtime.computing {
+                    None
+                } else {
+                    Some(Message::ResumeSnapshotClicked)
+                }),
+                button("Save").on_press_maybe(
+                    state
+                        .comp_storage
+                        .as_ref()
+                        .map(|_| Message::SaveSnapshotClicked)
+                ),
+            ]
+            .spacing(6)
+            .align_y(iced::Alignment::Center),
+            // === Fractal Formula Selection ===
+            text("Fractal:"),
+            pick_list(
+                FractalType::all(),
+                FractalType::all()
+                    .iter()
+                    .find(|t| t.same_kind(&state.math.fractal_type))
+                    .copied(),
+                Message::FractalTypeChanged,
+            )
+            .width(150),
+            fractal_parameters(state),
             // === Iteration Limit Configuration ===
             text("Max. iterations:"),
             row![
@@ -249,6 +425,35 @@ fn open_sidebar(state: &AppState) -> Element<'_, Message> {
             ]
             .spacing(6)
             .align_y(iced::Alignment::Center),
+            // === Escape Radius ===
+            row![
+                text("Bailout:"),
+                text_input("", &state.math.bailout.to_string())
+                    .width(100)
+                    .on_input(Message::BailoutChanged),
+            ]
+            .spacing(6)
+            .align_y(iced::Alignment::Center),
+            // === Anti-Aliasing ===
+            row![
+                text("Anti-aliasing:"),
+                pick_list(
+                    Supersampling::all(),
+                    Some(state.math.supersampling),
+                    Message::SupersamplingChanged,
+                )
+                .width(120),
+            ]
+            .spacing(6)
+            .align_y(iced::Alignment::Center),
+            // === Computation Strategy ===
+            text("Strategy:"),
+            pick_list(
+                ComputationStrategy::all(),
+                Some(state.math.strategy),
+                Message::StrategyChanged,
+            )
+            .width(150),
             // === Computation Control ===
             // Dynamic button text and action based on computation state
             if state.runtime.computing {
@@ -273,12 +478,23 @@ fn open_sidebar(state: &AppState) -> Element<'_, Message> {
 
             // Color gradient scheme selection
             text("Color scheme:"),
-            pick_list(
-                GradientColorPreset::all(),
-                Some(state.viz.gradient_color_preset),
-                Message::ColorSchemeChanged,
-            )
-            .width(150),
+            row![
+                pick_list(
+                    state.viz.color_scheme_choices(),
+                    Some(state.viz.color_scheme_choice()),
+                    Message::ColorSchemeChanged,
+                )
+                .width(150),
+                button("Edit…").on_press_maybe(if state.runtime.gradient_editor.is_some() {
+                    None
+                } else {
+                    Some(Message::GradientEditorOpened)
+                }),
+                button("Import…").on_press(Message::GradientImportClicked),
+            ]
+            .spacing(6)
+            .align_y(iced::Alignment::Center),
+            color_interpolation_parameters(state),
             // Mathematical iteration-to-color mapping function
             text("Iteration Mapping:"),
             pick_list(
@@ -287,6 +503,11 @@ fn open_sidebar(state: &AppState) -> Element<'_, Message> {
                 Message::IterationAssignmentChanged,
             )
             .width(150),
+            checkbox("Smooth coloring", state.viz.shading.smooth_coloring)
+                .on_toggle(Message::SmoothColoringToggled),
+            checkbox("3D lighting", state.viz.shading.lighting_enabled)
+                .on_toggle(Message::LightingToggled),
+            lighting_parameters(state),
             row![
                 text("Stripes:"),
                 text_input("", &state.viz.gradient_color_stripes.to_string())
@@ -299,19 +520,247 @@ fn open_sidebar(state: &AppState) -> Element<'_, Message> {
             ]
             .spacing(6)
             .align_y(iced::Alignment::Center),
-            button("Save PNG").on_press_maybe(if state.runtime.computing {
+            checkbox("Color cycling", state.viz.color_cycling)
+                .on_toggle(Message::ColorCyclingToggled),
+            color_cycle_parameters(state),
+            row![
+                button("Save PNG").on_press_maybe(if state.runtime.computing {
+                    None
+                } else {
+                    Some(Message::SaveImageClicked)
+                }),
+                button("Open PNG").on_press_maybe(if state.runtime.computing {
+                    None
+                } else {
+                    Some(Message::OpenImageClicked)
+                }),
+            ]
+            .spacing(6),
+            button("Save cycle as APNG").on_press_maybe(if state.runtime.computing {
+                None
+            } else {
+                Some(Message::SaveColorCycleClicked)
+            }),
+            button("Animation…").on_press_maybe(if state.runtime.animation.is_some() {
                 None
             } else {
-                Some(Message::SaveImageClicked)
+                Some(Message::AnimationDialogOpened)
             }),
         ]
         .spacing(6)
         .align_x(iced::Alignment::Start),
-    )
+    ))
     .width(Length::Shrink)
     .into()
 }
 
+/// Creates the animation export dialog.
+///
+/// The animation zooms from the current view to the selected end keyframe.
+/// While rendering, the settings are locked and a progress bar with a stop button is shown.
+///
+/// # Arguments
+///
+/// * `dialog` - State of the open dialog
+///
+/// # Returns
+///
+/// Container widget with the dialog's controls, to be shown on top of the main layout
+fn animation_dialog(dialog: &AnimationDialogState) -> Element<'_, Message> {
+    let idle = dialog.run.is_none();
+    let end_name = if dialog.end_preset.is_some() {
+        String::new()
+    } else {
+        dialog.end.name.clone()
+    };
+    column![
+        text("Zoom animation from the current view"),
+        text("End keyframe:"),
+        row![
+            pick_list(
+                ParamPreset::all(),
+                dialog.end_preset,
+                Message::AnimationEndPresetChanged,
+            )
+            .placeholder(end_name)
+            .width(200),
+            button("Load…").on_press_maybe(idle.then_some(Message::AnimationEndFileClicked)),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+        row![
+            text("Frames:"),
+            text_input("", &dialog.frame_count.to_string())
+                .width(60)
+                .on_input_maybe(idle.then_some(Message::AnimationFramesChanged)),
+            text("Easing:"),
+            pick_list(
+                Easing::all(),
+                Some(dialog.easing),
+                Message::AnimationEasingChanged,
+            )
+            .width(120),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+        row![
+            button("Folder…").on_press_maybe(idle.then_some(Message::AnimationDirectoryClicked)),
+            text(dialog.directory.as_ref().map_or_else(
+                || "No folder selected".to_string(),
+                |d| d.display().to_string()
+            )),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+        // === Progress Indication ===
+        if let Some(run) = &dialog.run {
+            let (done, count) = run
+                .progress
+                .as_ref()
+                .map_or((0, dialog.frame_count), |p| (p.frames_done, p.frame_count));
+            Element::from(
+                row![
+                    progress_bar(0.0..=count as f32, done as f32).width(150),
+                    text(format!("Frame {} of {}", done, count)),
+                ]
+                .spacing(6)
+                .align_y(iced::Alignment::Center),
+            )
+        } else {
+            Element::from(text(dialog.status.clone().unwrap_or_default()))
+        },
+        row![
+            if idle {
+                button("Render").on_press_maybe(
+                    dialog
+                        .directory
+                        .as_ref()
+                        .map(|_| Message::AnimationStartClicked),
+                )
+            } else {
+                button("Stop").on_press(Message::AnimationStopClicked)
+            },
+            button("Close").on_press_maybe(idle.then_some(Message::AnimationDialogClosed)),
+        ]
+        .spacing(6),
+    ]
+    .spacing(10)
+    .padding(20)
+    .width(420)
+    .into()
+}
+
+/// Creates the dialog showing the message of the last failed operation.
+///
+/// # Arguments
+///
+/// * `message` - Error message to show
+///
+/// # Returns
+///
+/// Container widget with the message and a button to dismiss it
+fn error_dialog(message: &str) -> Element<'_, Message> {
+    column![
+        text(message),
+        button("OK").on_press(Message::ErrorDismissed),
+    ]
+    .spacing(10)
+    .padding(20)
+    .width(420)
+    .into()
+}
+
+/// Creates the gradient editor dialog.
+///
+/// Lists the anchors with position and hex color, each with buttons to move its color up,
+/// to insert a copy after it and to remove it. All changes are previewed live, in the
+/// dialog's gradient strip as well as on the fractal canvas.
+///
+/// # Arguments
+///
+/// * `editor` - State of the open editor
+/// * `state` - Application state, needed to find out whether the scheme is already saved
+///
+/// # Returns
+///
+/// Container widget with the editor's controls, to be shown on top of the main layout
+fn gradient_editor<'a>(
+    editor: &'a GradientEditorState,
+    state: &'a AppState,
+) -> Element<'a, Message> {
+    let scheme = editor.scheme();
+    let mut anchors = column![].spacing(4);
+    for (i, anchor) in editor.anchors.iter().enumerate() {
+        let removable = editor.anchors.len() > 1;
+        anchors = anchors.push(
+            row![
+                text_input("position", &anchor.position)
+                    .width(60)
+                    .on_input(move |value| Message::GradientAnchorPositionChanged(i, value)),
+                text_input("#rrggbb", &anchor.color)
+                    .width(80)
+                    .on_input(move |value| Message::GradientAnchorColorChanged(i, value)),
+                button("↑").on_press_maybe((i > 0).then_some(Message::GradientAnchorMovedUp(i))),
+                button("+").on_press(Message::GradientAnchorAdded(i)),
+                button("−").on_press_maybe(removable.then_some(Message::GradientAnchorRemoved(i))),
+            ]
+            .spacing(6)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+    column![
+        row![
+            text("Name:"),
+            text_input("", &editor.name)
+                .width(200)
+                .on_input(Message::GradientNameChanged),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+        Canvas::new(GradientPreview::new(
+            &scheme
+                .scheme
+                .clone()
+                .with_interpolation(state.viz.color_interpolation)
+        ))
+        .width(Length::Fill)
+        .height(24),
+        row![
+            text("Body color:"),
+            text_input("#rrggbb", &editor.body)
+                .width(80)
+                .on_input(Message::GradientBodyChanged),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+        row![
+            text("Anchors (position, color):"),
+            button("Even").on_press(Message::GradientAnchorsSpread),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+        scrollable(anchors).height(Length::Shrink),
+        row![
+            button("Save")
+                .on_press_maybe((!scheme.name.is_empty()).then_some(Message::GradientEditorSaved)),
+            button("Delete").on_press_maybe(
+                state
+                    .viz
+                    .user_palette
+                    .find(&scheme.name)
+                    .map(|_| Message::GradientEditorDeleted)
+            ),
+            button("Export GGR…").on_press(Message::GradientExportClicked),
+            button("Cancel").on_press(Message::GradientEditorCancelled),
+        ]
+        .spacing(6),
+    ]
+    .spacing(10)
+    .padding(20)
+    .width(420)
+    .into()
+}
+
 /// Creates the collapsed sidebar showing only the expand button.
 ///
 /// Provides a minimal interface when the sidebar is hidden, allowing users
@@ -395,6 +844,8 @@ fn fractal(state: &AppState) -> Element<'_, Message> {
 /// - **Left Panel**: Sidebar (expanded with controls or collapsed toggle)
 /// - **Right Panel**: Fractal canvas (fills remaining space)
 /// - **Responsive**: Sidebar can be hidden to maximize canvas area
+/// - **Dialogs**: Animation export and gradient editor, shown modal on top of both panels
+/// - **Errors**: Failed operations are reported in a dialog on top of everything else
 ///
 /// # Design Features
 ///
@@ -411,7 +862,7 @@ fn fractal(state: &AppState) -> Element<'_, Message> {
 /// - **Touch-Friendly**: Appropriate spacing for various interaction methods
 pub fn view(state: &AppState) -> Element<'_, Message> {
     let spcol = if state.viz.sidebar_visible { 10 } else { 0 };
-    row![
+    let main = row![
         // Conditional sidebar: expanded controls or minimal toggle
         if state.viz.sidebar_visible {
             open_sidebar(state)
@@ -431,8 +882,28 @@ pub fn view(state: &AppState) -> Element<'_, Message> {
         .padding(spcol)
     ]
     .spacing(10)
-    .padding(10)
-    .into()
+    .padding(10);
+
+    // Dialogs are shown modal on top of everything else, errors even on top of other dialogs
+    let dialog = if let Some(dialog) = &state.runtime.animation {
+        Some(animation_dialog(dialog))
+    } else {
+        state
+            .runtime
+            .gradient_editor
+            .as_ref()
+            .map(|editor| gradient_editor(editor, state))
+    };
+    let mut layers = Stack::new().push(main);
+    for dialog in dialog
+        .into_iter()
+        .chain(state.runtime.error.as_deref().map(error_dialog))
+    {
+        layers = layers.push(opaque(center(
+            container(dialog).style(container::bordered_box),
+        )));
+    }
+    layers.into()
 }
 
 // end of file
@@ -118,6 +118,11 @@
 //! - **`visualization`**: Sequential access optimization for UI operations
 //! - **`event`**: Real-time synchronization between storage systems
 //!
+//! ## `batch` - Command-Line Operations
+//! Headless subcommands for scripted use:
+//! - **`render`**: Render parameters or a preset into a PNG file
+//! - **`animate`**: Render a zoom animation through keyframes into numbered PNG files
+//!
 //! ## `gui` - User Interface
 //! Modern GUI built with Iced framework:
 //! - **`iced/app`**: Application state and lifecycle management
@@ -148,13 +153,24 @@
 //! ```
 //!
 //! The `--release` flag is recommended for optimal fractal computation performance.
+//!
+//! Render an image without GUI with:
+//! ```bash
+//! cargo run --release -- render --preset MandelbrotFull -o mandelbrot.png
+//! ```
+//!
+//! Render a zoom animation into a directory of numbered frames with:
+//! ```bash
+//! cargo run --release -- animate --from MandelbrotFull --to MandelbrotSeahorseValley -o frames
+//! ```
 
 /// Application modules organized by architectural layer
+mod batch; // Command-line operations without GUI
 mod comp; // Computation engine and mathematical algorithms
 mod gui; // User interface and event handling
 mod storage; // Data storage and synchronization systems
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "mandel-rs")]
@@ -162,6 +178,17 @@ use clap::Parser;
 struct Args {
     #[arg(long, short = 'V')]
     version: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Subcommands running without GUI
+#[derive(Subcommand)]
+enum Command {
+    /// Render an image headless into a PNG file
+    Render(batch::render::RenderArgs),
+    /// Render a zoom animation headless into numbered PNG files
+    Animate(batch::animation::AnimateArgs),
 }
 
 /// Application entry point - launches the Iced GUI application.
@@ -173,6 +200,18 @@ fn main() -> iced::Result {
     if args.version {
         println!("{}", version);
         iced::Result::Ok(())
+    } else if let Some(Command::Render(render_args)) = args.command {
+        if let Err(error) = batch::render::run(&render_args) {
+            eprintln!("Rendering failed: {}", error);
+            std::process::exit(1);
+        }
+        iced::Result::Ok(())
+    } else if let Some(Command::Animate(animate_args)) = args.command {
+        if let Err(error) = batch::animation::run(&animate_args) {
+            eprintln!("Animation failed: {}", error);
+            std::process::exit(1);
+        }
+        iced::Result::Ok(())
     } else {
         gui::iced::app::launch(version)
     }
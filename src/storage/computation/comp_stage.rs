@@ -9,27 +9,35 @@
 //!
 //! ## Thread Safety Strategy
 //!
-//! - **Per-Pixel Locking**: Each pixel has its own `RwLock` for fine-grained concurrency
+//! - **Per-Tile Locking**: Pixel data is kept in `PointColumns`, one lock guards 64 pixels
 //! - **Multiple Readers**: Many threads can read computed pixels simultaneously
-//! - **Exclusive Writers**: Only one thread can update a specific pixel at a time
+//! - **Exclusive Writers**: Only one thread can update a specific tile at a time
+//! - **Lock-Free Checks**: `is_computed()` reads a bitset without any locking
 //! - **Event System**: Changes are broadcast to visualization thread via async channels
 //!
+//! ## Sub-Pixel Samples
+//!
+//! Adaptive anti-aliasing adds sub-pixel samples to a few edge pixels after the
+//! computation. They are kept in a sparse map next to the pixel grid, guarded by one
+//! lock, as they are written in large chunks per pixel and only for a fraction of all pixels.
+//!
 //! ## Memory Layout
 //!
 //! ```text
-//! 2D Pixel Grid          1D Memory Layout
-//! ┌────────────┐      ┌──────────────────────────────┐
-//! │ (0,0) (1,0) (2,0) │ → │ [0] [1] [2] [3] [4] [5] [6] [7] [8] │
-//! │ (0,1) (1,1) (2,1) │      │  RwLock<Option<DataPoint>>     │
-//! │ (0,2) (1,2) (2,2) │      │  for each pixel              │
-//! └────────────┘      └──────────────────────────────┘
+//! 2D Pixel Grid             1D Memory Layout
+//! ┌───────────────────┐     ┌─────────────────────────────────────┐
+//! │ (0,0) (1,0) (2,0) │  →  │ [0] [1] [2] [3] [4] [5] [6] [7] [8] │
+//! │ (0,1) (1,1) (2,1) │     │ atomic columns and bitsets,         │
+//! │ (0,2) (1,2) (2,2) │     │ one lock per 64 pixels              │
+//! └───────────────────┘     └─────────────────────────────────────┘
 //! ```
 //!
 //! # Performance Characteristics
 //!
 //! - **Scalability**: Supports massively parallel computation threads
 //! - **Cache Efficiency**: Row-major memory layout for sequential access patterns
-//! - **Low Contention**: Per-pixel locks minimize thread blocking
+//! - **Low Contention**: Short per-tile critical sections minimize thread blocking
+//! - **Compact**: 10 to 42 bytes per pixel depending on the known values, see `PointColumns`
 //! - **Event Batching**: Async event system prevents blocking on visualization updates
 //!
 //! # Usage Example
@@ -43,18 +51,25 @@
 //! let result = stage.get(100, 200);
 //! ```
 
+use std::collections::HashMap;
 use std::sync::RwLock;
 
-use euclid::{Point2D, Size2D, Vector2D};
-use tokio::sync::mpsc::UnboundedSender;
+use euclid::{Point2D, Rect, Size2D, Vector2D};
 
 use crate::storage::{
+    computation::point_columns::PointColumns,
     coord_spaces::StageSpace,
     data_point::DataPoint,
-    event::{data_point_change_event::DataPointChange, stage_event_batcher::StageEvent},
+    event::{
+        data_point_change_event::DataPointChange,
+        stage_event_batcher::{StageEvent, StageEventSender},
+    },
     image_comp_properties::StageState,
 };
 
+/// Maximum distance (in pixels) of a resampled position from an old pixel center to reuse its data
+const RESAMPLE_EPSILON: f64 = 1e-3;
+
 /// Thread-safe storage for fractal computation results.
 ///
 /// The computation stage provides concurrent access to a 2D grid of fractal
@@ -65,16 +80,17 @@ use crate::storage::{
 /// # Architecture
 ///
 /// - **Data Agnostic**: Independent of specific fractal algorithms
-/// - **Thread Safe**: Uses per-pixel RwLocks for fine-grained concurrency
+/// - **Thread Safe**: Uses per-tile locks and atomics for fine-grained concurrency
 /// - **Event Driven**: Broadcasts changes via async channels
 /// - **Memory Efficient**: Optional storage (None for uncomputed pixels)
 ///
 /// # Concurrency Model
 ///
-/// Each pixel has its own `RwLock<Option<DataPoint>>` allowing:
+/// Each tile of 64 pixels has its own lock in `PointColumns` allowing:
 /// - Multiple concurrent readers for visualization
 /// - Exclusive write access for computation threads
-/// - Non-blocking access to different pixels
+/// - Non-blocking access to pixels of different tiles
+/// - Lock-free checks whether a pixel is computed
 ///
 /// # State Management
 ///
@@ -89,12 +105,14 @@ pub struct CompStage {
     /// Dimensions of the computation stage in pixels (width × height)
     size: Size2D<usize, StageSpace>,
     /// Thread-safe storage for pixel data in row-major order
-    /// Each pixel has its own RwLock for fine-grained concurrency
-    data: Vec<RwLock<Option<DataPoint>>>,
+    /// Struct of arrays with one lock per tile of 64 pixels
+    data: PointColumns,
+    /// Additional sub-pixel samples of refined pixels, keyed by their array index
+    samples: RwLock<HashMap<usize, Vec<DataPoint>>>,
     /// Current computation state (Initialized/Evolving/Stalled/Completed)
     state: RwLock<StageState>,
-    /// Optional async channel for broadcasting data changes to visualization
-    change_sender: std::sync::Mutex<Option<UnboundedSender<StageEvent>>>,
+    /// Async channels of all subscribers for broadcasting data changes to visualization
+    change_senders: std::sync::Mutex<Vec<StageEventSender>>,
 }
 
 impl CompStage {
@@ -114,24 +132,53 @@ impl CompStage {
     ///
     /// # Memory Allocation
     ///
-    /// Pre-allocates `width * height` RwLocks for optimal performance.
-    /// Memory usage: approximately `size.area() * sizeof(RwLock<Option<DataPoint>>)`
+    /// Pre-allocates the base columns for all `width * height` pixels, about 10 bytes
+    /// per pixel. Final coordinates and distance estimates are allocated per tile of
+    /// 64 pixels once they are stored, up to about 42 bytes per pixel in total.
     ///
     /// # Thread Safety
     ///
     /// The returned stage is immediately safe for concurrent access
     /// by multiple computation threads.
     pub fn new(size: Size2D<u32, StageSpace>) -> Self {
-        let mut data = Vec::with_capacity(size.area() as usize);
-        for _ in 0..(size.area()) {
-            data.push(RwLock::new(None));
-        }
         CompStage {
             size: Size2D::new(size.width as usize, size.height as usize),
-            data,
+            data: PointColumns::new(size.area() as usize),
+            samples: RwLock::new(HashMap::new()),
             state: RwLock::new(StageState::Initialized),
-            change_sender: std::sync::Mutex::new(None),
+            change_senders: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Creates a computation stage with given content, e.g. restored from a snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Pixel dimensions of the computation stage
+    /// * `data` - Pixel data in row-major order, exactly `width * height` entries
+    /// * `samples` - Sub-pixel samples of refined pixels, keyed by their array index
+    /// * `state` - Computation state of the stage
+    ///
+    /// # Returns
+    ///
+    /// The stage, or `None` if the number of data points or a sample index does not fit the size
+    pub fn with_content(
+        size: Size2D<u32, StageSpace>,
+        data: Vec<Option<DataPoint>>,
+        samples: HashMap<usize, Vec<DataPoint>>,
+        state: StageState,
+    ) -> Option<Self> {
+        let area = size.area() as usize;
+        if data.len() != area || samples.keys().any(|index| *index >= area) {
+            return None;
         }
+        Some(CompStage {
+            size: Size2D::new(size.width as usize, size.height as usize),
+            data: PointColumns::from_points(&data),
+            samples: RwLock::new(samples),
+            state: RwLock::new(state),
+            change_senders: std::sync::Mutex::new(Vec::new()),
+        })
     }
 
     /// Returns the stage dimensions.
@@ -182,7 +229,7 @@ impl CompStage {
 
     /// Internal method to read pixel data by array index.
     ///
-    /// Acquires read lock and returns a copy of the pixel data.
+    /// Acquires the read lock of the pixel's tile and returns a copy of the pixel data.
     /// This is an internal helper to avoid code duplication.
     ///
     /// # Arguments
@@ -198,8 +245,7 @@ impl CompStage {
     /// Blocks until read lock is acquired. Multiple threads
     /// can read the same pixel simultaneously.
     fn internal_get(&self, idx: usize) -> Option<DataPoint> {
-        let guard = self.data[idx].read().unwrap();
-        *guard
+        self.data.get(idx)
     }
 
     /// Reads fractal computation data for a specific pixel.
@@ -220,7 +266,7 @@ impl CompStage {
     ///
     /// # Thread Safety
     ///
-    /// Acquires a read lock on the specific pixel. Multiple threads
+    /// Acquires a read lock on the pixel's tile. Multiple threads
     /// can read the same pixel simultaneously without blocking.
     ///
     /// # Panics
@@ -247,32 +293,63 @@ impl CompStage {
     /// # Usage
     ///
     /// Commonly used by computation algorithms to skip already-computed
-    /// pixels during incremental computation.
+    /// pixels during incremental computation. Reads only one bit without locking.
     pub fn is_computed(&self, x: u32, y: u32) -> bool {
-        self.get(x, y)
-            .is_some_and(|p| p.iteration_count_quality.is_accurate())
+        self.data.is_accurate(self.index(x, y))
     }
 
-    /// Sets the event channel for broadcasting data changes.
+    /// Adds an event channel for broadcasting data changes.
     ///
-    /// Configures an async channel to receive notifications when pixel
-    /// data or stage state changes. Setting to `None` disables events.
+    /// Any number of channels can be added, each subscriber receives all
+    /// notifications when pixel data or stage state changes.
     ///
     /// # Arguments
     ///
-    /// * `sender` - Optional async channel sender for stage events
+    /// * `sender` - Sender of a bounded stage event channel, sending never blocks
     ///
     /// # Event Types
     ///
+    /// - `StageEvent::InitialState` - First the current state
+    /// - `StageEvent::StateChange` - When the state changes
     /// - `StageEvent::ContentChange` - When pixel data is updated
-    /// - `StageEvent::StateChange` - When computation state changes
     ///
-    /// # Usage
+    /// # Joining Late
+    ///
+    /// Every change written after this call is sent to the new channel. A
+    /// subscriber which takes its snapshot of the stage _after_ adding its
+    /// channel and applies all events to it thus never misses a change.
+    /// The initial state is no state transition: clones of a stage start
+    /// `Stalled` and are computed further once the engine starts.
+    pub fn add_change_sender(&self, sender: StageEventSender) {
+        let mut senders = self.change_senders.lock().unwrap();
+        sender.send(StageEvent::InitialState(self.get_state()));
+        senders.push(sender);
+    }
+
+    /// Removes an event channel added by `add_change_sender()`.
+    ///
+    /// Channels whose receiver has gone are removed automatically.
     ///
-    /// Typically called during initialization to connect the computation
-    /// stage to the visualization system for real-time updates.
-    pub fn set_change_sender(&self, sender: Option<UnboundedSender<StageEvent>>) {
-        *self.change_sender.lock().unwrap() = sender;
+    /// # Arguments
+    ///
+    /// * `sender` - Sender of the channel to remove or any clone of it
+    pub fn remove_change_sender(&self, sender: &StageEventSender) {
+        self.change_senders
+            .lock()
+            .unwrap()
+            .retain(|s| !s.same_channel(sender));
+    }
+
+    /// Sends an event to all event channels, dropping closed channels.
+    fn send_event(&self, event: StageEvent) {
+        let mut senders = self.change_senders.lock().unwrap();
+        senders.retain(|sender| !sender.is_closed());
+        if let Some((last, others)) = senders.split_last() {
+            for sender in others {
+                sender.send(event.clone());
+            }
+            last.send(event);
+        }
     }
 
     /// Returns the current computation state.
@@ -298,8 +375,8 @@ impl CompStage {
 
     /// Sets fractal computation data for a specific pixel.
     ///
-    /// Stores computed fractal data and broadcasts a change event if an
-    /// event channel is configured. This is the primary method used by
+    /// Stores computed fractal data and broadcasts a change event to all
+    /// event channels. This is the primary method used by
     /// computation threads to store results.
     ///
     /// # Arguments
@@ -310,32 +387,104 @@ impl CompStage {
     ///
     /// # Thread Safety
     ///
-    /// Acquires an exclusive write lock on the specific pixel.
-    /// Only one thread can write to a pixel at a time, but
-    /// different threads can write to different pixels concurrently.
+    /// Acquires an exclusive write lock on the pixel's tile.
+    /// Only one thread can write to a tile at a time, but
+    /// different threads can write to different tiles concurrently.
     ///
     /// # Event Broadcasting
     ///
-    /// If an event channel is configured, sends a `ContentChange` event
+    /// Sends a `ContentChange` event to every added event channel
     /// for real-time visualization updates. Event sending is non-blocking.
     ///
     /// # Panics
     ///
     /// Panics if coordinates are outside stage bounds.
     pub fn set(&self, x: u32, y: u32, data_point: DataPoint) {
-        {
-            let mut data_write_guard = self.data[self.index(x, y)].write().unwrap();
-            *data_write_guard = Option::Some(data_point);
+        self.data.set(self.index(x, y), &data_point);
+        self.send_event(StageEvent::ContentChange(DataPointChange::new(
+            x,
+            y,
+            &data_point,
+        )));
+    }
+
+    /// Sets the same fractal data for a horizontal run of pixels.
+    ///
+    /// Used by filling algorithms which derive the data of whole areas.
+    /// Other than calling `set()` for each pixel, this broadcasts only
+    /// _one_ `ContentChange` event with a `DataPointChange::Run`.
+    ///
+    /// # Arguments
+    ///
+    /// * `x_start` - X coordinate of the first pixel of the run
+    /// * `x_end` - X coordinate after the last pixel of the run
+    /// * `y` - Pixel Y coordinate of the run
+    /// * `data_point` - Fractal data to store in all pixels of the run
+    ///
+    /// # Panics
+    ///
+    /// Panics if coordinates are outside stage bounds.
+    pub fn set_run(&self, x_start: u32, x_end: u32, y: u32, data_point: DataPoint) {
+        if x_start >= x_end {
+            return;
         }
-        if let Some(sender) = &*self.change_sender.lock().unwrap() {
-            let _ = sender.send(StageEvent::ContentChange(DataPointChange::new(
+        self.data.set_range(
+            self.index(x_start, y)..self.index(x_end - 1, y) + 1,
+            &data_point,
+        );
+        self.send_event(StageEvent::ContentChange(DataPointChange::new_run(
+            x_start,
+            x_end,
+            y,
+            &data_point,
+        )));
+    }
+
+    /// Adds sub-pixel samples to a pixel.
+    ///
+    /// Used by adaptive anti-aliasing to refine edge pixels. The pixel's own data point
+    /// remains unchanged, the renderer averages the colors of the point and all its samples.
+    /// Broadcasts one `DataPointChange::Sample` event per sample, numbered by its position
+    /// in the pixel's sample list.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Pixel X coordinate (0 to width-1)
+    /// * `y` - Pixel Y coordinate (0 to height-1)
+    /// * `samples` - Fractal data of the additional samples
+    ///
+    /// # Panics
+    ///
+    /// Panics if coordinates are outside stage bounds.
+    pub fn add_samples(&self, x: u32, y: u32, samples: &[DataPoint]) {
+        let index = self.index(x, y);
+        let first = {
+            let mut all_samples = self.samples.write().unwrap();
+            let pixel_samples = all_samples.entry(index).or_default();
+            let first = pixel_samples.len();
+            pixel_samples.extend_from_slice(samples);
+            first
+        };
+        for (position, sample) in (first..).zip(samples) {
+            self.send_event(StageEvent::ContentChange(DataPointChange::new_sample(
                 x,
                 y,
-                &data_point,
+                position as u32,
+                sample,
             )));
         }
     }
 
+    /// Checks if sub-pixel samples have been added to a pixel.
+    pub fn has_samples(&self, x: u32, y: u32) -> bool {
+        self.samples.read().unwrap().contains_key(&self.index(x, y))
+    }
+
+    /// Returns a snapshot of the sub-pixel samples of all refined pixels, keyed by array index.
+    pub fn get_full_samples(&self) -> HashMap<usize, Vec<DataPoint>> {
+        self.samples.read().unwrap().clone()
+    }
+
     /// Updates the computation state of the stage.
     ///
     /// Changes the overall computation state and broadcasts a state change
@@ -369,9 +518,7 @@ impl CompStage {
             }
         }
         if send_new_state {
-            if let Some(sender) = &*self.change_sender.lock().unwrap() {
-                let _ = sender.send(StageEvent::StateChange(new_state));
-            }
+            self.send_event(StageEvent::StateChange(new_state));
         }
     }
 
@@ -390,7 +537,7 @@ impl CompStage {
     /// # Performance
     ///
     /// This operation:
-    /// - Acquires read locks for all pixels sequentially
+    /// - Acquires read locks for all tiles sequentially
     /// - Copies all data (expensive for large images)
     /// - Releases locks immediately after copying
     ///
@@ -404,13 +551,7 @@ impl CompStage {
     /// Safe to call concurrently. Takes a consistent snapshot even
     /// if computation is ongoing during the copy operation.
     pub fn get_full_data(&self) -> Vec<Option<DataPoint>> {
-        // This functional approach is slightly less performant as it might reallocate the target Vec memory
-        //(0..self.data.len()).map(|i| self.internal_get(i)).collect()
-        let mut retval = Vec::with_capacity(self.size.area());
-        for i in 0..self.data.len() {
-            retval.push(self.internal_get(i));
-        }
-        retval
+        self.data.to_vec()
     }
 
     /// Creates a new stage with shifted data from this stage.
@@ -438,6 +579,7 @@ impl CompStage {
     /// - Same dimensions as original
     /// - Preserved data where regions overlap
     /// - `None` values for new regions requiring computation
+    /// - No sub-pixel samples, edges are refined again after the computation
     /// - State set to `Stalled` (computation not active)
     ///
     /// # Performance
@@ -476,70 +618,129 @@ impl CompStage {
             let line_width = self.size.width - (empty_line_start.max(empty_line_end));
             let first_line = empty_end_lines;
             let last_line = self.size.height - empty_start_lines;
-            let mut data = Vec::with_capacity(self.size.area());
-            for _ in 0..empty_start_lines {
-                for _ in 0..self.size.width {
-                    data.push(RwLock::new(None));
-                }
-            }
+            let mut data = PointColumns::new(self.size.area());
             for line in first_line..last_line {
-                for _ in 0..empty_line_start {
-                    data.push(RwLock::new(None));
-                }
                 let first_idx = line * self.size.width + empty_line_end;
-                let last_idx = first_idx + line_width;
-                for idx in first_idx..last_idx {
-                    data.push(RwLock::new(self.internal_get(idx)));
-                }
-                for _ in 0..empty_line_end {
-                    data.push(RwLock::new(None));
-                }
-            }
-            for _ in 0..empty_end_lines {
-                for _ in 0..self.size.width {
-                    data.push(RwLock::new(None));
+                let target_idx =
+                    (line + empty_start_lines - first_line) * self.size.width + empty_line_start;
+                for offset in 0..line_width {
+                    data.put(
+                        target_idx + offset,
+                        self.internal_get(first_idx + offset).as_ref(),
+                    );
                 }
             }
             CompStage {
                 size: self.size,
                 data,
+                samples: RwLock::new(HashMap::new()),
                 state: RwLock::new(StageState::Stalled),
-                change_sender: std::sync::Mutex::new(None),
+                change_senders: std::sync::Mutex::new(Vec::new()),
             }
         }
     }
 
-    /// Creates a new stage with zoomed data from this stage.
+    /// Creates a new stage of the given size with data resampled from this stage.
     ///
-    /// **Current Implementation**: This is a placeholder that returns an empty
-    /// stage. A complete implementation would preserve computed data that
-    /// remains visible after zooming, potentially interpolating or subsampling
-    /// existing results.
+    /// Used for zooming and for reusing the data of a differently sized image, so that
+    /// only the missing information has to be computed afterwards.
     ///
-    /// # Future Implementation Ideas
+    /// - If the position of a new pixel hits an old pixel center exactly, the old data
+    ///   point is taken unchanged.
+    /// - Otherwise, the nearest old data point is taken as `DataQuality::Guessed` estimate.
+    /// - Positions outside this stage remain `None`.
     ///
-    /// A complete zoom implementation could:
-    /// 1. **Data Preservation**: Map pixels from old to new coordinate system
-    /// 2. **Interpolation**: Estimate values for pixels between computed points
-    /// 3. **Subsampling**: Use existing high-resolution data for zoom-out
-    /// 4. **Quality Tracking**: Mark preserved data as `Derived` quality
+    /// Guessed points are not regarded as computed, so the engine recomputes them
+    /// while the visualization can already show the estimates.
     ///
     /// # Arguments
     ///
-    /// * `_origin` - Pixel coordinate that remains fixed during zoom (unused)
-    /// * `_factor` - Zoom factor >1.0=zoom in, <1.0=zoom out (unused)
+    /// * `size` - Dimensions of the new stage
+    /// * `source` - Maps a pixel of the new stage to the (fractional) pixel position
+    ///   of its center in this stage, integral values denote old pixel centers.
     ///
     /// # Returns
     ///
-    /// Currently: Empty stage of same dimensions
-    /// Future: Stage with preserved/interpolated data where possible
+    /// New `CompStage` with resampled data and state `Stalled`
+    pub fn resampled_clone_with_size(
+        &self,
+        size: Size2D<u32, StageSpace>,
+        source: impl Fn(u32, u32) -> Point2D<f64, StageSpace>,
+    ) -> Self {
+        let mut data = PointColumns::new(size.area() as usize);
+        for y in 0..size.height {
+            for x in 0..size.width {
+                let pos = source(x, y);
+                let (rx, ry) = (pos.x.round(), pos.y.round());
+                let point = if rx >= 0.0
+                    && ry >= 0.0
+                    && (rx as usize) < self.size.width
+                    && (ry as usize) < self.size.height
+                {
+                    let old = self.internal_get(ry as usize * self.size.width + rx as usize);
+                    if (pos.x - rx).abs() < RESAMPLE_EPSILON
+                        && (pos.y - ry).abs() < RESAMPLE_EPSILON
+                    {
+                        old
+                    } else {
+                        old.map(|p| p.as_guessed())
+                    }
+                } else {
+                    None
+                };
+                data.put((y * size.width + x) as usize, point.as_ref());
+            }
+        }
+        CompStage {
+            size: Size2D::new(size.width as usize, size.height as usize),
+            data,
+            samples: RwLock::new(HashMap::new()),
+            state: RwLock::new(StageState::Stalled),
+            change_senders: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Creates a new stage with the data and sub-pixel samples of a part of this stage.
+    ///
+    /// Used to drop the margin which tiles are computed with, see `batch::render`.
+    ///
+    /// # Arguments
     ///
-    /// # Status
+    /// * `rect` - Part of this stage to keep, must lie within the stage
     ///
-    /// 🚧 **TODO**: Implement intelligent data preservation for zoom operations
-    pub fn zoomed_clone(&self, _origin: Point2D<i32, StageSpace>, _factor: f32) -> Self {
-        // This is a dummy implementation always returning an empty new stage
-        Self::new(Size2D::new(self.size.width as u32, self.size.height as u32))
+    /// # Returns
+    ///
+    /// New `CompStage` of the size of `rect` with the state of this stage
+    pub fn cropped_clone(&self, rect: Rect<u32, StageSpace>) -> Self {
+        assert!(
+            rect.max_x() as usize <= self.size.width && rect.max_y() as usize <= self.size.height,
+            "crop rectangle exceeds the stage"
+        );
+        let (width, height) = (rect.size.width as usize, rect.size.height as usize);
+        let source_index = |x: usize, y: usize| {
+            (rect.origin.y as usize + y) * self.size.width + rect.origin.x as usize + x
+        };
+        let mut data = PointColumns::new(width * height);
+        let mut samples = HashMap::new();
+        let old_samples = self.samples.read().unwrap();
+        for y in 0..height {
+            for x in 0..width {
+                data.put(
+                    y * width + x,
+                    self.internal_get(source_index(x, y)).as_ref(),
+                );
+                if let Some(pixel_samples) = old_samples.get(&source_index(x, y)) {
+                    samples.insert(y * width + x, pixel_samples.clone());
+                }
+            }
+        }
+        CompStage {
+            size: Size2D::new(width, height),
+            data,
+            samples: RwLock::new(samples),
+            state: RwLock::new(self.get_state()),
+            change_senders: std::sync::Mutex::new(Vec::new()),
+        }
     }
 
     pub fn max_iteration_changed_clone(
@@ -547,18 +748,154 @@ impl CompStage {
         old_max_iteration: u32,
         new_max_iteration: u32,
     ) -> Self {
-        let mut data = Vec::with_capacity(self.size.area());
+        let mut data = PointColumns::new(self.size.area());
         for idx in 0..self.size.area() {
-            data.push(RwLock::new(self.internal_get(idx).and_then(|p| {
-                p.for_new_max_iteration(old_max_iteration, new_max_iteration)
-            })));
+            let point = self
+                .internal_get(idx)
+                .and_then(|p| p.for_new_max_iteration(old_max_iteration, new_max_iteration));
+            data.put(idx, point.as_ref());
         }
         CompStage {
             size: self.size,
             data,
+            samples: RwLock::new(HashMap::new()),
             state: RwLock::new(StageState::Stalled),
-            change_sender: std::sync::Mutex::new(None),
+            change_senders: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::data_point::DataQuality;
+    use crate::storage::event::stage_event_batcher::stage_event_channel;
+
+    fn filled_stage(size: u32) -> CompStage {
+        let stage = CompStage::new(Size2D::new(size, size));
+        for y in 0..size {
+            for x in 0..size {
+                stage.set(
+                    x,
+                    y,
+                    DataPoint::computed(y * size + x, Point2D::zero(), (y * size + x) as f32, None),
+                );
+            }
+        }
+        stage
+    }
+
+    #[test]
+    fn set_run_sends_single_event() {
+        let stage = CompStage::new(Size2D::new(10, 3));
+        let (sender, mut receiver) = stage_event_channel(16);
+        stage.add_change_sender(sender);
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(StageEvent::InitialState(StageState::Initialized))
+        ));
+        stage.set_run(2, 8, 1, DataPoint::derived(42));
+        assert!(stage.is_computed(2, 1) && stage.is_computed(7, 1));
+        assert!(!stage.is_computed(8, 1) && !stage.is_computed(1, 1));
+        match receiver.try_recv() {
+            Ok(StageEvent::ContentChange(change)) => assert_eq!(6, change.pixel_count()),
+            _ => panic!("expected a single content change"),
+        }
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn shift_keeps_distance_estimate() {
+        let stage = CompStage::new(Size2D::new(4, 4));
+        let normal = Vector2D::new(1.0, 0.0);
+        let point = DataPoint::computed(7, Point2D::new(3.0, 0.0), 6.5, Some((0.25, normal)));
+        stage.set(1, 1, point);
+        let shifted = stage.shifted_clone(Vector2D::new(1, 2));
+        let point = shifted.get(2, 3).unwrap();
+        assert_eq!(DataQuality::Computed, point.distance_estimate_quality);
+        assert_eq!(0.25, point.distance_estimate);
+        let truncated = point.for_new_max_iteration(100, 50).unwrap();
+        assert_eq!(0.25, truncated.distance_estimate);
+    }
+
+    #[test]
+    fn shift_moves_data_to_the_offset() {
+        let stage = filled_stage(70);
+        let shifted = stage.shifted_clone(Vector2D::new(-3, 2));
+        assert!(shifted.get(0, 1).is_none() && shifted.get(67, 5).is_none());
+        assert_eq!(3, shifted.get(0, 2).unwrap().iteration_count);
+        assert_eq!(67 * 70 + 69, shifted.get(66, 69).unwrap().iteration_count);
+        assert!(shifted.is_computed(66, 69) && !shifted.is_computed(66, 1));
+    }
+
+    /// Memory use and parallel set throughput of the stage's pixel storage compared with
+    /// one lock per pixel. The time includes the allocation, as a stage is usually filled once.
+    ///
+    /// Run with `cargo test --release -- --ignored --nocapture compare_stage_storage`.
+    #[test]
+    #[ignore]
+    fn compare_stage_storage() {
+        use rayon::prelude::*;
+        use std::time::Instant;
+
+        const WIDTH: u32 = 3840;
+        const HEIGHT: u32 = 2160;
+        const PIXELS_8K: usize = 7680 * 4320;
+        let area = (WIDTH * HEIGHT) as usize;
+        let point = |x: u32, y: u32| {
+            DataPoint::computed(x ^ y, Point2D::new(x as f64, y as f64), 1.5, None)
+        };
+
+        let start = Instant::now();
+        let per_pixel: Vec<RwLock<Option<DataPoint>>> =
+            (0..area).map(|_| RwLock::new(None)).collect();
+        (0..HEIGHT).into_par_iter().for_each(|y| {
+            for x in 0..WIDTH {
+                *per_pixel[(y * WIDTH + x) as usize].write().unwrap() = Some(point(x, y));
+            }
+        });
+        let per_pixel_time = start.elapsed();
+        let per_pixel_bytes = area * std::mem::size_of::<RwLock<Option<DataPoint>>>();
+        drop(per_pixel);
+
+        let start = Instant::now();
+        let columns = PointColumns::new(area);
+        (0..HEIGHT).into_par_iter().for_each(|y| {
+            for x in 0..WIDTH {
+                columns.set((y * WIDTH + x) as usize, &point(x, y));
+            }
+        });
+        let columns_time = start.elapsed();
+        let columns_bytes = columns.memory_size();
+        assert!(columns.is_accurate(area - 1));
+        drop(columns);
+
+        let start = Instant::now();
+        let derived = PointColumns::new(area);
+        (0..HEIGHT).into_par_iter().for_each(|y| {
+            for x in 0..WIDTH {
+                derived.set((y * WIDTH + x) as usize, &DataPoint::derived(x ^ y));
+            }
+        });
+        let derived_time = start.elapsed();
+        let derived_bytes = derived.memory_size();
+
+        for (name, bytes, time) in [
+            ("RwLock per pixel", per_pixel_bytes, per_pixel_time),
+            ("PointColumns", columns_bytes, columns_time),
+            ("derived only", derived_bytes, derived_time),
+        ] {
+            println!(
+                "{:<16} {:6.2} bytes/pixel, {:6} MiB at 4K, {:6} MiB at 8K, {:7.1} Mpixel/s",
+                name,
+                bytes as f64 / area as f64,
+                bytes >> 20,
+                (bytes as f64 / area as f64 * PIXELS_8K as f64) as usize >> 20,
+                area as f64 / time.as_secs_f64() / 1e6
+            );
         }
+        assert!(columns_bytes < per_pixel_bytes / 2);
+        assert!(derived_bytes < per_pixel_bytes / 6);
     }
 }
 
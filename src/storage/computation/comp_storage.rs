@@ -16,7 +16,7 @@
 //! │  │ ImageCompProps   │  │       CompStage          │  │
 //! │  │                  │  │                          │  │
 //! │  │ • Coordinate Sys │  │ • Thread-safe Storage    │  │
-//! │  │ • Math ↔ Pixel   │  │ • Per-pixel RwLocks      │  │
+//! │  │ • Math ↔ Pixel   │  │ • Per-tile locks, atomics│  │
 //! │  │ • Zoom/Pan Trans │  │ • Parallel Access        │  │
 //! │  └──────────────────┘  └──────────────────────────┘  │
 //! │                                                      │
@@ -24,7 +24,8 @@
 //! │  │                Event System                     │ │
 //! │  │                                                 │ │
 //! │  │  CompStage → Batcher → VizStorage               │ │
-//! │  │  (async event streaming with batching)          │ │
+//! │  │  (async event streaming with batching,          │ │
+//! │  │   one batcher per subscriber)                   │ │
 //! │  └─────────────────────────────────────────────────┘ │
 //! └──────────────────────────────────────────────────────┘
 //! ```
@@ -35,7 +36,7 @@
 //! 2. **Activation**: Start async event system with batching parameters
 //! 3. **Computation**: Parallel threads write to CompStage, events stream to VizStorage
 //! 4. **Navigation**: Create transformed clones for pan/zoom operations
-//! 5. **Cleanup**: Tear down event system when switching coordinate systems
+//! 5. **Cleanup**: End subscriptions when switching coordinate systems
 //!
 //! ## Key Responsibilities
 //!
@@ -49,42 +50,62 @@
 //! All operations are thread-safe. The event system uses async channels for
 //! non-blocking communication between computation threads and the visualization system.
 
+use std::collections::HashMap;
 use std::time::Duration;
 
-use bigdecimal::{BigDecimal, FromPrimitive};
+use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
 use euclid::{Point2D, Vector2D};
 use tokio::sync::mpsc;
 
 use super::comp_stage::CompStage;
 use crate::storage::{
     coord_spaces::StageSpace,
-    event::stage_event_batcher::{StageEvent, StageEventBatcher},
+    event::stage_event_batcher::{
+        StageEvent, StageEventBatcher, StageEventSender, stage_event_channel,
+    },
     image_comp_properties::ImageCompProperties,
 };
 
+/// Number of events the channel from CompStage to the batcher holds before it overflows
+const COMP_CHANNEL_CAPACITY: usize = 65536;
+/// Number of batches the channel from the batcher to VizStorage holds
+const VIZ_CHANNEL_CAPACITY: usize = 16;
+
+/// Identifies one subscriber of the event system of a `CompStorage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriberId(u64);
+
+/// Connection of one subscriber to the computation.
+struct Subscription {
+    /// Handle to the subscriber's async event batching task
+    task_handle: tokio::task::JoinHandle<()>,
+    /// Channel sender connected to CompStage for this subscriber
+    sender: StageEventSender,
+}
+
 /// Manages the async event system for streaming computation updates.
 ///
 /// Encapsulates the async infrastructure that connects computation threads
-/// to the visualization system. Handles spawning the event batching task
-/// and managing communication channels.
+/// to any number of visualization subscribers. Each subscriber has its own
+/// event batching task and communication channels.
 ///
 /// # Lifecycle
 ///
-/// - **Inactive**: Both fields are `None`, no async resources allocated
-/// - **Active**: Task spawned, sender connected to CompStage
-/// - **Cleanup**: Task aborted, channels dropped
+/// - **Inactive**: No subscriptions, no async resources allocated
+/// - **Active**: One task per subscriber, its sender connected to CompStage
+/// - **Cleanup**: Task aborted, channels dropped, per subscriber
 struct EventSystem {
-    /// Handle to the async event batching task, `None` when inactive
-    task_handle: Option<tokio::task::JoinHandle<()>>,
-    /// Channel sender for CompStage to send events, `None` when inactive
-    sender: Option<mpsc::UnboundedSender<StageEvent>>,
+    /// Id of the next subscriber
+    next_id: u64,
+    /// Active subscriptions by subscriber
+    subscriptions: HashMap<SubscriberId, Subscription>,
 }
 
 impl EventSystem {
     pub fn new() -> Self {
         EventSystem {
-            task_handle: None,
-            sender: None,
+            next_id: 0,
+            subscriptions: HashMap::new(),
         }
     }
 }
@@ -94,11 +115,8 @@ impl EventSystem {
 /// These errors help prevent invalid state transitions and provide
 /// clear feedback about event system lifecycle issues.
 pub enum EventSystemError {
-    /// Attempted to start event system when already active
-    /// Only one event system can be active per CompStorage instance
-    AlreadyActive,
-    /// Attempted to stop event system when not currently active
-    /// Must start event system before stopping it
+    /// Attempted to stop a subscription which is not currently active
+    /// It has been stopped before or never existed in this CompStorage instance
     NotActive,
 }
 
@@ -116,7 +134,7 @@ pub enum EventSystemError {
 /// - **Rectified Properties**: Adjusted for square pixels and optimal computation
 ///
 /// ## Data Storage
-/// - **CompStage**: Thread-safe storage with per-pixel RwLocks
+/// - **CompStage**: Thread-safe storage of atomic columns with one lock per tile of 64 pixels
 /// - **Event System**: Async streaming of computation updates
 ///
 /// ## Key Features
@@ -131,14 +149,16 @@ pub enum EventSystemError {
 /// // Create storage for specific coordinate area
 /// let storage = CompStorage::new(image_properties);
 ///
-/// // Start event streaming to visualization
-/// let receiver = storage.get_event_receiver(1000, Duration::from_millis(50))?;
+/// // Start event streaming to visualization, e.g. a canvas and a minimap
+/// let (canvas_id, canvas_events) = storage.get_event_receiver(1000, Duration::from_millis(50));
+/// let (minimap_id, minimap_events) = storage.get_event_receiver(10000, Duration::from_secs(1));
 ///
 /// // Multiple computation threads can now safely write to storage.stage
-/// // Events automatically stream to visualization system
+/// // Events automatically stream to all subscribers
 ///
 /// // Clean shutdown
-/// storage.drop_event_receiver()?;
+/// storage.drop_event_receiver(canvas_id)?;
+/// storage.drop_event_receiver(minimap_id)?;
 /// ```
 pub struct CompStorage {
     /// Original user-specified coordinate system and computation parameters
@@ -147,7 +167,7 @@ pub struct CompStorage {
     /// Rectified coordinate system optimized for computation
     /// Ensures square pixels and proper aspect ratios
     pub properties: ImageCompProperties,
-    /// Thread-safe fractal data storage with per-pixel concurrency
+    /// Thread-safe fractal data storage with per-tile locks and lock-free computed checks
     pub stage: CompStage,
     /// Async event system management (protected by mutex for thread safety)
     event_system: std::sync::Mutex<EventSystem>,
@@ -176,12 +196,15 @@ impl CompStorage {
     /// - Optimal aspect ratios for computation
     /// - Proper mathematical coordinate alignment
     ///
+    /// Afterwards, the stage is enlarged to hold all samples of the supersampling,
+    /// see `ImageCompProperties::supersampled()`.
+    ///
     /// # Thread Safety
     ///
     /// The returned storage is immediately safe for concurrent access
     /// by multiple computation threads.
     pub fn new(original_properties: ImageCompProperties) -> CompStorage {
-        let properties = original_properties.rectified();
+        let properties = original_properties.rectified().supersampled();
         CompStorage {
             original_properties,
             properties: properties.clone(),
@@ -190,11 +213,45 @@ impl CompStorage {
         }
     }
 
-    /// Activates the async event system and returns the visualization receiver.
+    /// Creates a computation storage with an already filled stage.
+    ///
+    /// Used to continue a computation restored from a snapshot, the engine
+    /// skips all points of the stage which are computed already.
+    ///
+    /// # Arguments
+    ///
+    /// * `original_properties` - User-specified coordinate system and parameters
+    /// * `stage` - Stage with the data computed so far
+    ///
+    /// # Returns
+    ///
+    /// The storage, or `None` if the stage size does not match the rectified and
+    /// supersampled properties, see `CompStorage::new()`
+    pub fn with_stage(original_properties: ImageCompProperties, stage: CompStage) -> Option<Self> {
+        let properties = original_properties.rectified().supersampled();
+        let size = properties.stage_properties.area.size();
+        if stage.width() != size.width as usize || stage.height() != size.height as usize {
+            return None;
+        }
+        Some(CompStorage {
+            original_properties,
+            properties,
+            stage,
+            event_system: std::sync::Mutex::new(EventSystem::new()),
+        })
+    }
+
+    /// Consumes the storage and returns its stage, e.g. to keep it as tile of a larger image.
+    pub fn into_stage(self) -> CompStage {
+        self.stage
+    }
+
+    /// Subscribes to the async event system and returns the visualization receiver.
     ///
     /// Spawns the event batching infrastructure that streams computation updates
-    /// from the CompStage to the visualization system. Events are batched for
-    /// efficiency while maintaining responsiveness.
+    /// from the CompStage to one visualization subscriber. Events are batched for
+    /// efficiency while maintaining responsiveness. Any number of subscribers can
+    /// follow the same computation, each with its own batching parameters.
     ///
     /// # Arguments
     ///
@@ -203,74 +260,100 @@ impl CompStorage {
     ///
     /// # Returns
     ///
-    /// - `Ok(receiver)` - Channel receiver for visualization system
-    /// - `Err(AlreadyActive)` - Event system is already running
+    /// Id of the subscription and channel receiver for the visualization subscriber
     ///
     /// # Event Pipeline
     ///
     /// ```text
-    /// CompStage → [Unbounded Channel] → EventBatcher → [Unbounded Channel] → VizStorage
+    ///          ┌→ [Bounded Channel] → EventBatcher → [Bounded Channel] → VizStorage
+    /// CompStage┼→ [Bounded Channel] → EventBatcher → [Bounded Channel] → Minimap
+    ///          └→ ...
     /// ```
     ///
     /// The batcher runs as an async task, collecting events and forwarding
     /// them in efficient batches to prevent overwhelming the visualization system.
     ///
+    /// # Back-pressure
+    ///
+    /// Both channels are bounded. If the visualization falls behind, the batcher
+    /// waits for it and the channel from the stage fills up. Further changes are
+    /// then dropped and replaced by a `StageEvent::Resync`, upon which the receiver
+    /// must rebuild its data from the stage. Slow subscribers do not slow down others.
+    ///
+    /// # Joining Late
+    ///
+    /// The first event is `StageEvent::InitialState` with the current state of the
+    /// stage, which does not end the subscription. To start from a consistent
+    /// snapshot, a subscriber copies the stage _after_ subscribing and applies all
+    /// received events to the copy, see `CompStage::add_change_sender()`.
+    ///
     /// # Resource Management
     ///
     /// This method allocates async resources (task, channels) that must be
     /// properly cleaned up with `drop_event_receiver()` to prevent resource leaks.
+    /// Dropping the receiver also ends the task when the next event arrives.
     ///
     /// # Thread Safety
     ///
-    /// Safe to call from any thread. Only one event system can be active
-    /// per CompStorage instance.
+    /// Safe to call from any thread.
     pub fn get_event_receiver(
         &self,
         max_capacity: usize,
         max_interval: Duration,
-    ) -> Result<mpsc::UnboundedReceiver<StageEvent>, EventSystemError> {
+    ) -> (SubscriberId, mpsc::Receiver<StageEvent>) {
         let mut event_system = self.event_system.lock().unwrap();
 
-        // event system cannot be active twice
-        if event_system.sender.is_some() {
-            return Err(EventSystemError::AlreadyActive);
-        }
+        // Forget subscriptions whose batcher has already ended
+        event_system
+            .subscriptions
+            .retain(|_, subscription| !subscription.task_handle.is_finished());
         // Create channel for CompStage sending events to batcher
-        let (comp_sender, comp_receiver) = mpsc::unbounded_channel();
+        let (comp_sender, comp_receiver) = stage_event_channel(COMP_CHANNEL_CAPACITY);
         // Create channel for VizStorage receiving events from batcher
-        let (viz_sender, viz_receiver) = mpsc::unbounded_channel();
+        let (viz_sender, viz_receiver) = mpsc::channel(VIZ_CHANNEL_CAPACITY);
         // Create the batcher
         let batcher = StageEventBatcher::new(max_capacity, max_interval);
         // Spawn the async task, this also connects both channels to the batcher
         let task_handle = tokio::task::spawn(batcher.run(comp_receiver, viz_sender));
         // Connect the comp channel to the stage
-        self.stage.set_change_sender(Some(comp_sender.clone()));
+        self.stage.add_change_sender(comp_sender.clone());
         // Put everything in event system
-        event_system.sender = Some(comp_sender);
-        event_system.task_handle = Some(task_handle);
+        let id = SubscriberId(event_system.next_id);
+        event_system.next_id += 1;
+        event_system.subscriptions.insert(
+            id,
+            Subscription {
+                task_handle,
+                sender: comp_sender,
+            },
+        );
 
         // And finally return the receiver to the caller
-        Ok(viz_receiver)
+        (id, viz_receiver)
     }
 
-    /// Deactivates the async event system and cleans up resources.
+    /// Ends a subscription to the async event system and cleans up its resources.
+    ///
+    /// Properly shuts down the event streaming infrastructure of one subscriber,
+    /// aborts its async batching task, and releases all associated resources.
+    /// Other subscribers are not affected. This is essential for preventing
+    /// resource leaks when switching coordinate systems or shutting down computation.
+    ///
+    /// # Arguments
     ///
-    /// Properly shuts down the event streaming infrastructure, aborts the
-    /// async batching task, and releases all associated resources. This is
-    /// essential for preventing resource leaks when switching coordinate systems
-    /// or shutting down computation.
+    /// * `id` - Id of the subscription as returned by `get_event_receiver()`
     ///
     /// # Returns
     ///
-    /// - `Ok(())` - Event system successfully deactivated
-    /// - `Err(NotActive)` - No event system was currently active
+    /// - `Ok(())` - Subscription successfully ended
+    /// - `Err(NotActive)` - The subscription was not active
     ///
     /// # Cleanup Process
     ///
-    /// 1. **Disconnect CompStage**: Stop sending new events
+    /// 1. **Disconnect CompStage**: Stop sending new events to the subscriber
     /// 2. **Abort Async Task**: Terminate the event batching task
     /// 3. **Drop Channels**: Release channel endpoints
-    /// 4. **Reset State**: Return to inactive state
+    /// 4. **Reset State**: Forget the subscription
     ///
     /// # Thread Safety
     ///
@@ -281,17 +364,17 @@ impl CompStorage {
     ///
     /// Always call this method before dropping CompStorage or switching
     /// to a different coordinate system to ensure proper resource cleanup.
-    pub fn drop_event_receiver(&self) -> Result<(), EventSystemError> {
+    pub fn drop_event_receiver(&self, id: SubscriberId) -> Result<(), EventSystemError> {
         let mut event_system = self.event_system.lock().unwrap();
 
-        if event_system.sender.is_none() {
+        let Some(subscription) = event_system.subscriptions.remove(&id) else {
             return Err(EventSystemError::NotActive);
-        }
+        };
 
-        // Disconnect CompStage from the event system
-        self.stage.set_change_sender(None);
-        event_system.task_handle.take().unwrap().abort();
-        event_system.sender.take().unwrap(); // Dropping the sender automatically closes the channel - according to Claude…
+        // Disconnect CompStage from the subscriber's channel
+        self.stage.remove_change_sender(&subscription.sender);
+        subscription.task_handle.abort();
+        // Dropping the subscription drops the last sender, which closes the channel
 
         Ok(())
     }
@@ -362,7 +445,7 @@ impl CompStorage {
     ///
     /// New `CompStorage` with:
     /// - Scaled coordinate systems (origin pixel maintains its mathematical coordinate)
-    /// - Fresh stage initialized for new coordinate system
+    /// - Stage resampled from this storage's stage
     /// - Inactive event system (must be reactivated if needed)
     ///
     /// # Coordinate Transformation
@@ -374,8 +457,8 @@ impl CompStorage {
     ///
     /// # Data Handling
     ///
-    /// Currently creates a fresh stage (no data preservation). Future
-    /// implementations could preserve and interpolate existing computed data.
+    /// Points landing exactly on an old sample keep their data, all others
+    /// are guessed from their old neighbours and recomputed by the engine.
     ///
     /// # Use Cases
     ///
@@ -384,14 +467,88 @@ impl CompStorage {
     /// - Detail level adjustments
     pub fn zoomed_clone_by_pixels(&self, origin: Point2D<i32, StageSpace>, factor: f32) -> Self {
         let new_properties = self.properties.zoomed_clone_by_pixels(origin, factor);
+        // The original properties have another raster, so they are zoomed at the math
+        // coordinate of the origin instead of at the origin pixel
+        let math_origin = self.properties.stage_properties.orig_area.coo_pix(origin);
         CompStorage {
-            original_properties: new_properties.clone(),
+            stage: self.resampled_stage(&new_properties),
+            original_properties: self
+                .original_properties
+                .zoomed_clone_by_math(math_origin, factor),
             properties: new_properties,
-            stage: self.stage.zoomed_clone(origin, factor),
             event_system: std::sync::Mutex::new(EventSystem::new()),
         }
     }
 
+    /// Creates a storage for other computation properties, reusing the data of this one.
+    ///
+    /// The new properties may describe any area and size. Each point of the new stage
+    /// is mapped to its position in this stage: Points landing exactly on an old point
+    /// keep its data, all others within the old area are guessed from their nearest
+    /// neighbour and recomputed by the engine, see `CompStage::resampled_clone_with_size()`.
+    /// Data is adapted to a changed maximum iteration count.
+    ///
+    /// # Arguments
+    ///
+    /// * `original_properties` - Properties of the new storage, as for `CompStorage::new()`
+    ///
+    /// # Returns
+    ///
+    /// New storage with resampled data, or an empty one if the data is not comparable
+    /// because fractal type or bailout differ or samples are jittered
+    ///
+    /// # Use Cases
+    ///
+    /// - Consecutive frames of zoom animations
+    pub fn resampled_clone(&self, original_properties: ImageCompProperties) -> Self {
+        let target = CompStorage::new(original_properties);
+        let mut stage = self.resampled_stage(&target.properties);
+        let (old, new) = (&self.properties, &target.properties);
+        if old.max_iteration != new.max_iteration {
+            stage = stage.max_iteration_changed_clone(old.max_iteration, new.max_iteration);
+        }
+        CompStorage { stage, ..target }
+    }
+
+    /// Resamples the stage of this storage into the raster of other properties.
+    ///
+    /// Each new pixel is mapped to the position of its center in this stage, derived from
+    /// the exact coordinates of both rasters, see `StageProperties::x()` and `y()`. Points
+    /// landing exactly on an old point keep its data, see `CompStage::resampled_clone_with_size()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `properties` - Rectified and supersampled properties of the new stage
+    ///
+    /// # Returns
+    ///
+    /// Stage with resampled data, or an empty one if the data is not comparable
+    /// because fractal type or bailout differ or samples are jittered
+    fn resampled_stage(&self, properties: &ImageCompProperties) -> CompStage {
+        let (old, new) = (&self.properties, properties);
+        let size = *new.stage_properties.area.size();
+        if old.fractal_type != new.fractal_type
+            || old.bailout != new.bailout
+            || old.supersampling.is_jittered()
+            || new.supersampling.is_jittered()
+        {
+            return CompStage::new(size);
+        }
+        // New pixel (x, y) lands at old pixel (x0 + x * dx, y0 + y * dy), computed
+        // with exact coordinates to stay precise in deep zooms
+        let (old_stage, new_stage) = (&old.stage_properties, &new.stage_properties);
+        let old_step_x = old_stage.x(1) - old_stage.x(0);
+        let old_step_y = old_stage.y(1) - old_stage.y(0);
+        let to_f64 = |value: BigDecimal| value.to_f64().unwrap_or(f64::NAN);
+        let x0 = to_f64((new_stage.x(0) - old_stage.x(0)) / &old_step_x);
+        let y0 = to_f64((new_stage.y(0) - old_stage.y(0)) / &old_step_y);
+        let dx = to_f64((new_stage.x(1) - new_stage.x(0)) / &old_step_x);
+        let dy = to_f64((new_stage.y(1) - new_stage.y(0)) / &old_step_y);
+        self.stage.resampled_clone_with_size(size, |x, y| {
+            Point2D::new(x0 + x as f64 * dx, y0 + y as f64 * dy)
+        })
+    }
+
     pub fn max_iteration_changed_clone(
         &self,
         old_max_iteration: u32,
@@ -412,4 +569,268 @@ impl CompStorage {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use euclid::Size2D;
+
+    use super::*;
+    use crate::{
+        comp::{
+            mandelbrot_engine::{ComputationStrategy, EngineState, MandelbrotEngine},
+            math_area::{MathArea, RasteredMathArea},
+        },
+        storage::{
+            data_point::DataPoint,
+            event::data_point_change_event::DataPointChange,
+            image_comp_properties::{StageProperties, StageState, Supersampling},
+            param_presets::FractalType,
+            visualization::viz_storage::{EventProcessResult, VizStorage},
+        },
+    };
+
+    fn storage() -> Arc<CompStorage> {
+        supersampled_storage(Supersampling::Off)
+    }
+
+    fn supersampled_storage(supersampling: Supersampling) -> Arc<CompStorage> {
+        Arc::new(CompStorage::new(ImageCompProperties::new(
+            StageProperties::new(RasteredMathArea::new(
+                MathArea::from_str("-0.5", "0", "1.5", "1").unwrap(),
+                Size2D::new(8, 8),
+            )),
+            100,
+            FractalType::Mandelbrot,
+            4.0,
+            supersampling,
+        )))
+    }
+
+    /// Processes events until the subscription of the visualization storage has ended.
+    async fn follow(viz: &mut VizStorage) {
+        while viz.process_events() != EventProcessResult::Stop {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+
+    /// Processes events until at least one event has been handled.
+    async fn wait_for_events(viz: &mut VizStorage) -> EventProcessResult {
+        loop {
+            match viz.process_events() {
+                EventProcessResult::Continue => tokio::time::sleep(Duration::from_millis(1)).await,
+                result => return result,
+            }
+        }
+    }
+
+    fn assert_same_data(storage: &CompStorage, viz: &VizStorage) {
+        for y in 0..storage.stage.height() {
+            for x in 0..storage.stage.width() {
+                assert_eq!(
+                    storage
+                        .stage
+                        .get(x as u32, y as u32)
+                        .map(|point| point.iteration_count),
+                    viz.stage.get(x, y).map(|point| point.iteration_count)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_keeps_data_at_same_coordinates() {
+        let storage = storage();
+        let (width, height) = (storage.stage.width() as u32, storage.stage.height() as u32);
+        for y in 0..height {
+            for x in 0..width {
+                storage.stage.set(x, y, DataPoint::derived(y * width + x));
+            }
+        }
+        let old = &storage.properties.stage_properties;
+        let tolerance = (old.x_f64(1) - old.x_f64(0)) * 1e-3;
+        // Pixel centers only meet again for odd factors, zooming by 2 must only guess
+        for (origin, factor, exact) in [
+            (Point2D::new(3, 3), 2.0, false),
+            (Point2D::new(2, 5), 3.0, true),
+            (Point2D::new(4, 4), 1.0 / 3.0, true),
+        ] {
+            let zoomed = storage.zoomed_clone_by_pixels(origin, factor);
+            let new = &zoomed.properties.stage_properties;
+            let (mut hits, mut outside) = (0, 0);
+            for y in 0..height {
+                for x in 0..width {
+                    let Some(point) = zoomed.stage.get(x, y) else {
+                        outside += 1;
+                        continue;
+                    };
+                    if point.iteration_count_quality.is_accurate() {
+                        // Exact hits must come from the old pixel at the same coordinates
+                        hits += 1;
+                        let (ox, oy) =
+                            (point.iteration_count % width, point.iteration_count / width);
+                        assert!((new.x_f64(x as i32) - old.x_f64(ox as i32)).abs() < tolerance);
+                        assert!((new.y_f64(y as i32) - old.y_f64(oy as i32)).abs() < tolerance);
+                    }
+                }
+            }
+            assert_eq!(exact, hits > 0, "exact hits zooming by {}", factor);
+            assert_eq!(factor < 1.0, outside > 0);
+        }
+    }
+
+    #[test]
+    fn zoom_keeps_original_properties_unsupersampled() {
+        let storage = supersampled_storage(Supersampling::Grid2x2);
+        let zoomed = storage.zoomed_clone_by_pixels(Point2D::new(5, 9), 2.0);
+        let original = zoomed.original_properties.clone();
+        assert_eq!(
+            Size2D::new(8, 8),
+            *original.stage_properties.orig_area.size()
+        );
+        let (expected, actual) = (
+            original.rectified().supersampled().stage_properties,
+            &zoomed.properties.stage_properties,
+        );
+        assert_eq!(expected.area.size(), actual.area.size());
+        for pix in [0, 7, 15] {
+            assert!((expected.x_f64(pix) - actual.x_f64(pix)).abs() < 1e-9);
+            assert!((expected.y_f64(pix) - actual.y_f64(pix)).abs() < 1e-9);
+        }
+        // The zoomed stage fits its original properties, e.g. when restored from a snapshot
+        assert!(CompStorage::with_stage(original, zoomed.stage).is_some());
+    }
+
+    #[tokio::test]
+    async fn subscribers_follow_computation_independently() {
+        let storage = storage();
+        let (width, height) = (storage.stage.width() as u32, storage.stage.height() as u32);
+        let mut canvas = VizStorage::with_batching(&storage, 4, Duration::from_millis(1));
+        storage.stage.set_state(StageState::Evolving);
+        for y in 0..height / 2 {
+            storage.stage.set_run(0, width, y, DataPoint::derived(y));
+        }
+        // The minimap joins late and starts from a snapshot of the first half
+        let mut minimap = VizStorage::with_batching(&storage, 1000, Duration::from_millis(20));
+        for y in height / 2..height {
+            for x in 0..width {
+                storage.stage.set(x, y, DataPoint::derived(x + y));
+            }
+        }
+        storage.stage.set_state(StageState::Completed);
+        // Joining after the end yields the final data from the snapshot
+        let mut late = VizStorage::new(&storage);
+
+        follow(&mut canvas).await;
+        follow(&mut minimap).await;
+        assert_eq!(
+            EventProcessResult::UpdateAndContinue,
+            wait_for_events(&mut late).await
+        );
+        assert_eq!(StageState::Completed, late.seen_state);
+        for viz in [&canvas, &minimap, &late] {
+            assert_same_data(&storage, viz);
+        }
+        assert_eq!(1, storage.event_system.lock().unwrap().subscriptions.len());
+        drop(late);
+        assert!(
+            storage
+                .event_system
+                .lock()
+                .unwrap()
+                .subscriptions
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn late_subscribers_get_each_sample_once() {
+        let storage = Arc::new(CompStorage::new(ImageCompProperties::new(
+            StageProperties::new(RasteredMathArea::new(
+                MathArea::from_str("-0.5", "0", "1.5", "1").unwrap(),
+                Size2D::new(96, 64),
+            )),
+            200,
+            FractalType::Mandelbrot,
+            4.0,
+            Supersampling::Adaptive3x3,
+        )));
+        let engine = MandelbrotEngine::new(&storage, ComputationStrategy::Shuffled);
+        engine.start();
+        // Subscribe again and again while the engine computes and refines the edges
+        let mut subscribers = Vec::new();
+        while engine.state() == EngineState::Running {
+            subscribers.push(VizStorage::with_batching(
+                &storage,
+                1000,
+                Duration::from_millis(1),
+            ));
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        engine.wait();
+        let samples = storage.stage.get_full_samples();
+        assert!(!samples.is_empty());
+        let width = storage.stage.width();
+        for mut viz in subscribers {
+            // The engine has ended, so all events are delivered once the batcher is quiet
+            loop {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                if viz.process_events() != EventProcessResult::UpdateAndContinue {
+                    break;
+                }
+            }
+            for (index, pixel_samples) in &samples {
+                let stored = viz.stage.samples(index % width, index / width);
+                assert_eq!(pixel_samples.len(), stored.len());
+            }
+            // Replaying a sample event leaves the samples unchanged
+            let (&index, pixel_samples) = samples.iter().next().unwrap();
+            let (x, y) = ((index % width) as u32, (index / width) as u32);
+            viz.stage
+                .set_from_change(DataPointChange::new_sample(x, y, 0, &pixel_samples[0]));
+            assert_eq!(
+                pixel_samples.len(),
+                viz.stage.samples(x as usize, y as usize).len()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn subscriber_of_stalled_clone_follows_engine() {
+        let storage = storage();
+        storage.stage.set_state(StageState::Completed);
+        let clone = Arc::new(storage.shifted_clone_by_pixels(Vector2D::new(2, 1)));
+        assert_eq!(StageState::Stalled, clone.stage.get_state());
+        let mut viz = VizStorage::new(&clone);
+        let engine = MandelbrotEngine::new(&clone, ComputationStrategy::Shuffled);
+        engine.start();
+        engine.wait();
+        assert_eq!(StageState::Completed, clone.stage.get_state());
+        follow(&mut viz).await;
+        assert_same_data(&clone, &viz);
+    }
+
+    #[tokio::test]
+    async fn dropped_subscriber_leaves_others_running() {
+        let storage = storage();
+        let first = VizStorage::new(&storage);
+        let mut second = VizStorage::new(&storage);
+        drop(first);
+        assert_eq!(1, storage.event_system.lock().unwrap().subscriptions.len());
+        storage.stage.set(3, 4, DataPoint::derived(17));
+        storage.stage.set_state(StageState::Stalled);
+        follow(&mut second).await;
+        assert_eq!(17, second.stage.get(3, 4).unwrap().iteration_count);
+        drop(second);
+        assert!(
+            storage
+                .event_system
+                .lock()
+                .unwrap()
+                .subscriptions
+                .is_empty()
+        );
+    }
+}
+
 // end of file
@@ -1,2 +1,5 @@
 pub mod comp_stage;
 pub mod comp_storage;
+pub mod point_columns;
+pub mod stage_snapshot;
+pub mod tiled_stage;
@@ -0,0 +1,651 @@
+//! Binary snapshot files of a computation, to continue it later.
+//!
+//! Deep images with high maximum iteration counts take hours to compute. A snapshot
+//! stores everything needed to continue such a computation in another session: the
+//! image computation properties and the complete content of the `CompStage`. After
+//! reloading, `MandelbrotEngine::start()` skips all points which are computed already,
+//! so the computation continues exactly where it has been stopped.
+//!
+//! # File Format
+//!
+//! All numbers are little-endian, strings are UTF-8 with a `u32` byte length in front.
+//!
+//! ```text
+//! "MANDSNAP"  u32 version
+//! Properties: center_x, center_y, radius, ratio (exact decimal strings),
+//!             u32 width, u32 height, u32 max_iteration,
+//!             u8 fractal type [+ f64 parameters], f64 bailout, u8 supersampling
+//! Stage:      u8 state, u32 width, u32 height,
+//!             entries in row-major order until all pixels are covered:
+//!               0, u32 n  n uncomputed pixels
+//!               1, point  one pixel with data
+//!             u32 refined pixel count, for each: u32 index, u32 sample count, points
+//! Point:      u32 iteration_count, u8 quality, f64 x, f64 y, u8 quality,
+//!             f32 smooth_iteration, f64 distance_estimate, u8 quality, f32 nx, f32 ny
+//! ```
+//!
+//! The properties are the original ones requested by the user, the stage size is stored
+//! nevertheless to detect damaged files. A stage saved while it was `Evolving` is
+//! restored as `Stalled`, as no computation is running on it after loading.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use bigdecimal::{BigDecimal, Zero};
+use euclid::{Point2D, Size2D, Vector2D};
+
+use crate::{
+    comp::math_area::{MathArea, RasteredMathArea},
+    storage::{
+        computation::{comp_stage::CompStage, comp_storage::CompStorage},
+        coord_spaces::StageSpace,
+        data_point::{DataPoint, DataQuality},
+        image_comp_properties::{ImageCompProperties, StageProperties, StageState, Supersampling},
+        param_presets::FractalType,
+    },
+};
+
+/// Leading bytes identifying a snapshot file
+const MAGIC: &[u8; 8] = b"MANDSNAP";
+
+/// Version of the snapshot format written by this program
+pub const CURRENT_VERSION: u32 = 1;
+
+/// File name extension of snapshot files
+pub const SNAPSHOT_EXTENSION: &str = "mandsnap";
+
+/// Longest string accepted in a snapshot, protects against allocating huge buffers
+const MAX_STRING_LENGTH: u32 = 1 << 20;
+
+/// Errors that can occur while reading or writing snapshot files.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reading or writing the snapshot file failed, includes unexpected ends of file
+    Io(std::io::Error),
+    /// The file is no snapshot or its content is inconsistent
+    Format(String),
+    /// The file has been written by a newer program version
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "cannot access snapshot file: {}", error),
+            Self::Format(message) => write!(f, "malformed snapshot: {}", message),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported snapshot version {} (supported: 1 to {})",
+                version, CURRENT_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Write a snapshot of the storage into the file at `path`, replacing its content.
+pub fn save_snapshot(storage: &CompStorage, path: &Path) -> Result<(), SnapshotError> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_snapshot(&mut w, storage)?;
+    Ok(w.flush()?)
+}
+
+/// Read a snapshot from the file at `path`.
+///
+/// # Returns
+///
+/// A new storage with the restored stage and no active event system
+pub fn load_snapshot(path: &Path) -> Result<CompStorage, SnapshotError> {
+    read_snapshot(&mut BufReader::new(File::open(path)?))
+}
+
+/// Write a snapshot of the storage.
+///
+/// The stage may be computed on while the snapshot is written, each point is
+/// stored as it was when it has been read.
+pub fn write_snapshot(w: &mut impl Write, storage: &CompStorage) -> Result<(), SnapshotError> {
+    w.write_all(MAGIC)?;
+    write_u32(w, CURRENT_VERSION)?;
+    write_properties(w, &storage.original_properties)?;
+    write_stage(w, &storage.stage)
+}
+
+/// Write the state and the complete content of a stage, the stage part of the snapshot format.
+///
+/// Also used on its own to move finished tiles of a `TiledStage` out of memory.
+pub fn write_stage(w: &mut impl Write, stage: &CompStage) -> Result<(), SnapshotError> {
+    write_u8(w, state_code(stage.get_state()))?;
+    write_u32(w, stage.width() as u32)?;
+    write_u32(w, stage.height() as u32)?;
+    let data = stage.get_full_data();
+    let mut index = 0;
+    while index < data.len() {
+        match &data[index] {
+            Some(point) => {
+                write_u8(w, 1)?;
+                write_point(w, point)?;
+                index += 1;
+            }
+            None => {
+                let run = data[index..].iter().take_while(|p| p.is_none()).count();
+                write_u8(w, 0)?;
+                write_u32(w, run as u32)?;
+                index += run;
+            }
+        }
+    }
+    let samples = stage.get_full_samples();
+    let mut indices: Vec<&usize> = samples.keys().collect();
+    indices.sort();
+    write_u32(w, indices.len() as u32)?;
+    for index in indices {
+        write_u32(w, *index as u32)?;
+        write_u32(w, samples[index].len() as u32)?;
+        for point in &samples[index] {
+            write_point(w, point)?;
+        }
+    }
+    Ok(())
+}
+
+/// Read a snapshot.
+///
+/// # Returns
+///
+/// A new storage with the restored stage, or the reason why the snapshot cannot be used
+pub fn read_snapshot(r: &mut impl Read) -> Result<CompStorage, SnapshotError> {
+    let mut magic = [0u8; 8];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(format_error("no mandel-rs snapshot"));
+    }
+    match read_u32(r)? {
+        1 => {}
+        version => return Err(SnapshotError::UnsupportedVersion(version)),
+    }
+    let properties = read_properties(r)?;
+    let stage = read_stage(
+        r,
+        *properties
+            .rectified()
+            .supersampled()
+            .stage_properties
+            .area
+            .size(),
+    )?;
+    CompStorage::with_stage(properties, stage)
+        .ok_or_else(|| format_error("stage size does not match the properties"))
+}
+
+/// Read a stage written by `write_stage()`.
+///
+/// # Arguments
+///
+/// * `r` - Source of the stage data
+/// * `expected_size` - Size the stage must have, checked before allocating it
+///
+/// # Returns
+///
+/// The restored stage, or the reason why the data cannot be used
+pub fn read_stage(
+    r: &mut impl Read,
+    expected_size: Size2D<u32, StageSpace>,
+) -> Result<CompStage, SnapshotError> {
+    let state = match read_u8(r)? {
+        0 => StageState::Initialized,
+        // Nothing computes on a freshly loaded stage
+        1 | 2 => StageState::Stalled,
+        3 => StageState::Completed,
+        code => return Err(format_error(&format!("unknown stage state {}", code))),
+    };
+    let size = Size2D::new(read_u32(r)?, read_u32(r)?);
+    // Check the size before allocating the stage, a damaged size might be huge
+    if size != expected_size {
+        return Err(format_error("stage size does not match the properties"));
+    }
+    let area = size.width as usize * size.height as usize;
+    let mut data = Vec::with_capacity(area);
+    while data.len() < area {
+        match read_u8(r)? {
+            0 => {
+                let run = read_u32(r)? as usize;
+                if run == 0 || data.len() + run > area {
+                    return Err(format_error("run of uncomputed points exceeds the stage"));
+                }
+                data.resize(data.len() + run, None);
+            }
+            1 => data.push(Some(read_point(r)?)),
+            tag => return Err(format_error(&format!("unknown point tag {}", tag))),
+        }
+    }
+    let mut samples = HashMap::new();
+    for _ in 0..read_u32(r)? {
+        let index = read_u32(r)? as usize;
+        let count = read_u32(r)?;
+        let points = (0..count)
+            .map(|_| read_point(r))
+            .collect::<Result<Vec<_>, _>>()?;
+        samples.insert(index, points);
+    }
+    CompStage::with_content(size, data, samples, state)
+        .ok_or_else(|| format_error("sample index outside of the stage"))
+}
+
+/// Write the properties with exact decimal coordinates.
+fn write_properties(w: &mut impl Write, properties: &ImageCompProperties) -> std::io::Result<()> {
+    let area = &properties.stage_properties.orig_area;
+    let math = area.math_area();
+    write_string(w, &math.center().x.to_string())?;
+    write_string(w, &math.center().y.to_string())?;
+    write_string(w, &math.radius().to_string())?;
+    write_string(w, &math.ratio().to_string())?;
+    write_u32(w, area.size().width)?;
+    write_u32(w, area.size().height)?;
+    write_u32(w, properties.max_iteration)?;
+    match properties.fractal_type {
+        FractalType::Mandelbrot => write_u8(w, 0)?,
+        FractalType::Julia { c_real, c_imag } => {
+            write_u8(w, 1)?;
+            write_f64(w, c_real)?;
+            write_f64(w, c_imag)?;
+        }
+        FractalType::BurningShip => write_u8(w, 2)?,
+        FractalType::Tricorn => write_u8(w, 3)?,
+        FractalType::Multibrot { exponent } => {
+            write_u8(w, 4)?;
+            write_f64(w, exponent)?;
+        }
+    }
+    write_f64(w, properties.bailout)?;
+    write_u8(w, supersampling_code(properties.supersampling))
+}
+
+/// Read the properties written by `write_properties()`.
+fn read_properties(r: &mut impl Read) -> Result<ImageCompProperties, SnapshotError> {
+    let mut decimal = |name: &str| -> Result<BigDecimal, SnapshotError> {
+        let text = read_string(r)?;
+        BigDecimal::from_str(&text)
+            .map_err(|_| format_error(&format!("invalid {} \"{}\"", name, text)))
+    };
+    let center_x = decimal("center_x")?;
+    let center_y = decimal("center_y")?;
+    let radius = decimal("radius")?;
+    let ratio = decimal("ratio")?;
+    // Checked before building the area, which divides by both
+    if radius <= BigDecimal::zero() || ratio <= BigDecimal::zero() {
+        return Err(format_error(&format!(
+            "radius {} and ratio {} must be positive",
+            radius, ratio
+        )));
+    }
+    let size = Size2D::new(read_u32(r)?, read_u32(r)?);
+    if size.is_empty() {
+        return Err(format_error(&format!(
+            "empty image size {}x{}",
+            size.width, size.height
+        )));
+    }
+    let max_iteration = read_u32(r)?;
+    let fractal_type = match read_u8(r)? {
+        0 => FractalType::Mandelbrot,
+        1 => FractalType::Julia {
+            c_real: read_f64(r)?,
+            c_imag: read_f64(r)?,
+        },
+        2 => FractalType::BurningShip,
+        3 => FractalType::Tricorn,
+        4 => FractalType::Multibrot {
+            exponent: read_f64(r)?,
+        },
+        code => return Err(format_error(&format!("unknown fractal type {}", code))),
+    };
+    let finite_parameters = match fractal_type {
+        FractalType::Julia { c_real, c_imag } => c_real.is_finite() && c_imag.is_finite(),
+        FractalType::Multibrot { exponent } => exponent.is_finite() && exponent > 1.0,
+        _ => true,
+    };
+    if !finite_parameters {
+        return Err(format_error(&format!(
+            "invalid fractal parameters {:?}",
+            fractal_type
+        )));
+    }
+    let bailout = read_f64(r)?;
+    if !(bailout.is_finite() && bailout >= 2.0) {
+        return Err(format_error(&format!("invalid bailout {}", bailout)));
+    }
+    let supersampling = read_supersampling(r)?;
+    Ok(ImageCompProperties::new(
+        StageProperties::new(RasteredMathArea::new(
+            MathArea::from_big_decimals(center_x, center_y, radius, ratio),
+            size,
+        )),
+        max_iteration,
+        fractal_type,
+        bailout,
+        supersampling,
+    ))
+}
+
+/// Write one data point with all its quality flags.
+fn write_point(w: &mut impl Write, point: &DataPoint) -> std::io::Result<()> {
+    write_u32(w, point.iteration_count)?;
+    write_u8(w, quality_code(point.iteration_count_quality))?;
+    write_f64(w, point.final_coordinate.x)?;
+    write_f64(w, point.final_coordinate.y)?;
+    write_u8(w, quality_code(point.final_coordinate_quality))?;
+    write_f32(w, point.smooth_iteration)?;
+    write_f64(w, point.distance_estimate)?;
+    write_u8(w, quality_code(point.distance_estimate_quality))?;
+    write_f32(w, point.surface_normal.x)?;
+    write_f32(w, point.surface_normal.y)
+}
+
+/// Read one data point written by `write_point()`.
+fn read_point(r: &mut impl Read) -> Result<DataPoint, SnapshotError> {
+    Ok(DataPoint::new(
+        read_u32(r)?,
+        read_quality(r)?,
+        Point2D::new(read_f64(r)?, read_f64(r)?),
+        read_quality(r)?,
+        read_f32(r)?,
+        read_f64(r)?,
+        read_quality(r)?,
+        Vector2D::new(read_f32(r)?, read_f32(r)?),
+    ))
+}
+
+/// Return the code of a stage state in the file.
+fn state_code(state: StageState) -> u8 {
+    match state {
+        StageState::Initialized => 0,
+        StageState::Evolving => 1,
+        StageState::Stalled => 2,
+        StageState::Completed => 3,
+    }
+}
+
+/// Return the code of a data quality in the file.
+fn quality_code(quality: DataQuality) -> u8 {
+    match quality {
+        DataQuality::Unknown => 0,
+        DataQuality::Guessed => 1,
+        DataQuality::Derived => 2,
+        DataQuality::Computed => 3,
+    }
+}
+
+/// Read a data quality written by `quality_code()`.
+fn read_quality(r: &mut impl Read) -> Result<DataQuality, SnapshotError> {
+    match read_u8(r)? {
+        0 => Ok(DataQuality::Unknown),
+        1 => Ok(DataQuality::Guessed),
+        2 => Ok(DataQuality::Derived),
+        3 => Ok(DataQuality::Computed),
+        code => Err(format_error(&format!("unknown data quality {}", code))),
+    }
+}
+
+/// Return the code of a supersampling mode in the file.
+fn supersampling_code(supersampling: Supersampling) -> u8 {
+    match supersampling {
+        Supersampling::Off => 0,
+        Supersampling::Grid2x2 => 1,
+        Supersampling::Grid3x3 => 2,
+        Supersampling::Grid4x4 => 3,
+        Supersampling::Jittered3x3 => 4,
+        Supersampling::Adaptive3x3 => 5,
+    }
+}
+
+/// Read a supersampling mode written by `supersampling_code()`.
+fn read_supersampling(r: &mut impl Read) -> Result<Supersampling, SnapshotError> {
+    match read_u8(r)? {
+        0 => Ok(Supersampling::Off),
+        1 => Ok(Supersampling::Grid2x2),
+        2 => Ok(Supersampling::Grid3x3),
+        3 => Ok(Supersampling::Grid4x4),
+        4 => Ok(Supersampling::Jittered3x3),
+        5 => Ok(Supersampling::Adaptive3x3),
+        code => Err(format_error(&format!("unknown supersampling {}", code))),
+    }
+}
+
+/// Return a format error with the given message.
+fn format_error(message: &str) -> SnapshotError {
+    SnapshotError::Format(message.to_string())
+}
+
+// Primitive values of the file format, all little-endian
+
+fn write_u8(w: &mut impl Write, value: u8) -> std::io::Result<()> {
+    w.write_all(&[value])
+}
+
+fn write_u32(w: &mut impl Write, value: u32) -> std::io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_f32(w: &mut impl Write, value: f32) -> std::io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_f64(w: &mut impl Write, value: f64) -> std::io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_string(w: &mut impl Write, value: &str) -> std::io::Result<()> {
+    write_u32(w, value.len() as u32)?;
+    w.write_all(value.as_bytes())
+}
+
+fn read_u8(r: &mut impl Read) -> std::io::Result<u8> {
+    let mut bytes = [0u8; 1];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u32(r: &mut impl Read) -> std::io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f32(r: &mut impl Read) -> std::io::Result<f32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+fn read_f64(r: &mut impl Read) -> std::io::Result<f64> {
+    let mut bytes = [0u8; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+fn read_string(r: &mut impl Read) -> Result<String, SnapshotError> {
+    let length = read_u32(r)?;
+    if length > MAX_STRING_LENGTH {
+        return Err(format_error("string too long"));
+    }
+    let mut bytes = vec![0u8; length as usize];
+    r.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| format_error("string is no valid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::param_presets::ParamPreset;
+
+    fn storage(supersampling: Supersampling) -> CompStorage {
+        let mut area = ParamPreset::MandelbrotSeahorseValley
+            .preset()
+            .math_area()
+            .unwrap();
+        area = MathArea::from_str(
+            &format!("{}123456789012345678901234567890", area.center().x),
+            &area.center().y.to_string(),
+            "0.000000000000000000000000000001",
+            &area.ratio().to_string(),
+        )
+        .unwrap();
+        CompStorage::new(ImageCompProperties::new(
+            StageProperties::new(RasteredMathArea::new(area, Size2D::new(12, 8))),
+            5000,
+            FractalType::Julia {
+                c_real: -0.8,
+                c_imag: 0.156,
+            },
+            64.0,
+            supersampling,
+        ))
+    }
+
+    #[test]
+    fn stage_survives_round_trip() {
+        let original = storage(Supersampling::Adaptive3x3);
+        let normal = Vector2D::new(0.6, 0.8);
+        original.stage.set(
+            3,
+            2,
+            DataPoint::computed(17, Point2D::new(1.5, -2.5), 16.25, Some((0.01, normal))),
+        );
+        original.stage.set_run(0, 12, 5, DataPoint::derived(5000));
+        original
+            .stage
+            .set(11, 7, DataPoint::derived(42).as_guessed());
+        original.stage.add_samples(
+            3,
+            2,
+            &[DataPoint::computed(18, Point2D::zero(), 17.5, None)],
+        );
+        original.stage.set_state(StageState::Evolving);
+        let mut bytes = Vec::new();
+        write_snapshot(&mut bytes, &original).unwrap();
+        let restored = read_snapshot(&mut bytes.as_slice()).unwrap();
+
+        let (old, new) = (
+            original
+                .original_properties
+                .stage_properties
+                .orig_area
+                .math_area(),
+            restored
+                .original_properties
+                .stage_properties
+                .orig_area
+                .math_area(),
+        );
+        assert_eq!(old.center(), new.center());
+        assert_eq!(old.radius(), new.radius());
+        assert_eq!(
+            original.original_properties.fractal_type,
+            restored.original_properties.fractal_type
+        );
+        assert_eq!(
+            Supersampling::Adaptive3x3,
+            restored.properties.supersampling
+        );
+        assert_eq!(StageState::Stalled, restored.stage.get_state());
+        let point = restored.stage.get(3, 2).unwrap();
+        assert_eq!(16.25, point.smooth_iteration);
+        assert_eq!(DataQuality::Computed, point.distance_estimate_quality);
+        assert_eq!(normal, point.surface_normal);
+        assert!(restored.stage.is_computed(7, 5));
+        assert_eq!(
+            DataQuality::Guessed,
+            restored.stage.get(11, 7).unwrap().iteration_count_quality
+        );
+        assert!(!restored.stage.is_computed(11, 7));
+        assert!(restored.stage.get(0, 0).is_none());
+        assert!(restored.stage.has_samples(3, 2));
+        assert_eq!(1, restored.stage.get_full_samples().len());
+    }
+
+    #[test]
+    fn supersampling_codes_round_trip() {
+        for &supersampling in Supersampling::all() {
+            let code = supersampling_code(supersampling);
+            assert_eq!(supersampling, read_supersampling(&mut &[code][..]).unwrap());
+        }
+        assert!(read_supersampling(&mut &[6u8][..]).is_err());
+    }
+
+    #[test]
+    fn damaged_snapshots_are_rejected() {
+        let mut bytes = Vec::new();
+        write_snapshot(&mut bytes, &storage(Supersampling::Grid2x2)).unwrap();
+        assert!(matches!(
+            read_snapshot(&mut &bytes[..bytes.len() - 1]),
+            Err(SnapshotError::Io(_))
+        ));
+        bytes[8] = 9;
+        assert!(matches!(
+            read_snapshot(&mut bytes.as_slice()),
+            Err(SnapshotError::UnsupportedVersion(9))
+        ));
+        assert!(matches!(
+            read_snapshot(&mut &b"MANDELRS"[..]),
+            Err(SnapshotError::Format(_))
+        ));
+        // The valid header ends before the stage, the damaged ones are rejected right away
+        assert!(matches!(
+            read_snapshot(&mut header("1", 8, -0.8, 64.0).as_slice()),
+            Err(SnapshotError::Io(_))
+        ));
+        for damaged in [
+            header("0", 8, -0.8, 64.0),
+            header("-1", 8, -0.8, 64.0),
+            header("1", 0, -0.8, 64.0),
+            header("1", 8, f64::NAN, 64.0),
+            header("1", 8, -0.8, f64::INFINITY),
+            header("1", 8, -0.8, 0.0),
+        ] {
+            assert!(matches!(
+                read_snapshot(&mut damaged.as_slice()),
+                Err(SnapshotError::Format(_))
+            ));
+        }
+    }
+
+    /// Return the beginning of a Julia set snapshot up to and including its properties.
+    fn header(ratio: &str, width: u32, c_real: f64, bailout: f64) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        write_u32(&mut bytes, 1).unwrap();
+        for value in ["-0.75", "0.1", "0.01", ratio] {
+            write_string(&mut bytes, value).unwrap();
+        }
+        for value in [width, 8, 100] {
+            write_u32(&mut bytes, value).unwrap();
+        }
+        write_u8(&mut bytes, 1).unwrap();
+        write_f64(&mut bytes, c_real).unwrap();
+        write_f64(&mut bytes, 0.156).unwrap();
+        write_f64(&mut bytes, bailout).unwrap();
+        write_u8(&mut bytes, 0).unwrap();
+        bytes
+    }
+}
+
+// end of file
@@ -0,0 +1,279 @@
+//! Tiled, sparse storage of a computation stage for images larger than the memory.
+//!
+//! A `CompStage` holds all pixels of an image in memory. For poster renders with
+//! billions of pixels, this is not possible. The `TiledStage` divides the stage into
+//! square tiles, each of which is a `CompStage` of its own.
+//!
+//! # Tile Lifecycle
+//!
+//! ```text
+//! Empty ──insert_tile()──→ Resident ──spill_tile()──→ Spilled
+//!   (no memory)           (CompStage in memory)       (file in the spill directory)
+//! ```
+//!
+//! - **On Demand**: Tiles occupy memory only from the moment their data is inserted,
+//!   a stage of empty tiles costs nothing but the tile table.
+//! - **Spilling**: Finished tiles can be moved into files of a spill directory, which
+//!   is removed together with the stage. Without spill directory, tiles stay resident.
+//! - **Reading**: `tile()` returns resident tiles directly and reads spilled tiles from
+//!   their file without keeping them, so a renderer can stream through all tiles while
+//!   only holding those it currently works on.
+//!
+//! Spill files use the stage part of the snapshot format, see `stage_snapshot::write_stage()`.
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use euclid::{Point2D, Rect, Size2D};
+
+use crate::storage::{
+    computation::{
+        comp_stage::CompStage,
+        stage_snapshot::{SnapshotError, read_stage, write_stage},
+    },
+    coord_spaces::StageSpace,
+};
+
+/// Number of the next spill directory, keeps the directories of one process apart
+static NEXT_SPILL_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// Storage state of one tile
+enum TileSlot {
+    /// No data has been inserted, no memory is allocated
+    Empty,
+    /// The tile's data is in memory
+    Resident(Arc<CompStage>),
+    /// The tile's data has been written into its spill file
+    Spilled,
+}
+
+/// Computation stage divided into separately stored square tiles.
+pub struct TiledStage {
+    /// Dimensions of the complete stage in pixels
+    size: Size2D<u32, StageSpace>,
+    /// Edge length of the tiles in pixels, tiles at the right and bottom edge may be smaller
+    tile_size: u32,
+    /// Number of tiles horizontally and vertically
+    tile_count: Size2D<u32, StageSpace>,
+    /// Storage state of all tiles in row-major order
+    tiles: Vec<Mutex<TileSlot>>,
+    /// Directory the tiles are spilled into, owned by this stage, `None` to keep tiles resident
+    spill_dir: Option<PathBuf>,
+}
+
+impl TiledStage {
+    /// Creates a tiled stage without any data.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Pixel dimensions of the complete stage
+    /// * `tile_size` - Edge length of the tiles in pixels, at least 1
+    /// * `spill_base` - Directory in which a spill directory for this stage is created,
+    ///   `None` to keep all tiles in memory
+    ///
+    /// # Returns
+    ///
+    /// The stage, or the I/O error which prevented creating the spill directory
+    pub fn new(
+        size: Size2D<u32, StageSpace>,
+        tile_size: u32,
+        spill_base: Option<&Path>,
+    ) -> std::io::Result<Self> {
+        let tile_size = tile_size.max(1);
+        let tile_count = Size2D::new(
+            size.width.div_ceil(tile_size),
+            size.height.div_ceil(tile_size),
+        );
+        let spill_dir = match spill_base {
+            Some(base) => {
+                let dir = base.join(format!(
+                    "mandel-rs-tiles-{}-{}",
+                    std::process::id(),
+                    NEXT_SPILL_DIR.fetch_add(1, Ordering::Relaxed)
+                ));
+                fs::create_dir_all(&dir)?;
+                Some(dir)
+            }
+            None => None,
+        };
+        Ok(TiledStage {
+            size,
+            tile_size,
+            tile_count,
+            tiles: (0..tile_count.area())
+                .map(|_| Mutex::new(TileSlot::Empty))
+                .collect(),
+            spill_dir,
+        })
+    }
+
+    /// Returns the number of tiles horizontally and vertically.
+    pub fn tile_count(&self) -> Size2D<u32, StageSpace> {
+        self.tile_count
+    }
+
+    /// Returns the pixel area of the tile at tile coordinates `(tx, ty)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile coordinates are outside the stage.
+    pub fn tile_rect(&self, tx: u32, ty: u32) -> Rect<u32, StageSpace> {
+        self.check_tile(tx, ty);
+        let origin = Point2D::new(tx * self.tile_size, ty * self.tile_size);
+        Rect::new(
+            origin,
+            Size2D::new(
+                self.tile_size.min(self.size.width - origin.x),
+                self.tile_size.min(self.size.height - origin.y),
+            ),
+        )
+    }
+
+    /// Stores the data of a tile in memory, replacing any previous data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile coordinates are outside the stage or the stage
+    /// does not have the size of the tile.
+    pub fn insert_tile(&self, tx: u32, ty: u32, stage: CompStage) {
+        let rect = self.tile_rect(tx, ty);
+        if stage.width() != rect.size.width as usize || stage.height() != rect.size.height as usize
+        {
+            panic!(
+                "Stage of size {}*{} does not fit tile ({},{}) of size {}*{}",
+                stage.width(),
+                stage.height(),
+                tx,
+                ty,
+                rect.size.width,
+                rect.size.height
+            );
+        }
+        *self.slot(tx, ty).lock().unwrap() = TileSlot::Resident(Arc::new(stage));
+    }
+
+    /// Moves a resident tile into its spill file and releases its memory.
+    ///
+    /// Does nothing if the stage has no spill directory or the tile is not resident.
+    pub fn spill_tile(&self, tx: u32, ty: u32) -> Result<(), SnapshotError> {
+        let Some(path) = self.spill_path(tx, ty) else {
+            return Ok(());
+        };
+        let mut slot = self.slot(tx, ty).lock().unwrap();
+        if let TileSlot::Resident(stage) = &*slot {
+            let mut w = BufWriter::new(File::create(&path)?);
+            write_stage(&mut w, stage)?;
+            w.flush()?;
+            *slot = TileSlot::Spilled;
+        }
+        Ok(())
+    }
+
+    /// Returns the data of a tile, reading it from its spill file if necessary.
+    ///
+    /// A spilled tile stays spilled, the returned stage is only held by the caller.
+    ///
+    /// # Returns
+    ///
+    /// The tile's stage, `None` for an empty tile, or the error which prevented reading it
+    pub fn tile(&self, tx: u32, ty: u32) -> Result<Option<Arc<CompStage>>, SnapshotError> {
+        let slot = self.slot(tx, ty).lock().unwrap();
+        match &*slot {
+            TileSlot::Empty => Ok(None),
+            TileSlot::Resident(stage) => Ok(Some(stage.clone())),
+            TileSlot::Spilled => {
+                let path = self.spill_path(tx, ty).unwrap();
+                let stage = read_stage(
+                    &mut BufReader::new(File::open(path)?),
+                    self.tile_rect(tx, ty).size,
+                )?;
+                Ok(Some(Arc::new(stage)))
+            }
+        }
+    }
+
+    /// Returns the number of tiles currently held in memory.
+    #[cfg(test)]
+    pub fn resident_count(&self) -> usize {
+        self.tiles
+            .iter()
+            .filter(|slot| matches!(*slot.lock().unwrap(), TileSlot::Resident(_)))
+            .count()
+    }
+
+    /// Returns the slot of the tile at `(tx, ty)`.
+    fn slot(&self, tx: u32, ty: u32) -> &Mutex<TileSlot> {
+        self.check_tile(tx, ty);
+        &self.tiles[(ty * self.tile_count.width + tx) as usize]
+    }
+
+    /// Returns the spill file of the tile at `(tx, ty)`, `None` without spill directory.
+    fn spill_path(&self, tx: u32, ty: u32) -> Option<PathBuf> {
+        self.spill_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("tile-{}-{}.bin", tx, ty)))
+    }
+
+    /// Panics if the tile coordinates are outside the stage.
+    fn check_tile(&self, tx: u32, ty: u32) {
+        if tx >= self.tile_count.width || ty >= self.tile_count.height {
+            panic!(
+                "Tile ({},{}) out of bounds for tiled stage with {}*{} tiles",
+                tx, ty, self.tile_count.width, self.tile_count.height
+            );
+        }
+    }
+}
+
+impl Drop for TiledStage {
+    /// Removes the spill directory with all spill files.
+    fn drop(&mut self) {
+        if let Some(dir) = &self.spill_dir {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::data_point::DataPoint;
+
+    #[test]
+    fn tiles_survive_spilling() {
+        let base = std::env::temp_dir().join("mandel-rs-tiled-stage-test");
+        let stage = TiledStage::new(Size2D::new(10, 7), 4, Some(&base)).unwrap();
+        assert_eq!(Size2D::new(3, 2), stage.tile_count());
+        assert_eq!(
+            Rect::new(Point2D::new(8, 4), Size2D::new(2, 3)),
+            stage.tile_rect(2, 1)
+        );
+        assert!(stage.tile(2, 1).unwrap().is_none());
+
+        let tile = CompStage::new(Size2D::new(2, 3));
+        tile.set(1, 2, DataPoint::derived(42));
+        stage.insert_tile(2, 1, tile);
+        assert_eq!(1, stage.resident_count());
+        stage.spill_tile(2, 1).unwrap();
+        assert_eq!(0, stage.resident_count());
+
+        let restored = stage.tile(2, 1).unwrap().unwrap();
+        assert_eq!(42, restored.get(1, 2).unwrap().iteration_count);
+        assert!(restored.get(0, 0).is_none());
+        assert_eq!(0, stage.resident_count());
+
+        let spill_dir = stage.spill_dir.clone().unwrap();
+        assert!(spill_dir.exists());
+        drop(stage);
+        assert!(!spill_dir.exists());
+    }
+}
+
+// end of file
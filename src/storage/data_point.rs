@@ -23,7 +23,7 @@
 //! let estimated = some_computed_point.as_guessed();
 //! ```
 
-use euclid::Point2D;
+use euclid::{Point2D, Vector2D};
 
 use crate::storage::coord_spaces::MathSpace;
 
@@ -74,6 +74,9 @@ impl DataQuality {
 ///
 /// - **Iteration Data**: How many iterations before escape (or max reached)
 /// - **Final Coordinate**: The final z-value after iteration (for smooth coloring)
+/// - **Smooth Iteration**: Fractional iteration count for band-free coloring
+/// - **Distance Estimate**: Distance of the point to the fractal, from the derivative `dz/dc`
+/// - **Surface Normal**: Direction of `z / (dz/dc)` for 3D lighting effects
 /// - **Quality Tracking**: Confidence level for both iteration count and coordinate
 ///
 /// # Mathematical Context
@@ -81,6 +84,8 @@ impl DataQuality {
 /// For Mandelbrot computation:
 /// - `iteration_count`: Number of iterations before |z| > 2.0 (or max_iteration)
 /// - `final_coordinate`: The z-value after the final iteration
+/// - `smooth_iteration`: `μ = n + 1 - log₂(ln|z|)`, continuous across iteration bands
+/// - `distance_estimate`: Exterior distance `2·|z|·ln|z| / |dz/dc|` in mathematical units
 /// - Quality indicates whether values are computed, estimated, or derived
 ///
 /// # Memory Layout
@@ -98,6 +103,14 @@ pub struct DataPoint {
     pub final_coordinate: Point2D<f64, MathSpace>,
     /// Quality/confidence level of the final coordinate value
     pub final_coordinate_quality: DataQuality,
+    /// Normalized fractional iteration count, same quality as the iteration count
+    pub smooth_iteration: f32,
+    /// Estimated distance of an escaped point to the fractal in mathematical units
+    pub distance_estimate: f64,
+    /// Quality/confidence level of the distance estimate, `Unknown` for non-escaped points
+    pub distance_estimate_quality: DataQuality,
+    /// Unit surface normal of an escaped point, same quality as the distance estimate
+    pub surface_normal: Vector2D<f32, MathSpace>,
 }
 
 impl DataPoint {
@@ -109,21 +122,34 @@ impl DataPoint {
     /// * `iteration_count_quality` - Confidence level for iteration count
     /// * `final_coordinate` - Final z-value in mathematical coordinates
     /// * `final_coordinate_quality` - Confidence level for final coordinate
+    /// * `smooth_iteration` - Fractional iteration count
+    /// * `distance_estimate` - Estimated distance to the fractal
+    /// * `distance_estimate_quality` - Confidence level for distance estimate and surface normal
+    /// * `surface_normal` - Unit surface normal of the point
     ///
     /// # Returns
     ///
     /// A new `DataPoint` with the specified values and quality metadata
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         iteration_count: u32,
         iteration_count_quality: DataQuality,
         final_coordinate: Point2D<f64, MathSpace>,
         final_coordinate_quality: DataQuality,
+        smooth_iteration: f32,
+        distance_estimate: f64,
+        distance_estimate_quality: DataQuality,
+        surface_normal: Vector2D<f32, MathSpace>,
     ) -> DataPoint {
         DataPoint {
             iteration_count,
             iteration_count_quality,
             final_coordinate,
             final_coordinate_quality,
+            smooth_iteration,
+            distance_estimate,
+            distance_estimate_quality,
+            surface_normal,
         }
     }
     /// Creates a data point from direct fractal computation.
@@ -131,29 +157,73 @@ impl DataPoint {
     /// Convenience constructor for results from actual Mandelbrot iteration.
     /// Both the iteration count and final coordinate are marked as `Computed`
     /// quality, indicating they were obtained through direct mathematical
-    /// calculation rather than estimation or interpolation.
+    /// calculation rather than estimation or interpolation. Distance estimate and
+    /// surface normal are `Computed` if given, `Unknown` otherwise.
     ///
     /// # Arguments
     ///
     /// * `iteration_count` - Iterations before escape (or max_iteration)
     /// * `final_coordinate` - Final z-value from iteration
+    /// * `smooth_iteration` - Fractional iteration count from the final z-value
+    /// * `distance_and_normal` - Distance estimate and surface normal if the point escaped
+    ///   and the formula supports it
     ///
     /// # Returns
     ///
-    /// A new `DataPoint` with `Computed` quality for all fields
+    /// A new `DataPoint` with `Computed` quality for all known fields
     ///
     /// # Usage
     ///
     /// ```rust
     /// // From Mandelbrot iteration algorithm
-    /// let result = DataPoint::computed(42, Point2D::new(1.5, 2.1));
+    /// let result = DataPoint::computed(42, Point2D::new(1.5, 2.1), 41.7, Some((0.001, normal)));
     /// ```
-    pub fn computed(iteration_count: u32, final_coordinate: Point2D<f64, MathSpace>) -> DataPoint {
+    pub fn computed(
+        iteration_count: u32,
+        final_coordinate: Point2D<f64, MathSpace>,
+        smooth_iteration: f32,
+        distance_and_normal: Option<(f64, Vector2D<f32, MathSpace>)>,
+    ) -> DataPoint {
+        let (distance_estimate, distance_estimate_quality, surface_normal) =
+            match distance_and_normal {
+                Some((distance, normal)) => (distance, DataQuality::Computed, normal),
+                None => (0.0, DataQuality::Unknown, Vector2D::zero()),
+            };
         Self::new(
             iteration_count,
             DataQuality::Computed,
             final_coordinate,
             DataQuality::Computed,
+            smooth_iteration,
+            distance_estimate,
+            distance_estimate_quality,
+            surface_normal,
+        )
+    }
+    /// Creates a data point whose iteration count is derived from surrounding computed points.
+    ///
+    /// Used by computation strategies which can deduce the iteration count of a point
+    /// without iterating it, e.g. the interior of a rectangle with a uniform border.
+    /// The final coordinate is not known for such points, the smooth iteration
+    /// count falls back to the integer iteration count.
+    ///
+    /// # Arguments
+    ///
+    /// * `iteration_count` - Derived iteration count
+    ///
+    /// # Returns
+    ///
+    /// A new `DataPoint` with `Derived` iteration count and `Unknown` final coordinate
+    pub fn derived(iteration_count: u32) -> DataPoint {
+        Self::new(
+            iteration_count,
+            DataQuality::Derived,
+            Point2D::zero(),
+            DataQuality::Unknown,
+            iteration_count as f32,
+            0.0,
+            DataQuality::Unknown,
+            Vector2D::zero(),
         )
     }
     /// Creates a copy of this data point with quality downgraded to `Guessed`.
@@ -185,12 +255,21 @@ impl DataPoint {
             DataQuality::Guessed,
             self.final_coordinate,
             DataQuality::Guessed,
+            self.smooth_iteration,
+            self.distance_estimate,
+            if self.distance_estimate_quality == DataQuality::Unknown {
+                DataQuality::Unknown
+            } else {
+                DataQuality::Guessed
+            },
+            self.surface_normal,
         )
     }
     /// Creates a copy of this data point containing the data for a changed maximum iteration.
     ///
     /// If the current data max iteration is deeper than then requested new maximum iteration depth,
     /// a point the the new max iteration depth (and unknown final coordinate) is returned.
+    /// Such a point does not escape anymore, so it has no distance estimate either.
     /// If the current data max iteration is equal to the old max iteration (and the new max iteration
     /// is deeper than the old max iteration) an empty data point is returned to invalidate this data.
     /// In all other cases, a copy of this data point is returned.
@@ -205,6 +284,10 @@ impl DataPoint {
                 self.iteration_count_quality,
                 Point2D::zero(),
                 DataQuality::Unknown,
+                new_max_iteration as f32,
+                0.0,
+                DataQuality::Unknown,
+                Vector2D::zero(),
             ))
         } else if self.iteration_count == old_max_iteration && new_max_iteration > old_max_iteration
         {
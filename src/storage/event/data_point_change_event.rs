@@ -15,7 +15,8 @@
 //!
 //! ## Event Types
 //!
-//! - **DataPointChange**: Single pixel update with coordinate and data
+//! - **DataPointChange**: Single pixel or horizontal run update with coordinates and data,
+//!   or an additional sub-pixel sample of an edge pixel
 //! - **DataPointMultiChange**: Batched collection of pixel updates
 //!
 //! ## Design Principles
@@ -37,20 +38,24 @@
 
 use crate::storage::data_point::DataPoint;
 
-/// Represents a single pixel update event in the computation-to-visualization pipeline.
+/// Represents a pixel update event in the computation-to-visualization pipeline.
 ///
-/// Contains the complete information needed to update a specific pixel in the
+/// Contains the complete information needed to update pixels in the
 /// visualization system: the pixel coordinates and the new fractal computation
 /// result. This is the atomic unit of change in the event system.
 ///
 /// # Event Content
 ///
-/// - **Coordinates**: Pixel position in the computation grid
-/// - **Data**: Complete fractal computation result (iteration count + final z-value)
+/// - **Point**: One pixel position in the computation grid and its data
+/// - **Run**: A horizontal sequence of pixels in one row which all get the _same_ data.
+///   Filling algorithms like boundary tracing set large areas this way with one event per row.
+/// - **Sample**: An additional sub-pixel sample of one pixel, added by adaptive anti-aliasing.
+///   The pixel's own data point is not changed. The sample carries its position in the
+///   pixel's sample list, so applying it twice replaces the sample instead of adding it again.
 ///
 /// # Memory Efficiency
 ///
-/// This struct is `Copy` to enable efficient transmission through async channels
+/// This enum is `Copy` to enable efficient transmission through async channels
 /// without allocation overhead. The contained `DataPoint` is also `Copy` for
 /// the same reason.
 ///
@@ -60,13 +65,38 @@ use crate::storage::data_point::DataPoint;
 /// transmitted through the event system, and applied by `VizStorage`
 /// to maintain visualization synchronization.
 #[derive(Debug, Clone, Copy)]
-pub struct DataPointChange {
-    /// X coordinate of the changed pixel (0 to width-1)
-    pub x: u32,
-    /// Y coordinate of the changed pixel (0 to height-1)
-    pub y: u32,
-    /// New fractal computation data for this pixel
-    pub data: DataPoint,
+pub enum DataPointChange {
+    /// Change of a single pixel
+    Point {
+        /// X coordinate of the changed pixel (0 to width-1)
+        x: u32,
+        /// Y coordinate of the changed pixel (0 to height-1)
+        y: u32,
+        /// New fractal computation data for this pixel
+        data: DataPoint,
+    },
+    /// Change of the pixels `x_start..x_end` in row `y` to the same data
+    Run {
+        /// X coordinate of the first changed pixel (inclusive)
+        x_start: u32,
+        /// X coordinate after the last changed pixel (exclusive)
+        x_end: u32,
+        /// Y coordinate of the changed row (0 to height-1)
+        y: u32,
+        /// New fractal computation data for all pixels of the run
+        data: DataPoint,
+    },
+    /// Additional sub-pixel sample of a single pixel
+    Sample {
+        /// X coordinate of the refined pixel (0 to width-1)
+        x: u32,
+        /// Y coordinate of the refined pixel (0 to height-1)
+        y: u32,
+        /// Position of the sample in the list of samples of the pixel
+        sample: u32,
+        /// Fractal computation data of the sample
+        data: DataPoint,
+    },
 }
 
 impl DataPointChange {
@@ -82,7 +112,55 @@ impl DataPointChange {
     ///
     /// Event ready for transmission through the event system
     pub fn new(x: u32, y: u32, data: &DataPoint) -> Self {
-        DataPointChange { x, y, data: *data }
+        DataPointChange::Point { x, y, data: *data }
+    }
+    /// Creates a new horizontal run change event.
+    ///
+    /// # Arguments
+    ///
+    /// * `x_start` - X coordinate of the first pixel of the run
+    /// * `x_end` - X coordinate after the last pixel of the run
+    /// * `y` - Pixel Y coordinate of the run
+    /// * `data` - Fractal computation result to store in all pixels of the run
+    ///
+    /// # Returns
+    ///
+    /// Event ready for transmission through the event system
+    pub fn new_run(x_start: u32, x_end: u32, y: u32, data: &DataPoint) -> Self {
+        DataPointChange::Run {
+            x_start,
+            x_end,
+            y,
+            data: *data,
+        }
+    }
+    /// Creates a new sub-pixel sample event.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X coordinate of the refined pixel
+    /// * `y` - Y coordinate of the refined pixel
+    /// * `sample` - Position of the sample in the list of samples of the pixel
+    /// * `data` - Fractal computation result of the sample
+    ///
+    /// # Returns
+    ///
+    /// Event ready for transmission through the event system
+    pub fn new_sample(x: u32, y: u32, sample: u32, data: &DataPoint) -> Self {
+        DataPointChange::Sample {
+            x,
+            y,
+            sample,
+            data: *data,
+        }
+    }
+    /// Returns the number of pixels changed by this event.
+    #[allow(dead_code)] // Public API for future use and debugging
+    pub fn pixel_count(&self) -> usize {
+        match self {
+            DataPointChange::Point { .. } | DataPointChange::Sample { .. } => 1,
+            DataPointChange::Run { x_start, x_end, .. } => x_end.saturating_sub(*x_start) as usize,
+        }
     }
 }
 
@@ -37,19 +37,40 @@
 //!
 //! # Event Types
 //!
-//! - **ContentChange**: Individual pixel updates (batched)
+//! - **ContentChange**: Individual pixel or horizontal run updates (batched)
 //! - **ContentMultiChange**: Pre-batched updates (re-batched)
 //! - **StateChange**: Computation state transitions (immediate pass-through)
+//! - **Resync**: Replacement for dropped updates (emitted by the batcher only)
 //!
 //! The batcher optimizes pixel updates while ensuring state changes are
 //! transmitted immediately for accurate progress tracking.
+//!
+//! # Bounded Channels
+//!
+//! Both channels of the event flow are bounded, so a visualization falling behind
+//! a fast engine cannot make the queued changes grow without limit:
+//!
+//! - **Batcher → VizStorage**: The batcher waits for room in the output channel,
+//!   while waiting it does not read its input.
+//! - **CompStage → Batcher**: Computation threads never wait. If the channel is full,
+//!   `StageEventSender` drops the change and marks the changes as lost. The batcher
+//!   then discards everything pending and emits one `StageEvent::Resync`, which tells
+//!   the visualization to rebuild its data from the `CompStage`. Dropped state changes
+//!   are kept aside and delivered after the resync.
 
 use std::{
     pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
     time::{Duration, Instant},
 };
 
-use tokio::sync::mpsc;
+use tokio::sync::{
+    Notify,
+    mpsc::{self, error::TrySendError},
+};
 
 use crate::storage::{
     event::data_point_change_event::{DataPointChange, DataPointMultiChange},
@@ -66,22 +87,134 @@ use crate::storage::{
 ///
 /// - **State Events**: Overall computation progress (start/stop/complete)
 /// - **Content Events**: Individual pixel updates and batched collections
+/// - **Resync Event**: Content updates have been lost, all content must be re-read
 ///
 /// # Processing Strategy
 ///
 /// - **StateChange**: Immediate transmission (not batched)
+/// - **InitialState**: Immediate transmission, never terminates the stream
 /// - **ContentChange**: Batched for efficiency
 /// - **ContentMultiChange**: Re-batched with other events
+/// - **Resync**: Replaces all pending content events
+#[derive(Clone)]
 pub enum StageEvent {
     /// Computation state transition (Initialized/Evolving/Stalled/Completed)
     /// Processed immediately without batching for accurate progress tracking
     StateChange(StageState),
-    /// Single pixel update from computation thread
-    /// Subject to batching for efficient transmission
+    /// State of the stage when the receiver subscribed, first event of every subscription
+    /// Informational only, a stalled stage may be restarted by the engine afterwards
+    InitialState(StageState),
+    /// Single pixel or horizontal run update from computation thread
+    /// Subject to batching for efficient transmission, a run counts as one change
     ContentChange(DataPointChange),
     /// Pre-batched collection of pixel updates
     /// Re-batched with other events for optimal efficiency
     ContentMultiChange(DataPointMultiChange),
+    /// Content changes have been dropped because a channel was full
+    /// The receiver must rebuild its data from the computation stage
+    Resync,
+}
+
+/// Events which did not fit into a full stage event channel.
+///
+/// Shared between `StageEventSender` and `StageEventReceiver`, so the
+/// receiving side learns about everything the sending side had to drop.
+struct EventOverflow {
+    /// Set if content changes have been dropped, the receiver must resync
+    changes_lost: AtomicBool,
+    /// Latest state change which has been dropped
+    lost_state: Mutex<Option<StageState>>,
+    /// Wakes up the receiver after something has been dropped
+    notify: Notify,
+}
+
+/// Sending side of a bounded stage event channel, used by `CompStage`.
+///
+/// Sending never blocks, so computation threads are not slowed down by a
+/// slow visualization. Events which do not fit into the channel are recorded
+/// as overflow instead, see the module documentation.
+#[derive(Clone)]
+pub struct StageEventSender {
+    /// Bounded channel to the receiver
+    sender: mpsc::Sender<StageEvent>,
+    /// Overflow shared with the receiver
+    overflow: Arc<EventOverflow>,
+}
+
+impl StageEventSender {
+    /// Sends an event, recording it as overflow if the channel is full.
+    ///
+    /// Dropped content changes only set a flag, as the receiver resyncs
+    /// from the stage anyway. A dropped state change is stored, replacing
+    /// an older dropped one. Events for a closed channel are discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Event to send
+    pub fn send(&self, event: StageEvent) {
+        if let Err(TrySendError::Full(event)) = self.sender.try_send(event) {
+            match event {
+                StageEvent::StateChange(state) => {
+                    *self.overflow.lost_state.lock().unwrap() = Some(state);
+                }
+                _ => self.overflow.changes_lost.store(true, Ordering::Release),
+            }
+            self.overflow.notify.notify_one();
+        }
+    }
+
+    /// Checks whether the receiver has gone, so that sending is pointless.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// Checks whether both senders send into the same channel.
+    pub fn same_channel(&self, other: &StageEventSender) -> bool {
+        self.sender.same_channel(&other.sender)
+    }
+}
+
+/// Receiving side of a bounded stage event channel, consumed by `StageEventBatcher`.
+pub struct StageEventReceiver {
+    /// Bounded channel from the senders
+    receiver: mpsc::Receiver<StageEvent>,
+    /// Overflow shared with the senders
+    overflow: Arc<EventOverflow>,
+}
+
+#[cfg(test)]
+impl StageEventReceiver {
+    /// Receives an event from the channel without waiting.
+    ///
+    /// Only reads the channel itself, the overflow is handled by `StageEventBatcher::run()`.
+    pub fn try_recv(&mut self) -> Result<StageEvent, mpsc::error::TryRecvError> {
+        self.receiver.try_recv()
+    }
+}
+
+/// Creates a bounded stage event channel.
+///
+/// # Arguments
+///
+/// * `capacity` - Maximum number of queued events, at least 1
+///
+/// # Returns
+///
+/// Sender for the computation stage and receiver for the event batcher
+pub fn stage_event_channel(capacity: usize) -> (StageEventSender, StageEventReceiver) {
+    let (sender, receiver) = mpsc::channel(capacity.max(1));
+    let overflow = Arc::new(EventOverflow {
+        changes_lost: AtomicBool::new(false),
+        lost_state: Mutex::new(None),
+        notify: Notify::new(),
+    });
+    (
+        StageEventSender {
+            sender,
+            overflow: overflow.clone(),
+        },
+        StageEventReceiver { receiver, overflow },
+    )
 }
 
 /// Internal buffering system for accumulating pixel change events.
@@ -199,7 +332,7 @@ impl DataPointChangeBuffer {
 /// ## Event Processing Rules
 /// - **ContentChange**: Accumulated in buffer for batching
 /// - **ContentMultiChange**: Individual changes re-batched with buffer contents
-/// - **StateChange**: Immediate pass-through + buffer flush for final states
+/// - **StateChange**: Immediate pass-through, final states flush the buffer first
 ///
 /// # Async Architecture
 ///
@@ -277,18 +410,20 @@ impl StageEventBatcher {
     /// # Behavior
     ///
     /// - Converts buffer contents to `ContentMultiChange` event
-    /// - Transmits batched event through output channel
+    /// - Transmits batched event through output channel, waiting for room in it
     /// - Clears buffer and timer for next batch cycle
     /// - Safe to call even when buffer is empty (no-op)
-    fn flush_buffer_and_clear_timer(
+    async fn flush_buffer_and_clear_timer(
         &self,
         buffer: &mut Option<DataPointChangeBuffer>,
         timer: &mut Option<Pin<Box<tokio::time::Sleep>>>,
-        output: &mpsc::UnboundedSender<StageEvent>,
+        output: &mpsc::Sender<StageEvent>,
     ) {
         if let Some(buf) = buffer.take() {
             let multi_change = buf.into_multi_change();
-            let _ = output.send(StageEvent::ContentMultiChange(multi_change));
+            let _ = output
+                .send(StageEvent::ContentMultiChange(multi_change))
+                .await;
         }
         *timer = None;
     }
@@ -319,14 +454,14 @@ impl StageEventBatcher {
     ///
     /// Buffer is automatically flushed when capacity is reached,
     /// ensuring timely transmission without manual intervention.
-    fn push_data_point_change_to_buffer(
+    async fn push_data_point_change_to_buffer(
         &self,
         change: DataPointChange,
         current_buffer: &mut Option<DataPointChangeBuffer>,
         timer: &mut Option<Pin<Box<tokio::time::Sleep>>>,
         max_capacity: usize,
         max_interval: Duration,
-        output: &mpsc::UnboundedSender<StageEvent>,
+        output: &mpsc::Sender<StageEvent>,
     ) {
         // Create new buffer and timer if this is the first change in a batch
         if current_buffer.is_none() {
@@ -341,7 +476,146 @@ impl StageEventBatcher {
             .push_data_point_change(change);
         // Check if buffer has reached capacity and flush if needed
         if current_buffer.as_ref().unwrap().is_capacity_exceeded() {
-            self.flush_buffer_and_clear_timer(current_buffer, timer, output);
+            self.flush_buffer_and_clear_timer(current_buffer, timer, output)
+                .await;
+        }
+    }
+
+    /// Processes one event received from the computation system.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Received event
+    /// * `current_buffer` - Mutable reference to current buffer state
+    /// * `timer` - Mutable reference to timeout timer
+    /// * `output` - Channel for transmitting events
+    ///
+    /// # Returns
+    ///
+    /// `true` if the event was a terminal state change and the batcher must stop
+    async fn handle_event(
+        &self,
+        event: StageEvent,
+        current_buffer: &mut Option<DataPointChangeBuffer>,
+        timer: &mut Option<Pin<Box<tokio::time::Sleep>>>,
+        output: &mpsc::Sender<StageEvent>,
+    ) -> bool {
+        match event {
+            // Single pixel update - add to batch buffer
+            StageEvent::ContentChange(change) => {
+                self.push_data_point_change_to_buffer(
+                    change,
+                    current_buffer,
+                    timer,
+                    self.max_capacity,
+                    self.max_interval,
+                    output,
+                )
+                .await;
+            }
+
+            // Pre-batched changes - re-batch with current buffer
+            StageEvent::ContentMultiChange(multi_change) => {
+                // Add each individual change to the current batch
+                for change in multi_change.changes() {
+                    self.push_data_point_change_to_buffer(
+                        *change,
+                        current_buffer,
+                        timer,
+                        self.max_capacity,
+                        self.max_interval,
+                        output,
+                    )
+                    .await;
+                }
+            }
+
+            // Computation state change - immediate transmission
+            StageEvent::StateChange(new_state) => {
+                // Terminal states trigger cleanup and shutdown, the last changes precede them
+                let terminal =
+                    new_state == StageState::Stalled || new_state == StageState::Completed;
+                if terminal {
+                    self.flush_buffer_and_clear_timer(current_buffer, timer, output)
+                        .await;
+                }
+
+                // Forward state change immediately (not batched)
+                let _ = output.send(StageEvent::StateChange(new_state)).await;
+                return terminal; // A finished computation terminates the batcher
+            }
+
+            // Initial state - immediate transmission, computation may still start
+            StageEvent::InitialState(state) => {
+                let _ = output.send(StageEvent::InitialState(state)).await;
+            }
+
+            // Resync requests are created by the batcher, never sent to it
+            StageEvent::Resync => {}
+        }
+        false
+    }
+
+    /// Handles events which have been dropped because the input channel was full.
+    ///
+    /// If content changes have been lost, the pending batch and all content
+    /// events still queued in the input are discarded and replaced by one
+    /// `Resync` event. Queued state changes are processed as usual, followed
+    /// by a dropped state change, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Receiver of the events from the computation system
+    /// * `current_buffer` - Mutable reference to current buffer state
+    /// * `timer` - Mutable reference to timeout timer
+    /// * `output` - Channel for transmitting events
+    ///
+    /// # Returns
+    ///
+    /// `true` if a terminal state change was processed and the batcher must stop
+    async fn handle_overflow(
+        &self,
+        input: &mut StageEventReceiver,
+        current_buffer: &mut Option<DataPointChangeBuffer>,
+        timer: &mut Option<Pin<Box<tokio::time::Sleep>>>,
+        output: &mpsc::Sender<StageEvent>,
+    ) -> bool {
+        let changes_lost = input.overflow.changes_lost.swap(false, Ordering::Acquire);
+        if changes_lost {
+            // The resync reads the stage after all dropped changes have been written to it
+            *current_buffer = None;
+            *timer = None;
+            let _ = output.send(StageEvent::Resync).await;
+        }
+        // Only look at the events queued now, senders may keep filling the channel
+        for _ in 0..input.receiver.len() {
+            let Ok(event) = input.receiver.try_recv() else {
+                break;
+            };
+            let is_content = !matches!(
+                event,
+                StageEvent::StateChange(_) | StageEvent::InitialState(_)
+            );
+            if !(changes_lost && is_content)
+                && self
+                    .handle_event(event, current_buffer, timer, output)
+                    .await
+            {
+                return true;
+            }
+        }
+        let lost_state = input.overflow.lost_state.lock().unwrap().take();
+        match lost_state {
+            Some(state) => {
+                self.handle_event(
+                    StageEvent::StateChange(state),
+                    current_buffer,
+                    timer,
+                    output,
+                )
+                .await
+            }
+            None => false,
         }
     }
 
@@ -353,99 +627,72 @@ impl StageEventBatcher {
     ///
     /// # Arguments
     ///
-    /// * `input` - Async receiver for events from computation system
-    /// * `output` - Async sender for batched events to visualization system
+    /// * `input` - Bounded receiver for events from computation system
+    /// * `output` - Bounded sender for batched events to visualization system
     ///
     /// # Async Architecture
     ///
-    /// The event loop handles three main conditions:
+    /// The event loop handles five main conditions:
     /// 1. **Event Reception**: New events from input channel
-    /// 2. **Timer Expiration**: Time-based buffer flushing
-    /// 3. **Channel Closure**: Graceful shutdown on input termination
+    /// 2. **Overflow**: Events dropped by the senders of the full input channel
+    /// 3. **Timer Expiration**: Time-based buffer flushing
+    /// 4. **Channel Closure**: Graceful shutdown on input termination
+    /// 5. **Consumer Gone**: Shutdown when the output receiver has been dropped
     ///
     /// # Event Processing Logic
     ///
     /// - **ContentChange**: Added to buffer, capacity-checked
     /// - **ContentMultiChange**: Individual changes re-batched
     /// - **StateChange**: Immediate pass-through + terminal state handling
+    /// - **Overflow**: Pending changes collapsed into one `Resync` event
     ///
     /// # Graceful Shutdown
     ///
     /// - Input channel closure triggers final buffer flush
     /// - All pending changes transmitted before termination
     /// - Output channel automatically closed on function exit
+    /// - Dropped output receiver stops the batcher, which closes its input channel,
+    ///   so that the stage stops sending to it
     ///
-    /// # Performance
+    /// # Back-pressure
     ///
-    /// - Non-blocking async operation
-    /// - Minimal CPU usage when idle
-    /// - Efficient batch processing during high activity
-    /// - Automatic resource cleanup
-    pub async fn run(
-        self,
-        mut input: mpsc::UnboundedReceiver<StageEvent>,
-        output: mpsc::UnboundedSender<StageEvent>,
-    ) {
+    /// - Waits for room in the output channel, a slow consumer slows down the batcher
+    /// - Meanwhile the input channel fills up and overflows into a resync
+    /// - Memory usage is limited by the capacities of both channels
+    pub async fn run(self, mut input: StageEventReceiver, output: mpsc::Sender<StageEvent>) {
         let mut current_buffer: Option<DataPointChangeBuffer> = None;
         let mut timer: Option<Pin<Box<tokio::time::Sleep>>> = None;
+        let overflow = input.overflow.clone();
 
         loop {
             tokio::select! {
                 // Branch 1: Event received from computation system
-                result = input.recv() => {
+                result = input.receiver.recv() => {
                     match result {
                         // Branch 1.1: Input channel closed - graceful shutdown
                         None => {
                             // Flush any pending changes before terminating
-                            self.flush_buffer_and_clear_timer(&mut current_buffer, &mut timer, &output);
+                            self.flush_buffer_and_clear_timer(&mut current_buffer, &mut timer, &output).await;
                             break; // Exit loop, dropping output sender closes output channel
                         }
 
                         // Branch 1.2: New event received - process based on type
                         Some(event) => {
-                            match event {
-                                // Single pixel update - add to batch buffer
-                                StageEvent::ContentChange(change) => {
-                                    self.push_data_point_change_to_buffer(
-                                        change,
-                                        &mut current_buffer,
-                                        &mut timer,
-                                        self.max_capacity,
-                                        self.max_interval,
-                                        &output);
-                                }
-
-                                // Pre-batched changes - re-batch with current buffer
-                                StageEvent::ContentMultiChange(multi_change) => {
-                                    // Add each individual change to the current batch
-                                    for change in multi_change.changes() {
-                                        self.push_data_point_change_to_buffer(
-                                            *change,
-                                            &mut current_buffer,
-                                            &mut timer,
-                                            self.max_capacity,
-                                            self.max_interval,
-                                            &output);
-                                    }
-                                }
-
-                                // Computation state change - immediate transmission
-                                StageEvent::StateChange(new_state) => {
-                                    // Forward state change immediately (not batched)
-                                    let _ = output.send(StageEvent::StateChange(new_state));
-
-                                    // Terminal states trigger cleanup and shutdown
-                                    if new_state == StageState::Stalled || new_state == StageState::Completed {
-                                        self.flush_buffer_and_clear_timer(&mut current_buffer, &mut timer, &output);
-                                        break; // Computation finished, terminate batcher
-                                    }
-                                }
+                            if self.handle_event(event, &mut current_buffer, &mut timer, &output).await {
+                                break; // Computation finished, terminate batcher
                             }
                         }
                     }
                 }
 
-                // Branch 2: Timeout timer expired - flush buffer
+                // Branch 2: Senders dropped events - resync and deliver lost state
+                () = overflow.notify.notified() => {
+                    if self.handle_overflow(&mut input, &mut current_buffer, &mut timer, &output).await {
+                        break; // Computation finished, terminate batcher
+                    }
+                }
+
+                // Branch 3: Timeout timer expired - flush buffer
                 () = async {
                     if let Some(t) = timer.as_mut() {
                         t.await // Wait for timer if one exists
@@ -454,7 +701,12 @@ impl StageEventBatcher {
                     }
                 } => {
                     // Time limit reached - flush buffer to maintain UI responsiveness
-                    self.flush_buffer_and_clear_timer(&mut current_buffer, &mut timer, &output);
+                    self.flush_buffer_and_clear_timer(&mut current_buffer, &mut timer, &output).await;
+                }
+
+                // Branch 4: Consumer dropped its receiver - nobody listens anymore
+                () = output.closed() => {
+                    break; // Exit loop, dropping input receiver closes input channel
                 }
 
             }
@@ -462,4 +714,127 @@ impl StageEventBatcher {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::data_point::DataPoint;
+
+    fn change(x: u32) -> StageEvent {
+        StageEvent::ContentChange(DataPointChange::new(x, 0, &DataPoint::derived(x)))
+    }
+
+    /// Collects all events of the batcher output until it is closed.
+    async fn collect(mut receiver: mpsc::Receiver<StageEvent>) -> Vec<StageEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = receiver.recv().await {
+            events.push(event);
+        }
+        events
+    }
+
+    fn pixel_count(events: &[StageEvent]) -> usize {
+        events
+            .iter()
+            .map(|event| match event {
+                StageEvent::ContentMultiChange(changes) => {
+                    changes.changes().iter().map(|c| c.pixel_count()).sum()
+                }
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn fast_consumer_gets_all_changes() {
+        let (sender, receiver) = stage_event_channel(8);
+        let (output, viz_receiver) = mpsc::channel(100);
+        let batcher =
+            tokio::spawn(StageEventBatcher::new(4, Duration::from_millis(1)).run(receiver, output));
+        for x in 0..100 {
+            sender.send(change(x));
+            tokio::task::yield_now().await;
+        }
+        sender.send(StageEvent::StateChange(StageState::Completed));
+        let events = collect(viz_receiver).await;
+        batcher.await.unwrap();
+        assert_eq!(100, pixel_count(&events));
+        assert!(!events.iter().any(|e| matches!(e, StageEvent::Resync)));
+    }
+
+    #[tokio::test]
+    async fn slow_consumer_gets_resync_instead_of_lost_changes() {
+        let (sender, receiver) = stage_event_channel(8);
+        let (output, viz_receiver) = mpsc::channel(2);
+        let batcher =
+            tokio::spawn(StageEventBatcher::new(4, Duration::from_millis(1)).run(receiver, output));
+        // The batcher does not run before the first await, so the channel overflows
+        for x in 0..1000 {
+            sender.send(change(x));
+        }
+        sender.send(StageEvent::StateChange(StageState::Completed));
+        assert_eq!(8, sender.sender.max_capacity() - sender.sender.capacity());
+        let events = collect(viz_receiver).await;
+        batcher.await.unwrap();
+        assert!(pixel_count(&events) <= 8);
+        let resync = events
+            .iter()
+            .position(|e| matches!(e, StageEvent::Resync))
+            .expect("expected a resync");
+        // The lost final state is delivered after the resync
+        assert_eq!(events.len() - 1, resync + 1);
+        assert!(matches!(
+            events.last(),
+            Some(StageEvent::StateChange(StageState::Completed))
+        ));
+    }
+
+    #[tokio::test]
+    async fn stalled_consumer_limits_queued_events() {
+        let (sender, receiver) = stage_event_channel(8);
+        let (output, mut viz_receiver) = mpsc::channel(2);
+        let batcher =
+            tokio::spawn(StageEventBatcher::new(4, Duration::from_millis(1)).run(receiver, output));
+        for round in 0..50 {
+            for x in 0..100 {
+                sender.send(change(round * 100 + x));
+            }
+            tokio::task::yield_now().await;
+        }
+        // Nothing has been consumed, still only the channel capacities are in use
+        assert_eq!(0, sender.sender.capacity());
+        assert_eq!(2, viz_receiver.len());
+        // Once the consumer catches up, it is told to resync
+        sender.send(StageEvent::StateChange(StageState::Stalled));
+        let mut events = Vec::new();
+        while let Some(event) = viz_receiver.recv().await {
+            events.push(event);
+        }
+        batcher.await.unwrap();
+        assert!(events.iter().any(|e| matches!(e, StageEvent::Resync)));
+        assert!(matches!(
+            events.last(),
+            Some(StageEvent::StateChange(StageState::Stalled))
+        ));
+    }
+
+    #[tokio::test]
+    async fn lost_state_change_is_delivered_in_order() {
+        let (sender, receiver) = stage_event_channel(1);
+        let (output, viz_receiver) = mpsc::channel(10);
+        let batcher =
+            tokio::spawn(StageEventBatcher::new(4, Duration::from_millis(1)).run(receiver, output));
+        sender.send(change(1));
+        sender.send(StageEvent::StateChange(StageState::Completed));
+        let events = collect(viz_receiver).await;
+        batcher.await.unwrap();
+        // No content has been lost, so no resync is needed
+        assert_eq!(2, events.len());
+        assert_eq!(1, pixel_count(&events));
+        assert!(matches!(
+            events[1],
+            StageEvent::StateChange(StageState::Completed)
+        ));
+    }
+}
+
 // end of file
@@ -36,7 +36,10 @@ use euclid::{Point2D, Vector2D};
 
 use crate::{
     comp::math_area::RasteredMathArea,
-    storage::coord_spaces::{MathSpace, StageSpace},
+    storage::{
+        coord_spaces::{MathSpace, StageSpace},
+        param_presets::FractalType,
+    },
 };
 
 /// Core coordinate transformation engine for fractal computation.
@@ -239,6 +242,27 @@ impl StageProperties {
         Self::new(self.orig_area.zoom_at_pixel(origin, factor))
     }
 
+    /// Creates a copy with the viewed area zoomed around a mathematical coordinate.
+    ///
+    /// Like `zoomed_clone_by_pixels()`, but the fixed point is given independently of
+    /// the raster, so that areas of different raster sizes can be zoomed alike.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - Mathematical coordinate that remains fixed during zoom
+    /// * `factor` - Zoom factor (>1.0 = zoom in, <1.0 = zoom out)
+    ///
+    /// # Returns
+    ///
+    /// New `StageProperties` with scaled coordinate system
+    pub fn zoomed_clone_by_math(
+        &self,
+        origin: Point2D<BigDecimal, MathSpace>,
+        factor: BigDecimal,
+    ) -> Self {
+        Self::new(self.orig_area.zoom_at_math(origin, factor))
+    }
+
     /// Create zoomed version with f64 parameter, needed during BigDecimal transition
     pub fn zoomed_clone_by_pixels_f64(
         &self,
@@ -425,6 +449,99 @@ impl StageProperties {
     }
 }
 
+/// Default bailout (escape) radius of the iteration.
+///
+/// Much larger than the mathematically sufficient 2.0 so that the smooth
+/// iteration count is free of visible distortion.
+pub const DEFAULT_BAILOUT: f64 = 256.0;
+
+/// Supersampling of the computation for anti-aliased rendering.
+///
+/// With supersampling, the computation stage contains `factor × factor` samples
+/// for each pixel of the rendered image. The renderer averages the colors of the
+/// samples, so high-frequency areas are smoothed instead of showing moiré patterns.
+/// Jittered supersampling moves each sample by a pseudo-random offset within its
+/// sub-pixel, which turns the remaining regular aliasing into less visible noise.
+/// Adaptive supersampling keeps one sample per pixel in the stage and computes
+/// additional sub-pixel samples only for pixels on strong edges once the stage is complete.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Supersampling {
+    /// One sample per pixel
+    Off,
+    /// 2×2 samples per pixel on a regular grid
+    Grid2x2,
+    /// 3×3 samples per pixel on a regular grid
+    Grid3x3,
+    /// 4×4 samples per pixel on a regular grid
+    Grid4x4,
+    /// 3×3 samples per pixel, each moved randomly within its sub-pixel
+    Jittered3x3,
+    /// 3×3 samples only for edge pixels, computed in a refinement pass after the stage
+    Adaptive3x3,
+}
+
+impl Supersampling {
+    /// Returns all supersampling modes, e.g. for UI selection lists.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Off,
+            Self::Grid2x2,
+            Self::Grid3x3,
+            Self::Grid4x4,
+            Self::Jittered3x3,
+            Self::Adaptive3x3,
+        ]
+    }
+
+    /// Returns the human-readable name of the supersampling mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Grid2x2 => "2×2",
+            Self::Grid3x3 => "3×3",
+            Self::Grid4x4 => "4×4",
+            Self::Jittered3x3 => "3×3 jittered",
+            Self::Adaptive3x3 => "3×3 adaptive",
+        }
+    }
+
+    /// Returns the number of samples per pixel in each direction of the stage.
+    ///
+    /// Adaptive supersampling does not enlarge the stage, see `refinement_factor()`.
+    pub fn factor(&self) -> u32 {
+        match self {
+            Self::Off | Self::Adaptive3x3 => 1,
+            Self::Grid2x2 => 2,
+            Self::Grid3x3 | Self::Jittered3x3 => 3,
+            Self::Grid4x4 => 4,
+        }
+    }
+
+    /// Returns whether the samples are moved randomly within their sub-pixels.
+    pub fn is_jittered(&self) -> bool {
+        *self == Self::Jittered3x3
+    }
+
+    /// Returns the number of sub-pixel samples in each direction for refined edge pixels.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(n)` if edge pixels are refined with `n × n` samples after the computation
+    /// - `None` if no refinement pass is done
+    pub fn refinement_factor(&self) -> Option<u32> {
+        match self {
+            Self::Adaptive3x3 => Some(3),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Supersampling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 /// Complete mathematical configuration for fractal image computation.
 ///
 /// Combines coordinate transformation capabilities with computation parameters
@@ -437,6 +554,9 @@ impl StageProperties {
 ///
 /// - Builds on `StageProperties` for coordinate transformations
 /// - Adds `max_iteration` for computation control
+/// - Adds `fractal_type` selecting the iteration formula
+/// - Adds `bailout` as escape radius of the iteration
+/// - Adds `supersampling` for anti-aliased rendering
 /// - Provides high-level operations for interactive manipulation
 ///
 /// # Usage
@@ -449,6 +569,12 @@ pub struct ImageCompProperties {
     pub stage_properties: StageProperties,
     /// Maximum iteration count for fractal computation
     pub max_iteration: u32,
+    /// Fractal type and formula parameters to compute
+    pub fractal_type: FractalType,
+    /// Escape radius, a point escapes once `|z| >= bailout`
+    pub bailout: f64,
+    /// Samples per rendered pixel, see `supersampled()`
+    pub supersampling: Supersampling,
 }
 
 impl ImageCompProperties {
@@ -458,14 +584,49 @@ impl ImageCompProperties {
     ///
     /// * `stage_properties` - Coordinate transformation system
     /// * `max_iteration` - Maximum iteration count for fractal computation
+    /// * `fractal_type` - Fractal type and formula parameters to compute
+    /// * `bailout` - Escape radius of the iteration, at least 2.0
+    /// * `supersampling` - Samples per rendered pixel
     ///
     /// # Returns
     ///
     /// A new `ImageCompProperties` instance ready for computation
-    pub fn new(stage_properties: StageProperties, max_iteration: u32) -> Self {
+    pub fn new(
+        stage_properties: StageProperties,
+        max_iteration: u32,
+        fractal_type: FractalType,
+        bailout: f64,
+        supersampling: Supersampling,
+    ) -> Self {
         ImageCompProperties {
             stage_properties,
             max_iteration,
+            fractal_type,
+            bailout,
+            supersampling,
+        }
+    }
+    /// Creates a copy whose stage contains all samples of the supersampling.
+    ///
+    /// The math area is kept while the raster size is multiplied by the supersampling
+    /// factor, so each sample lies in the center of its sub-pixel.
+    ///
+    /// # Returns
+    ///
+    /// New `ImageCompProperties` with the stage size of the computation, or a clone
+    /// if supersampling is off
+    pub fn supersampled(&self) -> Self {
+        let factor = self.supersampling.factor();
+        if factor == 1 {
+            return self.clone();
+        }
+        let orig_area = &self.stage_properties.orig_area;
+        ImageCompProperties {
+            stage_properties: StageProperties::new(RasteredMathArea::new(
+                orig_area.math_area().clone(),
+                *orig_area.size() * factor,
+            )),
+            ..self.clone()
         }
     }
     /// Creates a rectified copy with square pixels.
@@ -484,6 +645,9 @@ impl ImageCompProperties {
         ImageCompProperties {
             stage_properties: self.stage_properties.rectified(),
             max_iteration: self.max_iteration,
+            fractal_type: self.fractal_type,
+            bailout: self.bailout,
+            supersampling: self.supersampling,
         }
     }
 
@@ -503,6 +667,9 @@ impl ImageCompProperties {
         ImageCompProperties {
             stage_properties: self.stage_properties.shifted_clone_by_pixels(offset),
             max_iteration: self.max_iteration,
+            fractal_type: self.fractal_type,
+            bailout: self.bailout,
+            supersampling: self.supersampling,
         }
     }
 
@@ -525,6 +692,38 @@ impl ImageCompProperties {
                 .stage_properties
                 .zoomed_clone_by_pixels_f64(origin, factor as f64),
             max_iteration: self.max_iteration,
+            fractal_type: self.fractal_type,
+            bailout: self.bailout,
+            supersampling: self.supersampling,
+        }
+    }
+
+    /// Creates a copy zoomed around a mathematical coordinate.
+    ///
+    /// Delegates to `StageProperties::zoomed_clone_by_math()` while
+    /// preserving the iteration count.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - Mathematical coordinate that remains fixed during zoom
+    /// * `factor` - Zoom factor (f32 for UI compatibility)
+    ///
+    /// # Returns
+    ///
+    /// New `ImageCompProperties` with scaled coordinate system
+    pub fn zoomed_clone_by_math(
+        &self,
+        origin: Point2D<BigDecimal, MathSpace>,
+        factor: f32,
+    ) -> Self {
+        ImageCompProperties {
+            stage_properties: self
+                .stage_properties
+                .zoomed_clone_by_math(origin, BigDecimal::from_f64(factor as f64).unwrap()),
+            max_iteration: self.max_iteration,
+            fractal_type: self.fractal_type,
+            bailout: self.bailout,
+            supersampling: self.supersampling,
         }
     }
 
@@ -533,6 +732,9 @@ impl ImageCompProperties {
         ImageCompProperties {
             stage_properties: self.stage_properties.clone(),
             max_iteration: new_max_iteration,
+            fractal_type: self.fractal_type,
+            bailout: self.bailout,
+            supersampling: self.supersampling,
         }
     }
 
@@ -571,6 +773,9 @@ impl ImageCompProperties {
         ImageCompProperties {
             stage_properties: self.stage_properties.shifted_clone_by_math(offset),
             max_iteration: self.max_iteration,
+            fractal_type: self.fractal_type,
+            bailout: self.bailout,
+            supersampling: self.supersampling,
         }
     }
 }
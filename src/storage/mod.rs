@@ -19,5 +19,6 @@ pub mod data_point;
 pub mod image_comp_properties;
 pub mod param_description;
 pub mod param_presets;
+pub mod png_file;
 
 // end of file
@@ -1,8 +1,85 @@
+use std::{fmt, path::Path, str::FromStr};
+
+use bigdecimal::{BigDecimal, Zero};
+use serde::{Deserialize, Serialize};
+
 use crate::{
     comp::math_area::MathArea,
-    storage::visualization::coloring::presets::{GradientColorPreset, IterationAssignment},
+    storage::{
+        image_comp_properties::DEFAULT_BAILOUT,
+        param_presets::FractalType,
+        visualization::coloring::{
+            base::GradientColorScheme,
+            interpolation::{ColorInterpolation, HueDirection, InterpolationSpace},
+            lighting::NormalMapLighting,
+            presets::{GradientColorPreset, IterationAssignment},
+            user_palette::{ColorSchemeV1, NamedColorScheme},
+        },
+    },
 };
 
+/// Version of the parameter description written by this program
+pub const CURRENT_VERSION: u32 = 1;
+
+/// Errors that can occur while reading, writing or validating parameter descriptions.
+#[derive(Debug)]
+pub enum ParamDescriptionError {
+    /// Reading or writing the parameter file failed
+    Io(std::io::Error),
+    /// The text is no valid TOML or does not match the structure of its version
+    Syntax(String),
+    /// The file has been written by a newer program version or is damaged
+    UnsupportedVersion(u32),
+    /// The image file contains no parameter description
+    MissingInImage,
+    /// A parameter has a value which cannot be used for computation
+    InvalidValue {
+        /// Name of the offending parameter
+        field: &'static str,
+        /// The offending value as found in the description
+        value: String,
+    },
+}
+
+impl fmt::Display for ParamDescriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "cannot access parameter file: {}", error),
+            Self::Syntax(message) => write!(f, "malformed parameter description: {}", message),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported parameter description version {} (supported: 1 to {})",
+                version, CURRENT_VERSION
+            ),
+            Self::MissingInImage => write!(f, "image contains no mandel-rs parameters"),
+            Self::InvalidValue { field, value } => {
+                write!(f, "invalid value \"{}\" for parameter {}", value, field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParamDescriptionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ParamDescriptionError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for ParamDescriptionError {
+    fn from(error: toml::de::Error) -> Self {
+        Self::Syntax(error.to_string())
+    }
+}
+
 /// Description of an image generated by mandel-rs.
 ///
 /// This description can be used to recreate the image exactly as it was.
@@ -20,12 +97,57 @@ use crate::{
 ///
 /// The the versioned parameter descriptions know how to convert themselves to and from the
 /// complete current parameter description.
+///
+/// # File Format
+///
+/// Parameter files are TOML documents with a top-level `version` key selecting the
+/// versioned description used for the rest of the file:
+///
+/// ```toml
+/// version = 1
+/// name = "Full Mandelbrot Set"
+///
+/// [fractal]
+/// type = "Mandelbrot"
+///
+/// [area]
+/// center_x = "-0.675"
+/// center_y = "0"
+/// radius = "1.25"
+/// ratio = "1"
+///
+/// [computation]
+/// max_iteration = 200
+/// bailout = 256.0
+///
+/// [coloring]
+/// iteration_assignment = "Linear"
+/// color_preset = "Sunrise"
+/// stripe_count = 256
+/// stripe_offset = 0
+/// color_space = "Oklch"
+/// hue_direction = "Shorter"
+/// smooth = true
+///
+/// [coloring.lighting]
+/// angle = 45.0
+/// height = 1.5
+/// specular = 0.3
+/// ```
+///
+/// Coordinates are stored as strings so that deep zoom areas keep their full precision.
+/// If a user-defined color scheme is used, its complete definition is stored in an additional
+/// `[coloring.custom_scheme]` table, see `user_palette`, so the file can be used anywhere.
+/// Files without `color_space` and `hue_direction` interpolate in linear RGB.
+/// Files without `bailout`, `smooth` or `[coloring.lighting]` use the default escape radius
+/// and flat colors of the integer iteration count.
 
 /// Full and current description of parameters
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct ParamDescription {
     pub name: String,
+    pub fractal_type: FractalType,
     pub center_x: String,
     pub center_y: String,
     pub radius: String,
@@ -35,14 +157,457 @@ pub struct ParamDescription {
     pub color_preset: GradientColorPreset,
     pub stripe_count: u32,
     pub stripe_offset: u32,
+    /// User-defined color scheme used instead of `color_preset` if set
+    pub custom_scheme: Option<NamedColorScheme>,
+    /// Color space and hue direction the gradient colors are interpolated in
+    pub color_interpolation: ColorInterpolation,
+    /// Escape radius of the iteration, at least 2
+    pub bailout: f64,
+    /// Whether to interpolate between gradient stripes using the smooth iteration count
+    pub smooth_coloring: bool,
+    /// Normal-map lighting, `None` for flat colors
+    pub lighting: Option<NormalMapLighting>,
 }
 
 impl ParamDescription {
+    /// Return the gradient color scheme, the custom scheme if there is one, the preset otherwise.
+    ///
+    /// The scheme is interpolated as given by `color_interpolation`.
+    pub fn color_scheme(&self) -> GradientColorScheme {
+        self.custom_scheme
+            .as_ref()
+            .map_or_else(
+                || self.color_preset.scheme(),
+                |custom| custom.scheme.clone(),
+            )
+            .with_interpolation(self.color_interpolation)
+    }
+
     /// Return the math area as business logic object.
     ///
-    /// This is expected to always succeed as the consistency of the stored parameters is ensured elsewhere.
-    pub fn math_area(&self) -> MathArea {
-        MathArea::from_str(&self.center_x, &self.center_y, &self.radius, &self.ratio).unwrap()
+    /// # Returns
+    ///
+    /// The math area, or `InvalidValue` naming the first coordinate which is no
+    /// decimal number or the radius or ratio if it is not positive
+    pub fn math_area(&self) -> Result<MathArea, ParamDescriptionError> {
+        let center_x = Self::decimal("center_x", &self.center_x)?;
+        let center_y = Self::decimal("center_y", &self.center_y)?;
+        let radius = Self::positive_decimal("radius", &self.radius)?;
+        let ratio = Self::positive_decimal("ratio", &self.ratio)?;
+        Ok(MathArea::from_big_decimals(
+            center_x, center_y, radius, ratio,
+        ))
+    }
+
+    /// Check all parameters for values usable by the computation.
+    pub fn validate(&self) -> Result<(), ParamDescriptionError> {
+        self.math_area()?;
+        let invalid = |field, value: f64| ParamDescriptionError::InvalidValue {
+            field,
+            value: value.to_string(),
+        };
+        match self.fractal_type {
+            FractalType::Julia { c_real, .. } if !c_real.is_finite() => {
+                return Err(invalid("c_real", c_real));
+            }
+            FractalType::Julia { c_imag, .. } if !c_imag.is_finite() => {
+                return Err(invalid("c_imag", c_imag));
+            }
+            FractalType::Multibrot { exponent } if !(exponent.is_finite() && exponent > 1.0) => {
+                return Err(invalid("exponent", exponent));
+            }
+            _ => {}
+        }
+        if !(self.bailout.is_finite() && self.bailout >= 2.0) {
+            return Err(invalid("bailout", self.bailout));
+        }
+        if let Some(lighting) = self.lighting {
+            for (field, value) in [
+                ("angle", lighting.angle),
+                ("height", lighting.height),
+                ("specular", lighting.specular),
+            ] {
+                if !value.is_finite() {
+                    return Err(invalid(field, value as f64));
+                }
+            }
+        }
+        if self.max_iteration == 0 {
+            return Err(ParamDescriptionError::InvalidValue {
+                field: "max_iteration",
+                value: self.max_iteration.to_string(),
+            });
+        }
+        if self.stripe_count == 0 {
+            return Err(ParamDescriptionError::InvalidValue {
+                field: "stripe_count",
+                value: self.stripe_count.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Parse a parameter description from TOML text of any supported version.
+    ///
+    /// # Returns
+    ///
+    /// The validated parameter description or the reason why the text cannot be used
+    pub fn from_toml(text: &str) -> Result<Self, ParamDescriptionError> {
+        let probe: VersionProbe = toml::from_str(text)?;
+        let description = match probe.version {
+            1 => ParamDescription::from(toml::from_str::<ParamDescriptionV1>(text)?),
+            version => return Err(ParamDescriptionError::UnsupportedVersion(version)),
+        };
+        description.validate()?;
+        Ok(description)
+    }
+
+    /// Serialize this parameter description as TOML text in the current version.
+    pub fn to_toml(&self) -> String {
+        // Serializing plain structs of strings and numbers into TOML cannot fail
+        toml::to_string(&ParamDescriptionV1::from(self))
+            .expect("parameter description is always representable in TOML")
+    }
+
+    /// Read and validate a parameter description from the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, ParamDescriptionError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write this parameter description into the file at `path`, replacing its content.
+    pub fn save(&self, path: &Path) -> Result<(), ParamDescriptionError> {
+        Ok(std::fs::write(path, self.to_toml())?)
+    }
+
+    /// Parse `value` as decimal number, report `field` as invalid otherwise.
+    fn decimal(field: &'static str, value: &str) -> Result<BigDecimal, ParamDescriptionError> {
+        BigDecimal::from_str(value.trim()).map_err(|_| ParamDescriptionError::InvalidValue {
+            field,
+            value: value.to_string(),
+        })
+    }
+
+    /// Parse `value` as positive decimal number, report `field` as invalid otherwise.
+    fn positive_decimal(
+        field: &'static str,
+        value: &str,
+    ) -> Result<BigDecimal, ParamDescriptionError> {
+        let decimal = Self::decimal(field, value)?;
+        if decimal <= BigDecimal::zero() {
+            return Err(ParamDescriptionError::InvalidValue {
+                field,
+                value: value.to_string(),
+            });
+        }
+        Ok(decimal)
+    }
+}
+
+/// Minimal view of a parameter file to find out its version.
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+/// Parameter description as stored on disc in version 1.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParamDescriptionV1 {
+    version: u32,
+    name: String,
+    fractal: FractalTypeV1,
+    area: AreaV1,
+    computation: ComputationV1,
+    coloring: ColoringV1,
+}
+
+/// Fractal type of version 1, decoupled from the internal `FractalType`.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", deny_unknown_fields)]
+enum FractalTypeV1 {
+    Mandelbrot,
+    Julia { c_real: f64, c_imag: f64 },
+    BurningShip,
+    Tricorn,
+    Multibrot { exponent: f64 },
+}
+
+/// Mathematical area of version 1, all values are decimal strings.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AreaV1 {
+    center_x: String,
+    center_y: String,
+    radius: String,
+    ratio: String,
+}
+
+/// Computation parameters of version 1.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ComputationV1 {
+    max_iteration: u32,
+    #[serde(default = "default_bailout")]
+    bailout: f64,
+}
+
+/// Escape radius of files written before the bailout has been stored.
+fn default_bailout() -> f64 {
+    DEFAULT_BAILOUT
+}
+
+/// Coloring parameters of version 1.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColoringV1 {
+    iteration_assignment: IterationAssignment,
+    color_preset: GradientColorPreset,
+    stripe_count: u32,
+    stripe_offset: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    custom_scheme: Option<ColorSchemeV1>,
+    #[serde(default)]
+    color_space: InterpolationSpace,
+    #[serde(default)]
+    hue_direction: HueDirection,
+    #[serde(default)]
+    smooth: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lighting: Option<LightingV1>,
+}
+
+/// Normal-map lighting of version 1.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LightingV1 {
+    angle: f32,
+    height: f32,
+    specular: f32,
+}
+
+impl From<&ParamDescription> for ParamDescriptionV1 {
+    fn from(descr: &ParamDescription) -> Self {
+        ParamDescriptionV1 {
+            version: 1,
+            name: descr.name.clone(),
+            fractal: match descr.fractal_type {
+                FractalType::Mandelbrot => FractalTypeV1::Mandelbrot,
+                FractalType::Julia { c_real, c_imag } => FractalTypeV1::Julia { c_real, c_imag },
+                FractalType::BurningShip => FractalTypeV1::BurningShip,
+                FractalType::Tricorn => FractalTypeV1::Tricorn,
+                FractalType::Multibrot { exponent } => FractalTypeV1::Multibrot { exponent },
+            },
+            area: AreaV1 {
+                center_x: descr.center_x.clone(),
+                center_y: descr.center_y.clone(),
+                radius: descr.radius.clone(),
+                ratio: descr.ratio.clone(),
+            },
+            computation: ComputationV1 {
+                max_iteration: descr.max_iteration,
+                bailout: descr.bailout,
+            },
+            coloring: ColoringV1 {
+                iteration_assignment: descr.iteration_assignment,
+                color_preset: descr.color_preset,
+                stripe_count: descr.stripe_count,
+                stripe_offset: descr.stripe_offset,
+                custom_scheme: descr.custom_scheme.as_ref().map(ColorSchemeV1::from),
+                color_space: descr.color_interpolation.space,
+                hue_direction: descr.color_interpolation.hue_direction,
+                smooth: descr.smooth_coloring,
+                lighting: descr.lighting.map(|lighting| LightingV1 {
+                    angle: lighting.angle,
+                    height: lighting.height,
+                    specular: lighting.specular,
+                }),
+            },
+        }
+    }
+}
+
+impl From<ParamDescriptionV1> for ParamDescription {
+    fn from(v1: ParamDescriptionV1) -> Self {
+        ParamDescription {
+            name: v1.name,
+            fractal_type: match v1.fractal {
+                FractalTypeV1::Mandelbrot => FractalType::Mandelbrot,
+                FractalTypeV1::Julia { c_real, c_imag } => FractalType::Julia { c_real, c_imag },
+                FractalTypeV1::BurningShip => FractalType::BurningShip,
+                FractalTypeV1::Tricorn => FractalType::Tricorn,
+                FractalTypeV1::Multibrot { exponent } => FractalType::Multibrot { exponent },
+            },
+            center_x: v1.area.center_x,
+            center_y: v1.area.center_y,
+            radius: v1.area.radius,
+            ratio: v1.area.ratio,
+            max_iteration: v1.computation.max_iteration,
+            iteration_assignment: v1.coloring.iteration_assignment,
+            color_preset: v1.coloring.color_preset,
+            stripe_count: v1.coloring.stripe_count,
+            stripe_offset: v1.coloring.stripe_offset,
+            custom_scheme: v1.coloring.custom_scheme.map(NamedColorScheme::from),
+            color_interpolation: ColorInterpolation::new(
+                v1.coloring.color_space,
+                v1.coloring.hue_direction,
+            ),
+            bailout: v1.computation.bailout,
+            smooth_coloring: v1.coloring.smooth,
+            lighting: v1.coloring.lighting.map(|lighting| {
+                NormalMapLighting::new(lighting.angle, lighting.height, lighting.specular)
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::param_presets::ParamPreset;
+    use palette::Srgb;
+
+    #[test]
+    fn presets_survive_round_trip() {
+        for preset in ParamPreset::all() {
+            let original = preset.preset();
+            let restored = ParamDescription::from_toml(&original.to_toml()).unwrap();
+            assert_eq!(original.name, restored.name);
+            assert_eq!(original.fractal_type, restored.fractal_type);
+            assert_eq!(original.center_x, restored.center_x);
+            assert_eq!(original.radius, restored.radius);
+            assert_eq!(original.max_iteration, restored.max_iteration);
+            assert_eq!(original.iteration_assignment, restored.iteration_assignment);
+            assert_eq!(original.color_preset, restored.color_preset);
+            assert_eq!(original.stripe_offset, restored.stripe_offset);
+        }
+    }
+
+    #[test]
+    fn custom_scheme_survives_round_trip() {
+        let mut original = ParamPreset::MandelbrotFull.preset();
+        assert!(!original.to_toml().contains("custom_scheme"));
+        original.custom_scheme = Some(NamedColorScheme {
+            name: "Dusk".to_string(),
+            scheme: GradientColorScheme::with_positions(
+                Srgb::new(0.0, 0.0, 0.0),
+                vec![
+                    (0.1, Srgb::new(0.2, 0.1, 0.5)),
+                    (0.6, Srgb::new(1.0, 0.6, 0.2)),
+                ],
+            ),
+        });
+        let restored = ParamDescription::from_toml(&original.to_toml()).unwrap();
+        assert_eq!(original.custom_scheme, restored.custom_scheme);
+        assert_eq!(original.color_scheme(), restored.color_scheme());
+        let broken = original
+            .to_toml()
+            .replace("position = 0.1", "position = inf");
+        assert!(matches!(
+            ParamDescription::from_toml(&broken),
+            Err(ParamDescriptionError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn color_interpolation_survives_round_trip() {
+        let mut original = ParamPreset::MandelbrotFull.preset();
+        original.color_interpolation =
+            ColorInterpolation::new(InterpolationSpace::Hsluv, HueDirection::Decreasing);
+        let text = original.to_toml();
+        let restored = ParamDescription::from_toml(&text).unwrap();
+        assert_eq!(original.color_interpolation, restored.color_interpolation);
+        assert_eq!(original.color_scheme(), restored.color_scheme());
+        // Files written before the interpolation was selectable use linear RGB
+        let old = text
+            .lines()
+            .filter(|line| !line.starts_with("color_space") && !line.starts_with("hue_direction"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(
+            ColorInterpolation::default(),
+            ParamDescription::from_toml(&old)
+                .unwrap()
+                .color_interpolation
+        );
+    }
+
+    #[test]
+    fn image_settings_survive_round_trip() {
+        let mut original = ParamPreset::MandelbrotFull.preset();
+        original.bailout = 1000.0;
+        original.smooth_coloring = true;
+        original.lighting = Some(NormalMapLighting::new(120.0, 0.5, 0.8));
+        let text = original.to_toml();
+        let restored = ParamDescription::from_toml(&text).unwrap();
+        assert_eq!(original.bailout, restored.bailout);
+        assert!(restored.smooth_coloring);
+        assert_eq!(original.lighting, restored.lighting);
+        // Files written before these settings were stored use the defaults
+        let old = text
+            .lines()
+            .take_while(|line| !line.starts_with("[coloring.lighting]"))
+            .filter(|line| !line.starts_with("bailout") && !line.starts_with("smooth"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let restored = ParamDescription::from_toml(&old).unwrap();
+        assert_eq!(DEFAULT_BAILOUT, restored.bailout);
+        assert!(!restored.smooth_coloring);
+        assert_eq!(None, restored.lighting);
+    }
+
+    #[test]
+    fn malformed_descriptions_are_rejected() {
+        let valid = ParamPreset::MandelbrotFull.preset().to_toml();
+        assert!(matches!(
+            ParamDescription::from_toml(&valid.replace("version = 1", "version = 99")),
+            Err(ParamDescriptionError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(
+            ParamDescription::from_toml(&valid.replace("\"1.25\"", "\"-1\"")),
+            Err(ParamDescriptionError::InvalidValue {
+                field: "radius",
+                ..
+            })
+        ));
+        assert!(matches!(
+            ParamDescription::from_toml(&valid.replace("\"-0.675\"", "\"left\"")),
+            Err(ParamDescriptionError::InvalidValue {
+                field: "center_x",
+                ..
+            })
+        ));
+        assert!(matches!(
+            ParamDescription::from_toml("name = \"no version\""),
+            Err(ParamDescriptionError::Syntax(_))
+        ));
+        let mut julia = ParamPreset::JuliaDouadyRabbit.preset();
+        julia.fractal_type = FractalType::Julia {
+            c_real: f64::NAN,
+            c_imag: 0.5,
+        };
+        assert!(matches!(
+            ParamDescription::from_toml(&julia.to_toml()),
+            Err(ParamDescriptionError::InvalidValue {
+                field: "c_real",
+                ..
+            })
+        ));
+        let mut multibrot = ParamPreset::MultibrotCubic.preset();
+        multibrot.fractal_type = FractalType::Multibrot { exponent: 1.0 };
+        assert!(matches!(
+            ParamDescription::from_toml(&multibrot.to_toml()),
+            Err(ParamDescriptionError::InvalidValue {
+                field: "exponent",
+                ..
+            })
+        ));
+        assert!(matches!(
+            ParamDescription::from_toml(&valid.replace("bailout = 256.0", "bailout = 1.5")),
+            Err(ParamDescriptionError::InvalidValue {
+                field: "bailout",
+                ..
+            })
+        ));
     }
 }
 
@@ -2,34 +2,109 @@
 //!
 //! This module contains a number of hard-coded presets for nice fractal images.
 
-use crate::storage::{
-    param_description::ParamDescription,
-    visualization::coloring::presets::{GradientColorPreset, IterationAssignment},
+use crate::{
+    comp::fractal_formula::{
+        BurningShipFormula, FractalFormula, IntegerMultibrotFormula, JuliaFormula,
+        MandelbrotFormula, RealMultibrotFormula, TricornFormula,
+    },
+    storage::{
+        image_comp_properties::DEFAULT_BAILOUT,
+        param_description::ParamDescription,
+        visualization::coloring::{
+            interpolation::ColorInterpolation,
+            presets::{GradientColorPreset, IterationAssignment},
+        },
+    },
 };
 
-/// Enumeration of supported fractal types for future extensibility.
+/// Enumeration of supported fractal types together with their formula parameters.
 ///
-/// Currently supports only the Mandelbrot set, but designed to accommodate
-/// additional fractal types such as Julia sets, Burning Ship, and others.
-/// The enum serves as a type-safe way to specify fractal algorithms.
+/// The fractal type selects the iteration formula the engine uses for each point.
+/// Types with parameters carry them directly so that a fractal type value
+/// completely describes the formula which produced an image.
 ///
-/// # Future Expansion
+/// # Supported Types
 ///
-/// Planned fractal types for future implementation:
-/// - **Julia Sets**: Parameter-dependent fractals c = constant
+/// - **Mandelbrot**: The classic set, z(n+1) = z(n)² + c, z(0) = 0
+/// - **Julia Sets**: Parameter-dependent fractals with constant c, z(0) = point
 /// - **Burning Ship**: abs(z) variation of Mandelbrot
 /// - **Tricorn**: Complex conjugate variation
-/// - **Multibrot**: Higher-power generalizations (zⁿ + c)
+/// - **Multibrot**: Higher-power generalizations (z^d + c), d may be integer or real
 ///
-/// # Current Implementation
-///
-/// Only Mandelbrot is currently supported, but the architecture is designed
-/// to easily accommodate additional fractal types without breaking changes.
-#[allow(dead_code)]
+/// Only the Mandelbrot type supports perturbation-based deep zooms.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FractalType {
     /// The classic Mandelbrot set: z(n+1) = z(n)² + c, z(0) = 0
     /// Most famous fractal with rich boundary structure and infinite detail
     Mandelbrot,
+    /// Julia set of the constant c: z(n+1) = z(n)² + c, z(0) is the point
+    Julia { c_real: f64, c_imag: f64 },
+    /// Burning Ship: z(n+1) = (|Re z(n)| + i·|Im z(n)|)² + c
+    BurningShip,
+    /// Tricorn, also known as Mandelbar: z(n+1) = conj(z(n))² + c
+    Tricorn,
+    /// Multibrot: z(n+1) = z(n)^d + c with d > 1
+    Multibrot { exponent: f64 },
+}
+
+impl FractalType {
+    /// Returns all fractal types with default parameters, e.g. for UI selection lists.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Mandelbrot,
+            Self::Julia {
+                c_real: -0.123,
+                c_imag: 0.745,
+            },
+            Self::BurningShip,
+            Self::Tricorn,
+            Self::Multibrot { exponent: 3.0 },
+        ]
+    }
+
+    /// Returns the human-readable name of the fractal type, independent of its parameters.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Mandelbrot => "Mandelbrot",
+            Self::Julia { .. } => "Julia",
+            Self::BurningShip => "Burning Ship",
+            Self::Tricorn => "Tricorn",
+            Self::Multibrot { .. } => "Multibrot",
+        }
+    }
+
+    /// Returns whether `other` is the same fractal type, possibly with different parameters.
+    pub fn same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Returns the iteration formula implementing this fractal type.
+    ///
+    /// Multibrot exponents which are integers use exact complex multiplication,
+    /// all others are computed in polar form.
+    pub fn formula(&self) -> Box<dyn FractalFormula> {
+        match *self {
+            Self::Mandelbrot => Box::new(MandelbrotFormula),
+            Self::Julia { c_real, c_imag } => Box::new(JuliaFormula { c_real, c_imag }),
+            Self::BurningShip => Box::new(BurningShipFormula),
+            Self::Tricorn => Box::new(TricornFormula),
+            Self::Multibrot { exponent } => {
+                if exponent.fract() == 0.0 && (2.0..=64.0).contains(&exponent) {
+                    Box::new(IntegerMultibrotFormula {
+                        exponent: exponent as u32,
+                    })
+                } else {
+                    Box::new(RealMultibrotFormula { exponent })
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for FractalType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
 /// Pre-defined mathematical regions of interest in the Mandelbrot set.
@@ -92,6 +167,16 @@ pub enum ParamPreset {
     MandelbrotThroatSpiral,
     // Lots of spirals near Mandelbrot's extremity
     MandelbrotLotsOfSpirals,
+    /// Douady's rabbit, a Julia set with three-fold spirals
+    JuliaDouadyRabbit,
+    /// Dendrite Julia set at c = i
+    JuliaDendrite,
+    /// The "armada" of small ships near the Burning Ship's antenna
+    BurningShipArmada,
+    /// Complete Tricorn overview
+    TricornFull,
+    /// Complete overview of the cubic Multibrot set
+    MultibrotCubic,
 }
 
 impl ParamPreset {
@@ -139,6 +224,11 @@ impl ParamPreset {
             Self::MandelbrotJellyfish,
             Self::MandelbrotThroatSpiral,
             Self::MandelbrotLotsOfSpirals,
+            Self::JuliaDouadyRabbit,
+            Self::JuliaDendrite,
+            Self::BurningShipArmada,
+            Self::TricornFull,
+            Self::MultibrotCubic,
         ]
     }
 
@@ -162,15 +252,40 @@ impl ParamPreset {
             Self::MandelbrotJellyfish => "Jellyfish with a minibrot",
             Self::MandelbrotThroatSpiral => "Spiral on throat of Mandelbrot",
             Self::MandelbrotLotsOfSpirals => "Lots of spirals at Mandelbrot's arm",
+            Self::JuliaDouadyRabbit => "Julia: Douady's rabbit",
+            Self::JuliaDendrite => "Julia: Dendrite",
+            Self::BurningShipArmada => "Burning Ship armada",
+            Self::TricornFull => "Full Tricorn",
+            Self::MultibrotCubic => "Full cubic Multibrot",
         }
     }
 
     /// Actual preset data
     pub fn preset(&self) -> ParamDescription {
+        // Computation and coloring options which all presets leave at their defaults
+        let base = ParamDescription {
+            name: self.name().to_string(),
+            fractal_type: FractalType::Mandelbrot,
+            center_x: "0".to_owned(),
+            center_y: "0".to_owned(),
+            radius: "2".to_owned(),
+            ratio: "1".to_owned(),
+            max_iteration: 200,
+            iteration_assignment: IterationAssignment::Linear,
+            color_preset: GradientColorPreset::Sunrise,
+            stripe_count: 256,
+            stripe_offset: 0,
+            custom_scheme: None,
+            color_interpolation: ColorInterpolation::default(),
+            bailout: DEFAULT_BAILOUT,
+            smooth_coloring: false,
+            lighting: None,
+        };
         match self {
             // Full Mandelbrot set view: classic overview coordinates
             Self::MandelbrotFull => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.675".to_owned(),
                 center_y: "0".to_owned(),
                 radius: "1.25".to_owned(),
@@ -180,11 +295,13 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 256,
                 stripe_offset: 0,
+                ..base
             },
 
             // Elephant Valley: famous feature with trunk-like appendages
             Self::MandelbrotElephantValley => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.74728352972".to_owned(),
                 center_y: "0.10757720113".to_owned(),
                 radius: "0.00020306307".to_owned(),
@@ -194,11 +311,13 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 256,
                 stripe_offset: 0,
+                ..base
             },
 
             // Spiral formations: complex boundary spiral structures
             Self::MandelbrotSpirals => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.726516262498".to_owned(),
                 center_y: "0.18783225".to_owned(),
                 radius: "0.00003".to_owned(),
@@ -208,11 +327,13 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 256,
                 stripe_offset: 0,
+                ..base
             },
 
             // Seahorse Valley: seahorse-like spiral patterns
             Self::MandelbrotSeahorseValley => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.74579999998".to_owned(),
                 center_y: "0.10975".to_owned(),
                 radius: "0.0005".to_owned(),
@@ -222,11 +343,13 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 256,
                 stripe_offset: 0,
+                ..base
             },
 
             // Squared spirals at a minibrot
             Self::MandelbrotSquaredSpirals => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-1.76622701902486844983".to_owned(),
                 center_y: "0.01182325403486396853".to_owned(),
                 radius: "1.749564E-13".to_owned(),
@@ -236,11 +359,13 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 256,
                 stripe_offset: 0,
+                ..base
             },
 
             // Minibrot with "ring of fire"
             Self::MandelbrotRingOfFire => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-1.15266540088230347".to_owned(),
                 center_y: "0.30699874725259538".to_owned(),
                 radius: "6.2385403E-10".to_owned(),
@@ -250,11 +375,13 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 190,
                 stripe_offset: 160,
+                ..base
             },
 
             // Minibrot with "ring of fire"
             Self::MandelbrotWeaved => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.694241084688711".to_owned(),
                 center_y: "0.369018494065763".to_owned(),
                 radius: "1.7379089E-8".to_owned(),
@@ -264,12 +391,14 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 256,
                 stripe_offset: 0,
+                ..base
             },
 
             // Kraken-like area with a minibrot too small for f64 in the middle
             // Note: Use stripe count 2048 for this
             Self::MandelbrotKraken => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.36423818776604768336".to_owned(),
                 center_y: "-0.65667699544311595692".to_owned(),
                 radius: "1.1542801E-13".to_owned(),
@@ -279,10 +408,12 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 2048,
                 stripe_offset: 0,
+                ..base
             },
 
             Self::MandelbrotPsySpiral => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-1.14856791542804".to_owned(),
                 center_y: "0.26396031618693".to_owned(),
                 radius: "3.6690958E-7".to_owned(),
@@ -292,10 +423,12 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 256,
                 stripe_offset: 0,
+                ..base
             },
 
             Self::MandelbrotCaterpillar => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.5382233952419".to_owned(),
                 center_y: "0.6108236150811".to_owned(),
                 radius: "0.0000011122613".to_owned(),
@@ -305,10 +438,12 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 256,
                 stripe_offset: 0,
+                ..base
             },
 
             Self::MandelbrotBunchOfSpikes => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.995643151344189".to_owned(),
                 center_y: "0.280397788186929".to_owned(),
                 radius: "3.2430531E-8".to_owned(),
@@ -318,10 +453,12 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 64,
                 stripe_offset: 0,
+                ..base
             },
 
             Self::MandelbrotStraightSpikes => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.99441587727171340975".to_owned(),
                 center_y: "0.29980873842699326524".to_owned(),
                 radius: "1.0769815E-13".to_owned(),
@@ -331,10 +468,12 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 250,
                 stripe_offset: 0,
+                ..base
             },
 
             Self::MandelbrotMinibrotOnBackside => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "0.250268969430133".to_owned(),
                 center_y: "-0.000006636566143".to_owned(),
                 radius: "1.4116211E-8".to_owned(),
@@ -344,10 +483,12 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 2048,
                 stripe_offset: 0,
+                ..base
             },
 
             Self::MandelbrotFlashes => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.6922857838017087".to_owned(),
                 center_y: "0.4785331215741747".to_owned(),
                 radius: "9.3132215E-9".to_owned(),
@@ -357,10 +498,12 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 500,
                 stripe_offset: 365,
+                ..base
             },
 
             Self::MandelbrotJellyfish => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-1.749997863315900509".to_owned(),
                 center_y: "0".to_owned(),
                 radius: "1.1197185E-11".to_owned(),
@@ -370,10 +513,12 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 2048,
                 stripe_offset: 1995,
+                ..base
             },
 
             Self::MandelbrotThroatSpiral => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.7506634258751".to_owned(),
                 center_y: "0.0208472710261".to_owned(),
                 radius: "0.0000047683705".to_owned(),
@@ -383,10 +528,12 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 150,
                 stripe_offset: 0,
+                ..base
             },
 
             Self::MandelbrotLotsOfSpirals => ParamDescription {
                 name: self.name().to_string(),
+                fractal_type: FractalType::Mandelbrot,
                 center_x: "-0.177948421807616".to_owned(),
                 center_y: "0.825383662653996".to_owned(),
                 radius: "2.1396172E-8".to_owned(),
@@ -396,6 +543,88 @@ impl ParamPreset {
                 color_preset: GradientColorPreset::Sunrise,
                 stripe_count: 800,
                 stripe_offset: 0,
+                ..base
+            },
+
+            Self::JuliaDouadyRabbit => ParamDescription {
+                name: self.name().to_string(),
+                fractal_type: FractalType::Julia {
+                    c_real: -0.123,
+                    c_imag: 0.745,
+                },
+                center_x: "0".to_owned(),
+                center_y: "0".to_owned(),
+                radius: "1.5".to_owned(),
+                ratio: "1".to_owned(),
+                max_iteration: 500,
+                iteration_assignment: IterationAssignment::Linear,
+                color_preset: GradientColorPreset::Sunrise,
+                stripe_count: 64,
+                stripe_offset: 0,
+                ..base
+            },
+
+            Self::JuliaDendrite => ParamDescription {
+                name: self.name().to_string(),
+                fractal_type: FractalType::Julia {
+                    c_real: 0.0,
+                    c_imag: 1.0,
+                },
+                center_x: "0".to_owned(),
+                center_y: "0".to_owned(),
+                radius: "1.5".to_owned(),
+                ratio: "1".to_owned(),
+                max_iteration: 1000,
+                iteration_assignment: IterationAssignment::SquareRoot,
+                color_preset: GradientColorPreset::Moonlight,
+                stripe_count: 32,
+                stripe_offset: 0,
+                ..base
+            },
+
+            Self::BurningShipArmada => ParamDescription {
+                name: self.name().to_string(),
+                fractal_type: FractalType::BurningShip,
+                center_x: "-1.7585".to_owned(),
+                center_y: "-0.0325".to_owned(),
+                radius: "0.04".to_owned(),
+                ratio: "1".to_owned(),
+                max_iteration: 1000,
+                iteration_assignment: IterationAssignment::Linear,
+                color_preset: GradientColorPreset::Sunrise,
+                stripe_count: 128,
+                stripe_offset: 0,
+                ..base
+            },
+
+            Self::TricornFull => ParamDescription {
+                name: self.name().to_string(),
+                fractal_type: FractalType::Tricorn,
+                center_x: "-0.3".to_owned(),
+                center_y: "0".to_owned(),
+                radius: "1.6".to_owned(),
+                ratio: "1".to_owned(),
+                max_iteration: 200,
+                iteration_assignment: IterationAssignment::Linear,
+                color_preset: GradientColorPreset::Woods,
+                stripe_count: 64,
+                stripe_offset: 0,
+                ..base
+            },
+
+            Self::MultibrotCubic => ParamDescription {
+                name: self.name().to_string(),
+                fractal_type: FractalType::Multibrot { exponent: 3.0 },
+                center_x: "0".to_owned(),
+                center_y: "0".to_owned(),
+                radius: "1.4".to_owned(),
+                ratio: "1".to_owned(),
+                max_iteration: 200,
+                iteration_assignment: IterationAssignment::Linear,
+                color_preset: GradientColorPreset::Sunrise,
+                stripe_count: 128,
+                stripe_offset: 0,
+                ..base
             },
         }
     }
@@ -0,0 +1,300 @@
+//! PNG export with embedded render parameters.
+//!
+//! Images written by mandel-rs carry their complete `ParamDescription` so that a shared
+//! picture is enough to reproduce the render. The parameters are stored as the TOML text
+//! of the current parameter file format in an iTXt chunk, so coordinates keep their full
+//! BigDecimal precision. A second iTXt chunk names the program version which wrote the file.
+//!
+//! # Chunks
+//!
+//! - `mandel-rs parameters`: TOML parameter description, see `ParamDescription::to_toml()`
+//! - `Software`: Program name and version, a keyword predefined by the PNG specification
+//!
+//! All text chunks are written before the image data so that reading the parameters
+//! does not require decoding the image.
+//!
+//! # Animated PNG
+//!
+//! Color cycles are written as animated PNG (APNG). The first frame is also the default
+//! image, so viewers without APNG support show a still picture. The parameters embedded
+//! into an animated PNG are those of its first frame.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, Write};
+use std::path::Path;
+
+use crate::storage::param_description::{ParamDescription, ParamDescriptionError};
+
+/// Keyword of the iTXt chunk containing the parameter description
+pub const PARAMS_KEYWORD: &str = "mandel-rs parameters";
+
+/// Keyword of the iTXt chunk containing the program version
+pub const SOFTWARE_KEYWORD: &str = "Software";
+
+/// Write an RGBA image together with its parameter description as PNG.
+///
+/// # Arguments
+///
+/// * `w` - Destination of the PNG data stream
+/// * `width` - Image width in pixels
+/// * `height` - Image height in pixels
+/// * `rgba` - Pixel data in row-major order, 4 bytes per pixel
+/// * `descr` - Parameters the image has been rendered from
+///
+/// # Returns
+///
+/// `Ok(())` if the complete image has been written, the I/O or encoding error otherwise
+pub fn write_png<W: Write>(
+    w: W,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    descr: &ParamDescription,
+) -> std::io::Result<()> {
+    let mut writer = encoder(w, width, height, descr)?.write_header()?;
+    writer.write_image_data(rgba)?;
+    Ok(writer.finish()?)
+}
+
+/// Write an RGBA image delivered in bands of complete rows, with its parameter description.
+///
+/// Only one band is held in memory at any time, so images larger than the main memory can
+/// be written, e.g. by rendering one row of tiles after the other into a band.
+///
+/// # Arguments
+///
+/// * `w` - Destination of the PNG data stream
+/// * `width` - Image width in pixels
+/// * `height` - Image height in pixels
+/// * `band` - Returns the RGBA pixel data of the next band of rows, 4 bytes per pixel,
+///   called until all `height` rows have been delivered
+/// * `descr` - Parameters the image has been rendered from
+///
+/// # Returns
+///
+/// `Ok(())` if the complete image has been written, the error of the band callback
+/// or the I/O or encoding error otherwise
+pub fn write_png_in_bands<W: Write>(
+    w: W,
+    width: u32,
+    height: u32,
+    mut band: impl FnMut() -> std::io::Result<Vec<u8>>,
+    descr: &ParamDescription,
+) -> std::io::Result<()> {
+    let mut writer = encoder(w, width, height, descr)?.write_header()?;
+    let mut stream = writer.stream_writer()?;
+    let mut remaining = width as usize * height as usize * 4;
+    while remaining > 0 {
+        let rgba = band()?;
+        if rgba.is_empty() || rgba.len() > remaining || rgba.len() % (width as usize * 4) != 0 {
+            return Err(std::io::Error::other(
+                "band does not consist of complete image rows",
+            ));
+        }
+        stream.write_all(&rgba)?;
+        remaining -= rgba.len();
+    }
+    stream.finish()?;
+    Ok(writer.finish()?)
+}
+
+/// Write an endlessly looping animated PNG with the parameter description of its first frame.
+///
+/// The frames are requested one after the other, so only one of them is held in memory.
+///
+/// # Arguments
+///
+/// * `w` - Destination of the PNG data stream
+/// * `width` - Image width in pixels
+/// * `height` - Image height in pixels
+/// * `frame_count` - Number of frames, at least one
+/// * `frame_delay` - Display time of each frame as fraction `(numerator, denominator)` of seconds
+/// * `frame` - Returns the RGBA pixel data of the frame with the given index, 4 bytes per pixel
+/// * `descr` - Parameters the first frame has been rendered from
+///
+/// # Returns
+///
+/// `Ok(())` if all frames have been written, the error of the frame callback
+/// or the I/O or encoding error otherwise
+pub fn write_apng<W: Write>(
+    w: W,
+    width: u32,
+    height: u32,
+    frame_count: u32,
+    frame_delay: (u16, u16),
+    mut frame: impl FnMut(u32) -> std::io::Result<Vec<u8>>,
+    descr: &ParamDescription,
+) -> std::io::Result<()> {
+    let mut encoder = encoder(w, width, height, descr)?;
+    encoder.set_animated(frame_count, 0)?;
+    encoder.set_frame_delay(frame_delay.0, frame_delay.1)?;
+    let mut writer = encoder.write_header()?;
+    for index in 0..frame_count {
+        writer.write_image_data(&frame(index)?)?;
+    }
+    Ok(writer.finish()?)
+}
+
+/// Create a PNG encoder for RGBA images with sRGB color information and the text chunks.
+fn encoder<'a, W: Write>(
+    w: W,
+    width: u32,
+    height: u32,
+    descr: &ParamDescription,
+) -> std::io::Result<png::Encoder<'a, W>> {
+    let mut encoder = png::Encoder::new(w, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_gamma(png::ScaledFloat::new(1.0 / 2.2)); // 1.0 / 2.2, unscaled, but rounded
+    let source_chromaticities = png::SourceChromaticities::new(
+        // Using unscaled instantiation here
+        (0.31270, 0.32900),
+        (0.64000, 0.33000),
+        (0.30000, 0.60000),
+        (0.15000, 0.06000),
+    );
+    encoder.set_source_chromaticities(source_chromaticities);
+    encoder.add_itxt_chunk(PARAMS_KEYWORD.to_string(), descr.to_toml())?;
+    encoder.add_itxt_chunk(
+        SOFTWARE_KEYWORD.to_string(),
+        format!("mandel-rs {}", env!("MANDEL_FULL_VERSION")),
+    )?;
+    Ok(encoder)
+}
+
+/// Write an RGBA image together with its parameter description into the PNG file at `path`.
+pub fn write_png_file(
+    path: &Path,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    descr: &ParamDescription,
+) -> std::io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_png(&mut w, width, height, rgba, descr)?;
+    w.flush()
+}
+
+/// Read the parameter description embedded into a PNG data stream.
+///
+/// # Returns
+///
+/// The validated parameter description, `MissingInImage` if the PNG has been written
+/// by another program, or the error which prevented reading or parsing it
+pub fn read_param_description<R: BufRead + Seek>(
+    r: R,
+) -> Result<ParamDescription, ParamDescriptionError> {
+    let reader = png::Decoder::new(r)
+        .read_info()
+        .map_err(std::io::Error::from)?;
+    let chunk = reader
+        .info()
+        .utf8_text
+        .iter()
+        .find(|chunk| chunk.keyword == PARAMS_KEYWORD)
+        .ok_or(ParamDescriptionError::MissingInImage)?;
+    let text = chunk.get_text().map_err(std::io::Error::from)?;
+    ParamDescription::from_toml(&text)
+}
+
+/// Read the parameter description embedded into the PNG file at `path`.
+pub fn read_param_description_file(path: &Path) -> Result<ParamDescription, ParamDescriptionError> {
+    read_param_description(BufReader::new(File::open(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::param_presets::ParamPreset;
+    use std::io::Cursor;
+
+    #[test]
+    fn parameters_survive_png_round_trip() {
+        let descr = ParamPreset::MandelbrotSquaredSpirals.preset();
+        let mut data = Vec::new();
+        write_png(&mut data, 2, 1, &[255, 0, 0, 255, 0, 0, 255, 255], &descr).unwrap();
+        let restored = read_param_description(Cursor::new(&data)).unwrap();
+        assert_eq!(descr.center_x, restored.center_x);
+        assert_eq!(descr.center_y, restored.center_y);
+        assert_eq!(descr.radius, restored.radius);
+        assert_eq!(descr.max_iteration, restored.max_iteration);
+    }
+
+    #[test]
+    fn animated_png_has_all_frames_and_parameters() {
+        let descr = ParamPreset::MandelbrotFull.preset();
+        let mut data = Vec::new();
+        write_apng(
+            &mut data,
+            1,
+            1,
+            3,
+            (1, 25),
+            |frame| Ok(vec![frame as u8, 0, 0, 255]),
+            &descr,
+        )
+        .unwrap();
+        let reader = png::Decoder::new(Cursor::new(&data)).read_info().unwrap();
+        let animation = reader.info().animation_control.unwrap();
+        assert_eq!(3, animation.num_frames);
+        assert_eq!(0, animation.num_plays);
+        let restored = read_param_description(Cursor::new(&data)).unwrap();
+        assert_eq!(descr.radius, restored.radius);
+    }
+
+    #[test]
+    fn failing_frame_aborts_animated_png() {
+        let descr = ParamPreset::MandelbrotFull.preset();
+        let result = write_apng(
+            Vec::new(),
+            1,
+            1,
+            3,
+            (1, 25),
+            |frame| match frame {
+                0 => Ok(vec![0, 0, 0, 255]),
+                _ => Err(std::io::Error::other("no frame")),
+            },
+            &descr,
+        );
+        assert_eq!("no frame", result.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn banded_png_contains_all_rows() {
+        let descr = ParamPreset::MandelbrotFull.preset();
+        let rgba: Vec<u8> = (0..3 * 5 * 4).map(|value| value as u8).collect();
+        let mut bands = rgba.chunks(2 * 3 * 4);
+        let mut banded = Vec::new();
+        write_png_in_bands(
+            &mut banded,
+            3,
+            5,
+            || Ok(bands.next().unwrap_or_default().to_vec()),
+            &descr,
+        )
+        .unwrap();
+        let mut reader = png::Decoder::new(Cursor::new(&banded)).read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut decoded).unwrap();
+        assert_eq!(rgba, decoded);
+    }
+
+    #[test]
+    fn foreign_png_has_no_parameters() {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, 1, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder
+            .write_header()
+            .unwrap()
+            .write_image_data(&[0, 0, 0, 255])
+            .unwrap();
+        assert!(matches!(
+            read_param_description(Cursor::new(&data)),
+            Err(ParamDescriptionError::MissingInImage)
+        ));
+    }
+}
+
+// end of file
@@ -14,14 +14,15 @@
 //! - **Interpolation**: Smooth transitions between anchor colors
 //!
 //! ## Color Interpolation
-//! Uses linear interpolation in linear RGB color space for smooth transitions:
-//! - **Linear RGB**: Mathematically correct interpolation space
+//! Uses linear interpolation in a selectable color space for smooth transitions:
+//! - **Linear RGB**: Mathematically correct interpolation space, the default
+//! - **Perceptual Spaces**: Lab, Oklab, Oklch and HSLuv, see `interpolation`
 //! - **sRGB Conversion**: Final output in standard RGB for display
 //! - **Cyclic Gradients**: Seamless wrapping for infinite iteration ranges
 //!
 //! # Mathematical Foundation
 //!
-//! Color interpolation uses linear mixing in linear RGB space:
+//! Color interpolation uses linear mixing of the components of the color space:
 //! ```text
 //! color(t) = color₁ × (1-t) + color₂ × t
 //! where t ∈ [0,1] represents the interpolation factor
@@ -32,18 +33,34 @@
 //! - **Pre-computation**: Gradients calculated once and cached
 //! - **Fast Lookup**: O(1) color retrieval during rendering
 //! - **Memory Efficient**: Compact storage of interpolated color tables
+//!
+//! # Smooth Coloring
+//!
+//! Integer iteration counts select exactly one stripe which shows as visible bands.
+//! Fractional (smooth) iteration counts select a position _between_ two stripes,
+//! the color is then interpolated between both stripes in linear RGB.
 
 use palette::{LinSrgb, Mix, Srgb};
 
+use crate::storage::visualization::coloring::interpolation::ColorInterpolation;
+
+/// Largest position of an anchor color, position 1.0 is position 0.0 of the next cycle
+const MAX_ANCHOR_POSITION: f32 = 0.999_999;
+
+/// Largest histogram rank used for coloring, rank 1.0 would wrap around to the first stripe
+const MAX_RANK: f64 = 1.0 - f64::EPSILON;
+
 /// Generates linear color interpolation between two colors for gradient creation.
 ///
 /// Creates a smooth color transition by interpolating between two anchor colors
-/// in linear RGB color space. This ensures mathematically correct color mixing
-/// without the gamma-correction artifacts that occur in sRGB space.
+/// in the color space of `interpolation`. Linear RGB ensures mathematically correct
+/// color mixing without the gamma-correction artifacts that occur in sRGB space,
+/// perceptual spaces give even lightness and saturation.
 ///
 /// # Arguments
 ///
 /// * `target` - Destination vector to append interpolated colors
+/// * `interpolation` - Color space and hue direction to mix in
 /// * `stripe_count` - Number of interpolation steps to generate
 /// * `first_color` - Starting color of the interpolation (sRGB f32)
 /// * `last_color` - Ending color of the interpolation (sRGB f32)
@@ -52,14 +69,14 @@ use palette::{LinSrgb, Mix, Srgb};
 ///
 /// - **Inclusive Start**: `first_color` is included in the output
 /// - **Exclusive End**: `last_color` is NOT included (allows seamless gradient chains)
-/// - **Linear RGB**: Interpolation performed in linear color space
+/// - **Color Space**: Interpolation performed in the selected color space
 /// - **Quantization**: Results converted to 8-bit sRGB for final output
 ///
 /// # Color Space Mathematics
 ///
-/// 1. **sRGB → Linear RGB**: Remove gamma correction for proper interpolation
-/// 2. **Linear Interpolation**: Mathematical mixing in linear space
-/// 3. **Linear RGB → sRGB**: Apply gamma correction for display
+/// 1. **sRGB → Color Space**: E.g. remove gamma correction for proper interpolation
+/// 2. **Linear Interpolation**: Mathematical mixing of the components
+/// 3. **Color Space → sRGB**: Convert back and clamp to the displayable range
 ///
 /// # Performance
 ///
@@ -71,18 +88,18 @@ use palette::{LinSrgb, Mix, Srgb};
 /// between consecutive anchor colors in a color scheme.
 fn push_interpolation_part(
     target: &mut Vec<Srgb<u8>>,
+    interpolation: ColorInterpolation,
     stripe_count: usize,
     first_color: Srgb<f32>,
     last_color: Srgb<f32>,
 ) {
-    if stripe_count > 0 {
-        let first_lin: LinSrgb = first_color.into();
-        let last_lin: LinSrgb = last_color.into();
-        for stripe in 0..stripe_count {
-            let ratio = stripe as f32 / stripe_count as f32;
-            let stripe_lin = first_lin.mix(last_lin, ratio);
-            target.push(stripe_lin.into());
-        }
+    for stripe in 0..stripe_count {
+        let ratio = stripe as f32 / stripe_count as f32;
+        target.push(
+            interpolation
+                .mix(first_color, last_color, ratio)
+                .into_format(),
+        );
     }
 }
 
@@ -117,11 +134,16 @@ fn push_interpolation_part(
 /// - **Continuous**: Smooth transitions between all anchor points
 /// - **Periodic**: Cyclic behavior for infinite iteration ranges
 /// - **Deterministic**: Same scheme always produces identical results
+#[derive(Debug, Clone, PartialEq)]
 pub struct GradientColorScheme {
     /// Color for points that never escape (iteration = max_iterations)
     body_color: Srgb<f32>,
     /// Sequence of colors defining the gradient character
     anchor_colors: Vec<Srgb<f32>>,
+    /// Position of each anchor color in the gradient, ascending in `0.0..1.0`
+    anchor_positions: Vec<f32>,
+    /// Color space the colors between the anchors are interpolated in
+    interpolation: ColorInterpolation,
 }
 
 impl GradientColorScheme {
@@ -142,11 +164,106 @@ impl GradientColorScheme {
     /// - **Anchor Colors**: Should have sufficient contrast for visual appeal
     /// - **Color Count**: More anchors provide finer gradient control
     pub fn new(body_color: Srgb<f32>, anchor_colors: Vec<Srgb<f32>>) -> Self {
+        let count = anchor_colors.len();
         GradientColorScheme {
             body_color,
+            anchor_positions: (0..count).map(|i| i as f32 / count as f32).collect(),
             anchor_colors,
+            interpolation: ColorInterpolation::default(),
+        }
+    }
+    /// Creates a gradient color scheme with anchor colors at individual positions.
+    ///
+    /// # Arguments
+    ///
+    /// * `body_color` - Color for points that never escape (max iterations)
+    /// * `anchors` - Pairs of position and color, positions run from 0.0 to 1.0
+    ///   over the whole gradient and are clamped to this range
+    ///
+    /// # Returns
+    ///
+    /// New gradient scheme with the anchors sorted by position,
+    /// anchors with a non-finite position are dropped
+    ///
+    /// # Uneven Positions
+    ///
+    /// The gradient is still cyclic: after the last anchor, the colors are interpolated
+    /// back to the first anchor at position 1.0 + its position. A first anchor beyond 0.0
+    /// therefore gives the beginning of the gradient a blend of the last and first color.
+    pub fn with_positions(body_color: Srgb<f32>, mut anchors: Vec<(f32, Srgb<f32>)>) -> Self {
+        anchors.retain(|(position, _)| position.is_finite());
+        anchors.sort_by(|a, b| a.0.total_cmp(&b.0));
+        GradientColorScheme {
+            body_color,
+            anchor_positions: anchors
+                .iter()
+                .map(|(position, _)| position.clamp(0.0, MAX_ANCHOR_POSITION))
+                .collect(),
+            anchor_colors: anchors.into_iter().map(|(_, color)| color).collect(),
+            interpolation: ColorInterpolation::default(),
         }
     }
+    /// Returns this scheme with its colors interpolated as given, the default is linear RGB.
+    pub fn with_interpolation(self, interpolation: ColorInterpolation) -> Self {
+        GradientColorScheme {
+            interpolation,
+            ..self
+        }
+    }
+    /// Returns the color for points that never escape.
+    pub fn body_color(&self) -> Srgb<f32> {
+        self.body_color
+    }
+    /// Returns the anchors as pairs of position and color, sorted by position.
+    pub fn anchors(&self) -> Vec<(f32, Srgb<f32>)> {
+        self.anchor_positions
+            .iter()
+            .copied()
+            .zip(self.anchor_colors.iter().copied())
+            .collect()
+    }
+    /// Returns the color of the gradient at `position`, interpolated like the stripes.
+    ///
+    /// Positions wrap around, so 1.25 gives the same color as 0.25.
+    pub fn color_at(&self, position: f32) -> Srgb<f32> {
+        let count = self.anchor_colors.len();
+        if count == 0 {
+            return self.body_color;
+        }
+        // Find the last anchor at or before the position, wrapping to the last anchor
+        let position = position.rem_euclid(1.0);
+        let i = self
+            .anchor_positions
+            .iter()
+            .rposition(|p| *p <= position)
+            .unwrap_or(count - 1);
+        let start = self.anchor_positions[i];
+        let end = if i + 1 < count {
+            self.anchor_positions[i + 1]
+        } else {
+            self.anchor_positions[0] + 1.0
+        };
+        let offset = (position - start).rem_euclid(1.0);
+        let ratio = if end > start {
+            offset / (end - start)
+        } else {
+            0.0
+        };
+        self.interpolation.mix(
+            self.anchor_colors[i],
+            self.anchor_colors[(i + 1) % count],
+            ratio,
+        )
+    }
+    /// Returns the gradient interpolated to `stripe_count` colors, e.g. for previews.
+    pub fn stripes(&self, stripe_count: usize) -> Vec<Srgb<u8>> {
+        self.create_interpolation(stripe_count)
+    }
+    /// Returns the stripe at which an anchor at `position` starts.
+    fn anchor_stripe(position: f32, stripe_count: usize) -> usize {
+        // The small addition compensates rounding errors of positions like 0.7
+        (position as f64 * stripe_count as f64 + 1e-6) as usize
+    }
     /// Converts the abstract color scheme into a concrete interpolated color table.
     ///
     /// Generates a lookup table of colors by interpolating between anchor colors,
@@ -163,15 +280,16 @@ impl GradientColorScheme {
     ///
     /// # Interpolation Algorithm
     ///
-    /// 1. **Anchor Distribution**: Anchor colors spread evenly across stripe range
-    /// 2. **Segment Interpolation**: Linear interpolation between consecutive anchors
+    /// 1. **Anchor Distribution**: Anchor colors placed at their positions in the stripe range
+    /// 2. **Segment Interpolation**: Interpolation between consecutive anchors in the color space
     /// 3. **Cyclic Completion**: Final segment interpolates from last to first anchor
-    /// 4. **Quantization**: Convert to 8-bit sRGB for final output
+    /// 4. **Rotation**: Table rotated so that it starts at position 0.0
+    /// 5. **Quantization**: Convert to 8-bit sRGB for final output
     ///
     /// # Mathematical Approach
     ///
     /// ```text
-    /// For n anchor colors and s stripes:
+    /// For n evenly spread anchor colors and s stripes:
     /// - Anchor positions: 0, s/n, 2s/n, ..., (n-1)s/n, s
     /// - Each segment gets ⌊s/n⌋ interpolation steps
     /// - Remainder distributed across segments
@@ -183,21 +301,26 @@ impl GradientColorScheme {
     fn create_interpolation(&self, stripe_count: usize) -> Vec<Srgb<u8>> {
         let mut target = Vec::with_capacity(stripe_count);
         let anchor_count = self.anchor_colors.len();
-        let mut anchor_stripe = Vec::with_capacity(anchor_count + 1);
-        let stripe_factor = stripe_count as f32 / anchor_count as f32;
-        anchor_stripe.push(0);
-        for i in 1..anchor_count {
-            anchor_stripe.push((stripe_factor * i as f32) as usize);
+        if anchor_count == 0 || stripe_count == 0 {
+            return target;
         }
-        anchor_stripe.push(stripe_count);
+        let mut anchor_stripe: Vec<usize> = self
+            .anchor_positions
+            .iter()
+            .map(|position| Self::anchor_stripe(*position, stripe_count))
+            .collect();
+        let start = anchor_stripe[0];
+        anchor_stripe.push(start + stripe_count);
         for i in 0..anchor_count {
             push_interpolation_part(
                 &mut target,
+                self.interpolation,
                 anchor_stripe[i + 1] - anchor_stripe[i],
                 self.anchor_colors[i],
                 self.anchor_colors[(i + 1) % anchor_count],
             );
         }
+        target.rotate_right(start % stripe_count);
         target
     }
 }
@@ -329,7 +452,7 @@ impl GradientColors {
     /// # Assignment Function Examples
     ///
     /// - **Linear**: `assigner(it, _) = it` (direct mapping)
-    /// - **Logarithmic**: `assigner(it, _) = ln(it)` (compress high values)
+    /// - **Logarithmic**: `assigner(it, _) = ln(it)` (compress high values, truncated)
     /// - **Power**: `assigner(it, _) = it²` (emphasize low values)
     /// - **Modulo**: `assigner(it, modulo) = it % modulo` (periodic patterns)
     ///
@@ -339,19 +462,166 @@ impl GradientColors {
     pub fn iteration_to_color(
         &self,
         it: u32,
-        assigner: fn(u32, u32) -> u32,
+        assigner: fn(f64, u32) -> f64,
         maxit: u32,
     ) -> [u8; 4] {
-        if it == maxit || self.stripes.len() < 1 {
+        if it == maxit || self.stripes.is_empty() {
             Self::rgb_to_u84(&self.body_color)
         } else {
             Self::rgb_to_u84(
-                &self.stripes[(assigner(it, self.stripes.len() as u32) as usize)
+                &self.stripes[(assigner(it as f64, self.stripes.len() as u32) as usize)
                     .wrapping_add(self.offset)
                     % self.stripes.len()],
             )
         }
     }
+
+    /// Converts a fractional (smooth) iteration count to RGBA color without banding.
+    ///
+    /// Works like `iteration_to_color()`, but the assigned value selects a position
+    /// between two neighbouring stripes. The color is interpolated between them
+    /// in linear RGB, so continuous iteration values give continuous colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `it` - Integer iteration count, decides about body color
+    /// * `smooth` - Fractional iteration count used for the color position
+    /// * `assigner` - Mathematical function to transform iteration count
+    /// * `maxit` - Maximum iteration limit used in fractal computation
+    ///
+    /// # Returns
+    ///
+    /// 4-byte RGBA color array ready for pixel rendering
+    pub fn smooth_iteration_to_color(
+        &self,
+        it: u32,
+        smooth: f32,
+        assigner: fn(f64, u32) -> f64,
+        maxit: u32,
+    ) -> [u8; 4] {
+        if it == maxit || self.stripes.is_empty() {
+            return Self::rgb_to_u84(&self.body_color);
+        }
+        let len = self.stripes.len();
+        let position =
+            (assigner(smooth as f64, len as u32) + self.offset as f64).rem_euclid(len as f64);
+        let index = (position as usize).min(len - 1);
+        let ratio = (position - index as f64) as f32;
+        let first: LinSrgb = self.stripes[index].into_linear();
+        let second: LinSrgb = self.stripes[(index + 1) % len].into_linear();
+        Self::rgb_to_u84(&first.mix(second, ratio).into())
+    }
+
+    /// Converts the rank of a point in the iteration histogram to RGBA color.
+    ///
+    /// Used for histogram equalisation: The ranks `0.0..=1.0` are spread once over
+    /// the whole gradient, so each stripe covers the same number of points.
+    /// Rank 1.0 of the points with the most iterations stays on the last stripe.
+    ///
+    /// # Arguments
+    ///
+    /// * `it` - Integer iteration count, decides about body color
+    /// * `rank` - Fraction of escaped points with fewer iterations,
+    ///   see `IterationHistogram::rank()`
+    /// * `interpolate` - Whether to interpolate between stripes (smooth coloring)
+    /// * `maxit` - Maximum iteration limit used in fractal computation
+    ///
+    /// # Returns
+    ///
+    /// 4-byte RGBA color array ready for pixel rendering
+    pub fn rank_to_color(&self, it: u32, rank: f64, interpolate: bool, maxit: u32) -> [u8; 4] {
+        let position = rank.clamp(0.0, MAX_RANK) * self.stripes.len() as f64;
+        let identity: fn(f64, u32) -> f64 = |it, _modulo| it;
+        if interpolate {
+            self.smooth_iteration_to_color(it, position as f32, identity, maxit)
+        } else if it == maxit || self.stripes.is_empty() {
+            Self::rgb_to_u84(&self.body_color)
+        } else {
+            let index = (position as usize).wrapping_add(self.offset) % self.stripes.len();
+            Self::rgb_to_u84(&self.stripes[index])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::visualization::coloring::presets::IterationAssignment;
+
+    #[test]
+    fn smooth_color_interpolates_between_stripes() {
+        let scheme = GradientColorScheme::new(
+            Srgb::new(0.0, 0.0, 0.0),
+            vec![Srgb::new(0.0, 0.0, 0.0), Srgb::new(1.0, 1.0, 1.0)],
+        );
+        let colors = GradientColors::new(&scheme, 2, 0);
+        let linear = IterationAssignment::Linear.assignment_function();
+        assert_eq!(
+            [0, 0, 0, 255],
+            colors.smooth_iteration_to_color(4, 4.0, linear, 100)
+        );
+        assert_eq!(
+            [255, 255, 255, 255],
+            colors.smooth_iteration_to_color(5, 5.0, linear, 100)
+        );
+        let between = colors.smooth_iteration_to_color(4, 4.5, linear, 100);
+        assert!(between[0] > 0 && between[0] < 255);
+        // Integer coloring stays on the stripe
+        assert_eq!([0, 0, 0, 255], colors.iteration_to_color(4, linear, 100));
+    }
+
+    #[test]
+    fn anchors_are_placed_at_their_positions() {
+        let (black, white) = (Srgb::new(0.0, 0.0, 0.0), Srgb::new(1.0, 1.0, 1.0));
+        let even = GradientColorScheme::new(black, vec![black, white]);
+        assert_eq!(
+            even.create_interpolation(8),
+            GradientColorScheme::with_positions(black, vec![(0.5, white), (0.0, black)])
+                .create_interpolation(8)
+        );
+        let uneven = GradientColorScheme::with_positions(black, vec![(0.25, black), (0.75, white)]);
+        let stripes = uneven.create_interpolation(8);
+        assert_eq!(8, stripes.len());
+        assert_eq!(Srgb::new(0, 0, 0), stripes[2]);
+        assert_eq!(Srgb::new(255, 255, 255), stripes[6]);
+        // Before the first anchor, the gradient blends from the last anchor back to the first
+        assert!(stripes[0].red > 0 && stripes[0].red < 255);
+    }
+
+    #[test]
+    fn non_finite_positions_are_dropped() {
+        let (black, white) = (Srgb::new(0.0, 0.0, 0.0), Srgb::new(1.0, 1.0, 1.0));
+        let scheme = GradientColorScheme::with_positions(
+            black,
+            vec![
+                (0.0, black),
+                (f32::NAN, white),
+                (f32::INFINITY, white),
+                (0.5, white),
+            ],
+        );
+        assert_eq!(vec![(0.0, black), (0.5, white)], scheme.anchors());
+        assert_eq!(8, scheme.create_interpolation(8).len());
+    }
+
+    #[test]
+    fn highest_rank_stays_on_last_stripe() {
+        let scheme = GradientColorScheme::new(
+            Srgb::new(0.0, 0.0, 0.0),
+            vec![Srgb::new(0.0, 0.0, 0.0), Srgb::new(1.0, 1.0, 1.0)],
+        );
+        for stripes in [2, 3, 7] {
+            let colors = GradientColors::new(&scheme, stripes, 0);
+            assert_eq!(
+                colors.rank_to_color(5, 0.999, false, 100),
+                colors.rank_to_color(5, 1.0, false, 100)
+            );
+            assert_eq!(
+                colors.rank_to_color(5, 0.0, false, 100),
+                colors.rank_to_color(5, -0.5, false, 100)
+            );
+        }
+    }
 }
 
 // end of file
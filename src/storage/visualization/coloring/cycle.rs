@@ -0,0 +1,154 @@
+//! Color cycling by advancing the stripe offset of the gradient colors.
+//!
+//! Changing the stripe offset only shifts the gradient lookup, so the fractal can be
+//! animated without any recomputation. The GUI advances the offset continuously while
+//! cycling is enabled, the animated PNG export writes exactly one full cycle.
+//!
+//! # Full Cycle
+//!
+//! After `stripe_count` steps of one stripe, the offset is back at its start value.
+//! An exported cycle therefore loops seamlessly. If the speed is too high for the
+//! frame rate viewers can display, several stripes are advanced per frame, always
+//! a divisor of the stripe count so that the loop stays seamless.
+
+/// Highest frame rate of exported color cycles, viewers may slow down faster animations
+pub const MAX_CYCLE_FPS: u32 = 50;
+
+/// Direction in which the colors move through the gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CycleDirection {
+    /// Increase the stripe offset
+    Forward,
+    /// Decrease the stripe offset
+    Backward,
+}
+
+impl CycleDirection {
+    /// Returns all available directions.
+    pub fn all() -> &'static [Self] {
+        &[Self::Forward, Self::Backward]
+    }
+    /// Returns human-readable name for the direction.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Forward => "Forward",
+            Self::Backward => "Backward",
+        }
+    }
+}
+
+impl std::fmt::Display for CycleDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Speed and direction of the color cycling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorCycle {
+    /// Stripes advanced per second, at least 1
+    pub speed: u32,
+    /// Direction of the movement
+    pub direction: CycleDirection,
+}
+
+impl ColorCycle {
+    /// Creates a color cycle, the speed is raised to at least 1 stripe per second.
+    pub fn new(speed: u32, direction: CycleDirection) -> Self {
+        ColorCycle {
+            speed: speed.max(1),
+            direction,
+        }
+    }
+
+    /// Returns the stripe offset after advancing `steps` stripes from `offset`.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Current stripe offset
+    /// * `steps` - Number of stripes to advance in the cycle's direction
+    /// * `stripe_count` - Number of stripes of the gradient, the offset wraps around there
+    pub fn advance(&self, offset: u32, steps: u32, stripe_count: u32) -> u32 {
+        if stripe_count == 0 {
+            return offset;
+        }
+        let (offset, steps) = (offset % stripe_count, steps % stripe_count);
+        match self.direction {
+            CycleDirection::Forward => (offset + steps) % stripe_count,
+            CycleDirection::Backward => (offset + stripe_count - steps) % stripe_count,
+        }
+    }
+
+    /// Returns the number of stripes advanced per frame of an exported cycle.
+    ///
+    /// This is the smallest divisor of the stripe count which keeps the frame rate
+    /// at or below `MAX_CYCLE_FPS`.
+    pub fn frame_step(&self, stripe_count: u32) -> u32 {
+        (1..=stripe_count.max(1))
+            .find(|step| {
+                stripe_count.max(1).is_multiple_of(*step) && self.speed <= MAX_CYCLE_FPS * step
+            })
+            .unwrap_or(stripe_count.max(1))
+    }
+
+    /// Returns the stripe offsets of all frames of one full cycle, starting at `offset`.
+    pub fn frame_offsets(&self, offset: u32, stripe_count: u32) -> Vec<u32> {
+        let step = self.frame_step(stripe_count);
+        (0..stripe_count.max(1) / step)
+            .map(|frame| self.advance(offset, frame * step, stripe_count))
+            .collect()
+    }
+
+    /// Returns the display time of one exported frame as `(numerator, denominator)` of seconds.
+    pub fn frame_delay(&self, stripe_count: u32) -> (u16, u16) {
+        let step = self.frame_step(stripe_count);
+        // Reduce the fraction so that both parts fit into the 16 bits of the PNG frame control
+        let divisor = gcd(step, self.speed);
+        let (mut numerator, mut denominator) = (step / divisor, self.speed / divisor);
+        while numerator > u16::MAX as u32 || denominator > u16::MAX as u32 {
+            numerator = numerator.div_ceil(2);
+            denominator = denominator.div_ceil(2);
+        }
+        (numerator as u16, denominator as u16)
+    }
+}
+
+impl Default for ColorCycle {
+    /// Creates a forward cycle with 20 stripes per second.
+    fn default() -> Self {
+        Self::new(20, CycleDirection::Forward)
+    }
+}
+
+/// Greatest common divisor of two numbers
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a.max(1) } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_wraps_around_in_both_directions() {
+        let forward = ColorCycle::new(10, CycleDirection::Forward);
+        let backward = ColorCycle::new(10, CycleDirection::Backward);
+        assert_eq!(2, forward.advance(14, 4, 16));
+        assert_eq!(14, backward.advance(2, 4, 16));
+        assert_eq!(5, forward.advance(5, 16, 16));
+    }
+
+    #[test]
+    fn exported_cycle_loops_seamlessly() {
+        let fast = ColorCycle::new(120, CycleDirection::Backward);
+        assert_eq!(4, fast.frame_step(256));
+        let offsets = fast.frame_offsets(3, 256);
+        assert_eq!(64, offsets.len());
+        assert_eq!(3, offsets[0]);
+        assert_eq!(fast.advance(offsets[63], 4, 256), offsets[0]);
+        assert_eq!((1, 30), fast.frame_delay(256));
+        assert_eq!(1, ColorCycle::default().frame_step(256));
+    }
+}
+
+// end of file
@@ -0,0 +1,372 @@
+//! Import and export of gradients in the file formats of other fractal and graphics programs.
+//!
+//! Artists keep their palettes in the formats of the programs they come from. These are
+//! converted into `GradientColorScheme`s with anchor positions as close to the original
+//! as the format allows.
+//!
+//! # Supported Formats
+//!
+//! - **Fractint `.map`**: One `R G B` line per color index (0..=255). Index 0 becomes the
+//!   body color, the following indices become evenly spread anchors.
+//! - **GIMP `.ggr`**: Segments with left, middle and right position and a color at each end.
+//!   Each segment start becomes an anchor, a midpoint moved away from the center adds an
+//!   anchor with the half-way color there, and a color jump between segments adds an anchor
+//!   just before the jump. Curved, sine and spherical blending as well as HSV coloring are
+//!   approximated by the linear blending of mandel-rs.
+//! - **Ultra Fractal `.ugr`**: Collections of gradients with `index=… color=…` entries,
+//!   indices run from 0 to 399, colors are `0xBBGGRR` integers. Opacity is ignored.
+//!
+//! Gradients can be exported as `.ggr`, one linear RGB segment per anchor pair.
+
+use std::path::Path;
+
+use palette::Srgb;
+
+use crate::storage::visualization::coloring::{
+    base::GradientColorScheme,
+    interpolation::ColorInterpolation,
+    user_palette::{NamedColorScheme, PaletteError},
+};
+
+/// Number of positions of an Ultra Fractal gradient
+const UGR_POSITIONS: f32 = 400.0;
+
+/// Distance before a color jump at which the color before the jump is placed
+const JUMP_WIDTH: f32 = 0.0005;
+
+/// Black body color for formats without one
+const BLACK: Srgb<f32> = Srgb::new(0.0, 0.0, 0.0);
+
+/// Import all gradients from a file, the format is selected by its extension.
+///
+/// # Returns
+///
+/// The gradients of the file, at least one, or the error which prevented reading them
+pub fn import_file(path: &Path) -> Result<Vec<NamedColorScheme>, PaletteError> {
+    let text = std::fs::read_to_string(path)?;
+    let name = path
+        .file_stem()
+        .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("map") => Ok(vec![import_map(&text, name)?]),
+        Some("ggr") => Ok(vec![import_ggr(&text, name)?]),
+        Some("ugr") => import_ugr(&text),
+        _ => Err(PaletteError::UnsupportedFormat(path.display().to_string())),
+    }
+}
+
+/// Import a Fractint color map.
+///
+/// # Arguments
+///
+/// * `text` - Content of the `.map` file
+/// * `name` - Name of the scheme, Fractint maps have no name of their own
+pub fn import_map(text: &str, name: String) -> Result<NamedColorScheme, PaletteError> {
+    let mut colors = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let values: Vec<&str> = line.split_whitespace().take(3).collect();
+        if values.is_empty() {
+            continue;
+        }
+        let component = |value: &str| {
+            value
+                .parse::<u8>()
+                .map_err(|_| syntax_error("map", number, line))
+        };
+        if values.len() < 3 {
+            return Err(syntax_error("map", number, line));
+        }
+        colors.push(
+            Srgb::new(
+                component(values[0])?,
+                component(values[1])?,
+                component(values[2])?,
+            )
+            .into_format(),
+        );
+    }
+    if colors.len() < 2 {
+        return Err(PaletteError::Syntax(
+            "color map needs at least two colors".to_string(),
+        ));
+    }
+    let body = colors.remove(0);
+    Ok(NamedColorScheme {
+        name,
+        scheme: GradientColorScheme::new(body, colors),
+    })
+}
+
+/// Import a GIMP gradient.
+///
+/// # Arguments
+///
+/// * `text` - Content of the `.ggr` file
+/// * `fallback_name` - Name of the scheme if the file contains none
+pub fn import_ggr(text: &str, fallback_name: String) -> Result<NamedColorScheme, PaletteError> {
+    let mut lines = text.lines().enumerate();
+    if lines.next().map(|(_, line)| line.trim()) != Some("GIMP Gradient") {
+        return Err(PaletteError::Syntax(
+            "missing \"GIMP Gradient\" header".to_string(),
+        ));
+    }
+    let mut name = fallback_name;
+    let mut segment_count = None;
+    for (number, line) in lines.by_ref() {
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = value.trim().to_string();
+        } else {
+            segment_count = Some(
+                line.trim()
+                    .parse::<usize>()
+                    .map_err(|_| syntax_error("ggr", number, line))?,
+            );
+            break;
+        }
+    }
+    let segment_count = segment_count.unwrap_or(0);
+    let mut anchors: Vec<(f32, Srgb<f32>)> = Vec::new();
+    let mut previous_end: Option<(f32, Srgb<f32>)> = None;
+    for (number, line) in lines.take(segment_count) {
+        let values = line
+            .split_whitespace()
+            .map(|value| value.parse::<f32>().ok().filter(|value| value.is_finite()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| syntax_error("ggr", number, line))?;
+        if values.len() < 11 {
+            return Err(syntax_error("ggr", number, line));
+        }
+        let (left, middle, right) = (values[0], values[1], values[2]);
+        let left_color = Srgb::new(values[3], values[4], values[5]);
+        let right_color = Srgb::new(values[7], values[8], values[9]);
+        // A color jump keeps the color of the previous segment until just before the jump
+        if let Some((end, end_color)) = previous_end
+            && end_color != left_color
+        {
+            anchors.push(((end - JUMP_WIDTH).max(0.0), end_color));
+        }
+        anchors.push((left, left_color));
+        let center = (left + right) / 2.0;
+        if (middle - center).abs() > JUMP_WIDTH {
+            anchors.push((
+                middle,
+                ColorInterpolation::default().mix(left_color, right_color, 0.5),
+            ));
+        }
+        previous_end = Some((right, right_color));
+    }
+    if anchors.is_empty() {
+        return Err(PaletteError::Syntax("gradient has no segments".to_string()));
+    }
+    // The gradient of mandel-rs is cyclic, a jump from the end to the start is kept as well
+    if let Some((end, end_color)) = previous_end
+        && end_color != anchors[0].1
+    {
+        anchors.push(((end - JUMP_WIDTH).max(0.0), end_color));
+    }
+    Ok(NamedColorScheme {
+        name,
+        scheme: GradientColorScheme::with_positions(BLACK, anchors),
+    })
+}
+
+/// Import all gradients of an Ultra Fractal gradient collection.
+pub fn import_ugr(text: &str) -> Result<Vec<NamedColorScheme>, PaletteError> {
+    let mut schemes = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..]
+            .find('}')
+            .map(|close| open + close)
+            .ok_or_else(|| PaletteError::Syntax("unterminated gradient".to_string()))?;
+        let entry_name = rest[..open].trim().rsplit('\n').next().unwrap_or("").trim();
+        schemes.push(import_ugr_gradient(&rest[open + 1..close], entry_name)?);
+        rest = &rest[close + 1..];
+    }
+    if schemes.is_empty() {
+        return Err(PaletteError::Syntax("no gradient found".to_string()));
+    }
+    Ok(schemes)
+}
+
+/// Import one gradient of an Ultra Fractal collection, given is the text between the braces.
+fn import_ugr_gradient(body: &str, entry_name: &str) -> Result<NamedColorScheme, PaletteError> {
+    let gradient = body.split("opacity:").next().unwrap_or(body);
+    let title = gradient
+        .find("title=\"")
+        .and_then(|start| {
+            let title = &gradient[start + 7..];
+            title.find('"').map(|end| title[..end].to_string())
+        })
+        .filter(|title| !title.is_empty());
+    let mut anchors = Vec::new();
+    let mut index = None;
+    for token in gradient.split_whitespace() {
+        if let Some(value) = token.strip_prefix("index=") {
+            index = Some(value.parse::<i32>().map_err(|_| ugr_error(token))?);
+        } else if let Some(value) = token.strip_prefix("color=") {
+            let color = value.parse::<u32>().map_err(|_| ugr_error(token))?;
+            let index = index.take().ok_or_else(|| ugr_error(token))?;
+            let [red, green, blue, _] = color.to_le_bytes();
+            anchors.push((
+                (index as f32 / UGR_POSITIONS).rem_euclid(1.0),
+                Srgb::new(red, green, blue).into_format(),
+            ));
+        }
+    }
+    if anchors.is_empty() {
+        return Err(PaletteError::Syntax(format!(
+            "gradient \"{}\" has no colors",
+            entry_name
+        )));
+    }
+    Ok(NamedColorScheme {
+        name: title.unwrap_or_else(|| entry_name.to_string()),
+        scheme: GradientColorScheme::with_positions(BLACK, anchors),
+    })
+}
+
+/// Export a gradient as GIMP gradient.
+///
+/// Each pair of consecutive anchors becomes one linear RGB segment. As the gradient is
+/// cyclic, the segments from the last anchor to 1.0 and from 0.0 to the first anchor
+/// use the color the gradient has at position 0.0. The body color cannot be exported.
+pub fn export_ggr(named: &NamedColorScheme) -> String {
+    let scheme = &named.scheme;
+    let mut points = scheme.anchors();
+    if points.first().is_none_or(|(position, _)| *position > 0.0) {
+        points.insert(0, (0.0, scheme.color_at(0.0)));
+    }
+    points.push((1.0, points[0].1));
+    let mut ggr = format!(
+        "GIMP Gradient\nName: {}\n{}\n",
+        named.name.replace('\n', " "),
+        points.len() - 1
+    );
+    for pair in points.windows(2) {
+        let ((left, left_color), (right, right_color)) = (pair[0], pair[1]);
+        ggr.push_str(&format!(
+            "{:.6} {:.6} {:.6} {:.6} {:.6} {:.6} 1.000000 {:.6} {:.6} {:.6} 1.000000 0 0\n",
+            left,
+            (left + right) / 2.0,
+            right,
+            left_color.red,
+            left_color.green,
+            left_color.blue,
+            right_color.red,
+            right_color.green,
+            right_color.blue,
+        ));
+    }
+    ggr
+}
+
+/// Return the syntax error for an unparsable line of a file format.
+fn syntax_error(format: &str, number: usize, line: &str) -> PaletteError {
+    PaletteError::Syntax(format!(
+        "invalid {} line {}: \"{}\"",
+        format,
+        number + 1,
+        line.trim()
+    ))
+}
+
+/// Return the syntax error for an unparsable entry of an Ultra Fractal gradient.
+fn ugr_error(token: &str) -> PaletteError {
+    PaletteError::Syntax(format!("invalid ugr entry \"{}\"", token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fractint_map_gives_body_and_even_anchors() {
+        let named = import_map("0 0 0\n255 0 0 red\n0 0 255\n", "test".to_string()).unwrap();
+        assert_eq!(Srgb::new(0.0, 0.0, 0.0), named.scheme.body_color());
+        let anchors = named.scheme.anchors();
+        assert_eq!(2, anchors.len());
+        assert_eq!((0.5, Srgb::new(0.0, 0.0, 1.0)), anchors[1]);
+        assert!(import_map("0 0\n", "broken".to_string()).is_err());
+    }
+
+    #[test]
+    fn ultra_fractal_gradients_keep_their_positions() {
+        let ugr = "first {\ngradient:\n  title=\"Fire\" smooth=yes index=0 color=255\n  \
+                   index=100 color=65535\nopacity:\n  smooth=no index=0 opacity=255\n}\n\
+                   second {\ngradient:\n  index=200 color=16711680\n}\n";
+        let schemes = import_ugr(ugr).unwrap();
+        assert_eq!(2, schemes.len());
+        assert_eq!("Fire", schemes[0].name);
+        assert_eq!(
+            vec![
+                (0.0, Srgb::new(1.0, 0.0, 0.0)),
+                (0.25, Srgb::new(1.0, 1.0, 0.0))
+            ],
+            schemes[0].scheme.anchors()
+        );
+        assert_eq!("second", schemes[1].name);
+        assert_eq!(
+            (0.5, Srgb::new(0.0, 0.0, 1.0)),
+            schemes[1].scheme.anchors()[0]
+        );
+    }
+
+    #[test]
+    fn ggr_export_can_be_imported_again() {
+        let original = NamedColorScheme {
+            name: "Uneven".to_string(),
+            scheme: GradientColorScheme::with_positions(
+                BLACK,
+                vec![
+                    (0.0, Srgb::new(1.0, 0.0, 0.0)),
+                    (0.3, Srgb::new(0.0, 1.0, 0.0)),
+                    (0.8, Srgb::new(0.0, 0.0, 1.0)),
+                ],
+            ),
+        };
+        let ggr = export_ggr(&original);
+        assert!(ggr.starts_with("GIMP Gradient\nName: Uneven\n3\n"));
+        let restored = import_ggr(&ggr, String::new()).unwrap();
+        assert_eq!(original.name, restored.name);
+        for ((position, color), (original_position, original_color)) in restored
+            .scheme
+            .anchors()
+            .into_iter()
+            .zip(original.scheme.anchors())
+        {
+            assert!((position - original_position).abs() < 1e-5);
+            assert!((color.red - original_color.red).abs() < 1e-5);
+            assert!((color.blue - original_color.blue).abs() < 1e-5);
+        }
+        assert_eq!(3, restored.scheme.anchors().len());
+    }
+
+    #[test]
+    fn ggr_midpoints_and_jumps_add_anchors() {
+        let ggr = "GIMP Gradient\nName: Jump\n2\n\
+                   0.0 0.2 0.5 0 0 0 1 1 1 1 1 0 0\n\
+                   0.5 0.75 1.0 1 0 0 1 0 0 1 1 0 0\n";
+        let anchors = import_ggr(ggr, String::new()).unwrap().scheme.anchors();
+        // Start, moved midpoint, end before the jump to red, red, end before the jump to black
+        assert_eq!(5, anchors.len());
+        assert_eq!(0.2, anchors[1].0);
+        assert_eq!(Srgb::new(1.0, 1.0, 1.0), anchors[2].1);
+        assert_eq!((0.5, Srgb::new(1.0, 0.0, 0.0)), anchors[3]);
+    }
+
+    #[test]
+    fn ggr_with_non_finite_values_is_rejected() {
+        let ggr = "GIMP Gradient\nName: Broken\n1\n\
+                   0.0 NaN 1.0 0 0 0 1 1 1 1 1 0 0\n";
+        assert!(matches!(
+            import_ggr(ggr, String::new()),
+            Err(PaletteError::Syntax(_))
+        ));
+    }
+}
+
+// end of file
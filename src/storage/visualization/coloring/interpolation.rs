@@ -0,0 +1,298 @@
+//! Color space in which the colors between two gradient anchors are interpolated.
+//!
+//! Mixing in linear RGB is physically correct but not perceptually even: between
+//! complementary anchors like blue and yellow it passes through a muddy gray, and
+//! the lightness of a transition changes faster at one end than at the other.
+//! Perceptual color spaces avoid this.
+//!
+//! # Color Spaces
+//!
+//! - **Linear RGB**: The classic interpolation of mandel-rs
+//! - **Lab**: CIE L\*a\*b\*, perceptually uniform lightness
+//! - **Oklab**: Modern perceptual space, even lightness and saturation
+//! - **Oklch**: Oklab in polar coordinates, keeps the saturation between anchors
+//! - **HSLuv**: Perceptual hue, saturation and lightness, colorful transitions
+//!
+//! # Hue Direction
+//!
+//! Cylindrical spaces (Oklch, HSLuv) interpolate the hue as an angle, which can run
+//! either way around the color wheel. The `HueDirection` selects the way, like the
+//! `hue-interpolation-method` of CSS. Anchors without hue (black, white and grays)
+//! take over the hue of the other anchor, so that no unrelated hues appear.
+
+use palette::{FromColor, Hsluv, Lab, LinSrgb, Mix, Oklab, Oklch, Srgb};
+use serde::{Deserialize, Serialize};
+
+/// Chroma or saturation below which a color is regarded as having no hue
+const ACHROMATIC_LIMIT: f32 = 1e-4;
+
+/// Color space in which gradient colors are interpolated.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum InterpolationSpace {
+    /// Linear mixing of the physical light intensities
+    #[default]
+    LinearRgb,
+    /// CIE L*a*b* with D65 white point
+    Lab,
+    /// Oklab perceptual color space
+    Oklab,
+    /// Oklab with polar hue and chroma
+    Oklch,
+    /// Perceptual hue, saturation and lightness
+    Hsluv,
+}
+
+impl InterpolationSpace {
+    /// Returns all available color spaces.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::LinearRgb,
+            Self::Lab,
+            Self::Oklab,
+            Self::Oklch,
+            Self::Hsluv,
+        ]
+    }
+    /// Returns human-readable name for the color space.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LinearRgb => "Linear RGB",
+            Self::Lab => "Lab",
+            Self::Oklab => "Oklab",
+            Self::Oklch => "Oklch",
+            Self::Hsluv => "HSLuv",
+        }
+    }
+    /// Returns whether the space interpolates the hue as angle, so that the hue direction applies.
+    pub fn is_cylindrical(&self) -> bool {
+        matches!(self, Self::Oklch | Self::Hsluv)
+    }
+}
+
+impl std::fmt::Display for InterpolationSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Way around the color wheel the hue takes in cylindrical color spaces.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum HueDirection {
+    /// The shorter way, at most 180°
+    #[default]
+    Shorter,
+    /// The longer way, at least 180°
+    Longer,
+    /// Always with increasing hue angle
+    Increasing,
+    /// Always with decreasing hue angle
+    Decreasing,
+}
+
+impl HueDirection {
+    /// Returns all available hue directions.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Shorter,
+            Self::Longer,
+            Self::Increasing,
+            Self::Decreasing,
+        ]
+    }
+    /// Returns human-readable name for the hue direction.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Shorter => "Shorter",
+            Self::Longer => "Longer",
+            Self::Increasing => "Increasing",
+            Self::Decreasing => "Decreasing",
+        }
+    }
+    /// Returns the hue at `ratio` between `first` and `last`, all in degrees.
+    ///
+    /// Equal hues stay constant in every direction instead of running through all hues.
+    fn mix_hue(&self, first: f32, last: f32, ratio: f32) -> f32 {
+        let difference = (last - first).rem_euclid(360.0);
+        let difference = match self {
+            Self::Shorter if difference > 180.0 => difference - 360.0,
+            Self::Longer if difference > 0.0 && difference <= 180.0 => difference - 360.0,
+            Self::Decreasing if difference > 0.0 => difference - 360.0,
+            _ => difference,
+        };
+        (first + difference * ratio).rem_euclid(360.0)
+    }
+}
+
+impl std::fmt::Display for HueDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Complete interpolation setup of a gradient, the color space and the hue direction.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ColorInterpolation {
+    /// Color space to mix in
+    pub space: InterpolationSpace,
+    /// Hue direction, only used by cylindrical color spaces
+    pub hue_direction: HueDirection,
+}
+
+impl ColorInterpolation {
+    /// Creates an interpolation setup.
+    pub fn new(space: InterpolationSpace, hue_direction: HueDirection) -> Self {
+        ColorInterpolation {
+            space,
+            hue_direction,
+        }
+    }
+
+    /// Mixes two colors, `ratio` 0.0 gives `first_color`, 1.0 gives `last_color`.
+    ///
+    /// Colors outside of the sRGB gamut, which perceptual spaces can reach between
+    /// two valid colors, are clamped to it.
+    pub fn mix(&self, first_color: Srgb<f32>, last_color: Srgb<f32>, ratio: f32) -> Srgb<f32> {
+        match self.space {
+            InterpolationSpace::LinearRgb => {
+                let first_lin: LinSrgb = first_color.into_linear();
+                let last_lin: LinSrgb = last_color.into_linear();
+                Srgb::from_linear(first_lin.mix(last_lin, ratio))
+            }
+            InterpolationSpace::Lab => Srgb::from_color(
+                Lab::from_color(first_color).mix(Lab::from_color(last_color), ratio),
+            ),
+            InterpolationSpace::Oklab => Srgb::from_color(
+                Oklab::from_color(first_color).mix(Oklab::from_color(last_color), ratio),
+            ),
+            InterpolationSpace::Oklch => {
+                let (first, last) = (
+                    Oklch::from_color(first_color),
+                    Oklch::from_color(last_color),
+                );
+                let (first_hue, last_hue) = hues(
+                    (first.hue.into_positive_degrees(), first.chroma),
+                    (last.hue.into_positive_degrees(), last.chroma),
+                );
+                Srgb::from_color(Oklch::new(
+                    lerp(first.l, last.l, ratio),
+                    lerp(first.chroma, last.chroma, ratio),
+                    self.hue_direction.mix_hue(first_hue, last_hue, ratio),
+                ))
+            }
+            InterpolationSpace::Hsluv => {
+                let (first, last) = (
+                    Hsluv::from_color(first_color),
+                    Hsluv::from_color(last_color),
+                );
+                // Black and white have no hue whatever their saturation is
+                let colorfulness = |color: &Hsluv| {
+                    if color.l < 0.01 || color.l > 99.99 {
+                        0.0
+                    } else {
+                        color.saturation
+                    }
+                };
+                let (first_hue, last_hue) = hues(
+                    (first.hue.into_positive_degrees(), colorfulness(&first)),
+                    (last.hue.into_positive_degrees(), colorfulness(&last)),
+                );
+                Srgb::from_color(Hsluv::new(
+                    self.hue_direction.mix_hue(first_hue, last_hue, ratio),
+                    lerp(first.saturation, last.saturation, ratio),
+                    lerp(first.l, last.l, ratio),
+                ))
+            }
+        }
+    }
+}
+
+/// Returns the hues to interpolate between, an achromatic color takes the hue of the other.
+///
+/// Both arguments are pairs of hue in degrees and chroma or saturation.
+fn hues((first_hue, first_chroma): (f32, f32), (last_hue, last_chroma): (f32, f32)) -> (f32, f32) {
+    match (
+        first_chroma < ACHROMATIC_LIMIT,
+        last_chroma < ACHROMATIC_LIMIT,
+    ) {
+        (true, false) => (last_hue, last_hue),
+        (false, true) => (first_hue, first_hue),
+        _ => (first_hue, last_hue),
+    }
+}
+
+/// Linear interpolation of a single component
+fn lerp(first: f32, last: f32, ratio: f32) -> f32 {
+    first + (last - first) * ratio
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hue_takes_the_selected_way() {
+        assert_eq!(10.0, HueDirection::Shorter.mix_hue(350.0, 30.0, 0.5));
+        assert_eq!(190.0, HueDirection::Longer.mix_hue(350.0, 30.0, 0.5));
+        assert_eq!(10.0, HueDirection::Increasing.mix_hue(350.0, 30.0, 0.5));
+        assert_eq!(190.0, HueDirection::Decreasing.mix_hue(350.0, 30.0, 0.5));
+        assert_eq!(90.0, HueDirection::Decreasing.mix_hue(120.0, 60.0, 0.5));
+        assert_eq!(60.0, HueDirection::Longer.mix_hue(60.0, 60.0, 0.5));
+    }
+
+    #[test]
+    fn all_spaces_keep_the_end_colors() {
+        const STEP: f32 = 1.0 / 255.0;
+        let (blue, yellow) = (Srgb::new(0.0, 0.2, 1.0), Srgb::new(1.0, 0.9, 0.0));
+        for space in InterpolationSpace::all() {
+            for hue_direction in HueDirection::all() {
+                let interpolation = ColorInterpolation::new(*space, *hue_direction);
+                for (ratio, expected) in [(0.0, blue), (1.0, yellow)] {
+                    let color = interpolation.mix(blue, yellow, ratio);
+                    // The HSLuv conversion is not exact in f32, but within one 8 bit step
+                    assert!(
+                        (color.red - expected.red).abs() < STEP,
+                        "{} {}",
+                        space,
+                        ratio
+                    );
+                    assert!(
+                        (color.blue - expected.blue).abs() < STEP,
+                        "{} {}",
+                        space,
+                        ratio
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn perceptual_spaces_avoid_muddy_middle() {
+        let (blue, yellow) = (Srgb::new(0.0, 0.0, 1.0), Srgb::new(1.0, 1.0, 0.0));
+        let saturation = |color: Srgb<f32>| {
+            let max = color.red.max(color.green).max(color.blue);
+            let min = color.red.min(color.green).min(color.blue);
+            max - min
+        };
+        let linear = ColorInterpolation::default().mix(blue, yellow, 0.5);
+        let oklch = ColorInterpolation::new(InterpolationSpace::Oklch, HueDirection::Shorter)
+            .mix(blue, yellow, 0.5);
+        assert!(saturation(linear) < 0.01);
+        assert!(saturation(oklch) > 0.3);
+    }
+
+    #[test]
+    fn achromatic_anchor_takes_the_other_hue() {
+        let (black, red) = (Srgb::new(0.0, 0.0, 0.0), Srgb::new(1.0, 0.0, 0.0));
+        for space in [InterpolationSpace::Oklch, InterpolationSpace::Hsluv] {
+            let color = ColorInterpolation::new(space, HueDirection::Longer).mix(black, red, 0.5);
+            assert!(
+                color.red > color.green && color.red > color.blue,
+                "{}",
+                space
+            );
+        }
+    }
+}
+
+// end of file
@@ -0,0 +1,117 @@
+//! Normal-map lighting for a pseudo-3D appearance of the fractal.
+//!
+//! The escaped points of the fractal can be interpreted as a surface whose
+//! normal at each point is the direction of `z / (dz/dc)`, see `DataPoint::surface_normal`.
+//! Lighting this surface from a configurable direction gives the fractal an embossed,
+//! three-dimensional look, known as "3D" or "normal map" mode in other fractal programs.
+//!
+//! # Lighting Model
+//!
+//! The 2D normal `n` is lifted into 3D with the light height `h`:
+//!
+//! ```text
+//! diffuse  = clamp((n · l + h) / (1 + h), 0, 1)
+//! specular = strength · diffuse^SHININESS
+//! color    = gradient_color · diffuse + white · specular
+//! ```
+//!
+//! where `l` is the unit vector of the light direction in the complex plane.
+//! A large height flattens the relief, a small height exaggerates it.
+//! Shading is performed in linear RGB, like the gradient interpolation.
+
+use euclid::Vector2D;
+use palette::{LinSrgb, Srgb};
+
+use crate::storage::coord_spaces::MathSpace;
+
+/// Exponent of the specular highlight, higher values give smaller highlights
+const SHININESS: i32 = 20;
+
+/// Light setup for the normal-map shading of gradient colors.
+///
+/// Points without surface normal (the fractal's body, formulas without
+/// derivative) are not shaded and keep their gradient color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalMapLighting {
+    /// Direction of the light in degrees, counter-clockwise from the positive real axis
+    pub angle: f32,
+    /// Height of the light above the surface, at least 0
+    pub height: f32,
+    /// Strength of the specular highlight, 0 (none) to 1 (full white)
+    pub specular: f32,
+}
+
+impl Default for NormalMapLighting {
+    /// Light from the upper right, moderate relief and a gentle highlight.
+    fn default() -> Self {
+        NormalMapLighting {
+            angle: 45.0,
+            height: 1.5,
+            specular: 0.3,
+        }
+    }
+}
+
+impl NormalMapLighting {
+    /// Creates a new lighting setup.
+    ///
+    /// # Arguments
+    ///
+    /// * `angle` - Light direction in degrees, counter-clockwise from the positive real axis
+    /// * `height` - Light height above the surface, negative values are raised to 0
+    /// * `specular` - Specular strength, clamped to `0..=1`
+    ///
+    /// # Returns
+    ///
+    /// Lighting setup ready for shading
+    pub fn new(angle: f32, height: f32, specular: f32) -> Self {
+        NormalMapLighting {
+            angle,
+            height: height.max(0.0),
+            specular: specular.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Shades a gradient color according to the surface normal of its point.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - RGBA gradient color of the point
+    /// * `normal` - Unit surface normal of the point in mathematical coordinates
+    ///
+    /// # Returns
+    ///
+    /// RGBA color with diffuse lighting and specular highlight applied, alpha is kept
+    pub fn shade(&self, color: [u8; 4], normal: Vector2D<f32, MathSpace>) -> [u8; 4] {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let diffuse =
+            ((normal.x * cos + normal.y * sin + self.height) / (1.0 + self.height)).clamp(0.0, 1.0);
+        let highlight = self.specular * diffuse.powi(SHININESS);
+        let base: LinSrgb = Srgb::new(color[0], color[1], color[2]).into_linear();
+        let lit = LinSrgb::new(
+            (base.red * diffuse + highlight).min(1.0),
+            (base.green * diffuse + highlight).min(1.0),
+            (base.blue * diffuse + highlight).min(1.0),
+        );
+        let shaded: Srgb<u8> = Srgb::from_linear(lit);
+        [shaded.red, shaded.green, shaded.blue, color[3]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_facing_normal_is_brightest() {
+        let lighting = NormalMapLighting::new(0.0, 1.0, 0.0);
+        let color = [200, 100, 50, 255];
+        assert_eq!(color, lighting.shade(color, Vector2D::new(1.0, 0.0)));
+        let averted = lighting.shade(color, Vector2D::new(-1.0, 0.0));
+        assert_eq!([0, 0, 0, 255], averted);
+        let sideways = lighting.shade(color, Vector2D::new(0.0, 1.0));
+        assert!(sideways[0] < color[0] && sideways[0] > 0);
+    }
+}
+
+// end of file
@@ -26,10 +26,15 @@
 //! ## 2. Gradient Interpolation
 //! Transformed values are mapped to colors using gradient schemes:
 //! - **Anchor Colors**: Key colors defining the gradient
-//! - **Linear Interpolation**: Smooth transitions between anchors
+//! - **Interpolation**: Smooth transitions between anchors in a selectable color space
 //! - **Cyclic Gradients**: Wrapping behavior for infinite iteration ranges
 //!
-//! ## 3. Special Cases
+//! ## 3. Optional Lighting
+//! Gradient colors can be shaded with a normal map derived from `dz/dc`:
+//! - **Light Direction**: Angle of the light in the complex plane
+//! - **Height and Specular**: Strength of relief and highlight
+//!
+//! ## 4. Special Cases
 //! - **Body Color**: Special color for points that never escape (iteration = max)
 //! - **Modulo Wrapping**: Handles iteration counts exceeding gradient length
 //!
@@ -49,5 +54,20 @@
 /// Core color mapping data structures and algorithms
 pub mod base;
 
+/// Color cycling by advancing the stripe offset
+pub mod cycle;
+
+/// Import and export of gradient files of other programs
+pub mod gradient_files;
+
+/// Color spaces for the interpolation between gradient anchors
+pub mod interpolation;
+
+/// Normal-map shading for a pseudo-3D appearance
+pub mod lighting;
+
 /// Pre-defined color schemes and mathematical assignment functions
 pub mod presets;
+
+/// User-defined color schemes and their palette file
+pub mod user_palette;
@@ -47,6 +47,7 @@
 //! ```
 
 use palette::Srgb;
+use serde::{Deserialize, Serialize};
 
 use crate::storage::visualization::coloring::base::GradientColorScheme;
 
@@ -73,7 +74,9 @@ use crate::storage::visualization::coloring::base::GradientColorScheme;
 /// - **Sufficient Contrast**: Clear differentiation between iteration levels
 /// - **Mathematical Relevance**: Colors that enhance fractal pattern visibility
 /// - **Cross-Platform Consistency**: Colors specified in device-independent sRGB
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// Variants are stored by name in parameter files, renaming them breaks existing files.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum GradientColorPreset {
     /// Dawn/dusk color progression: deep blue → white → yellow → red
     Sunrise,
@@ -263,7 +266,9 @@ impl std::fmt::Display for GradientColorPreset {
 /// - **Deep Zooms**: Logarithmic functions handle extreme iteration ranges
 /// - **Boundary Details**: Root functions reveal fine escape patterns
 /// - **Overview Images**: Linear or squared functions provide clear structure
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// Variants are stored by name in parameter files, renaming them breaks existing files.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum IterationAssignment {
     /// x → x³ - Extreme emphasis on low iteration counts
     Cubic,
@@ -279,6 +284,8 @@ pub enum IterationAssignment {
     Logarithmic,
     /// x → ln(ln(x)) - Double logarithmic for very large iteration counts
     LogLog,
+    /// x → rank(x) - Histogram equalisation, spreads the gradient over the occurring counts
+    Histogram,
 }
 
 impl IterationAssignment {
@@ -294,7 +301,8 @@ impl IterationAssignment {
     /// # Ordering
     ///
     /// Functions are ordered from most emphasis (Cubic) to most compression (LogLog),
-    /// providing a logical progression for UI presentation.
+    /// providing a logical progression for UI presentation. The data-dependent
+    /// Histogram assignment comes last.
     pub fn all() -> &'static [Self] {
         &[
             Self::Cubic,
@@ -304,6 +312,7 @@ impl IterationAssignment {
             Self::CubicRoot,
             Self::Logarithmic,
             Self::LogLog,
+            Self::Histogram,
         ]
     }
     /// Returns the mathematical notation and description for the assignment function.
@@ -337,6 +346,7 @@ impl IterationAssignment {
             Self::CubicRoot => "x → ∛x (cube root)",
             Self::Logarithmic => "x → ln(x) (logarithmic)",
             Self::LogLog => "x → ln(ln(x)) (double log)",
+            Self::Histogram => "x → rank(x) (histogram)",
         }
     }
     /// Returns the actual mathematical function for iteration transformation.
@@ -347,55 +357,66 @@ impl IterationAssignment {
     ///
     /// # Returns
     ///
-    /// Function pointer with signature `fn(u32, u32) -> u32` where:
-    /// - **First parameter**: Iteration count from fractal computation
+    /// Function pointer with signature `fn(f64, u32) -> f64` where:
+    /// - **First parameter**: Iteration count from fractal computation, possibly fractional
     /// - **Second parameter**: Modulo value (stripe count for wrapping)
-    /// - **Return value**: Transformed iteration value for color lookup
+    /// - **Return value**: Transformed iteration value for color lookup, never negative
     ///
     /// # Function Implementations
     ///
     /// ## Power Functions
-    /// - **Cubic**: `(it % modulo)³` - Uses modulo to prevent overflow
-    /// - **Squared**: `(it % modulo)²` - Uses modulo to prevent overflow
+    /// - **Cubic**: `(it % modulo)³` - Uses modulo to keep values in range
+    /// - **Squared**: `(it % modulo)²` - Uses modulo to keep values in range
     ///
     /// ## Root Functions
-    /// - **Square Root**: `⌊√it⌋` - Ignores modulo, direct square root
-    /// - **Cube Root**: `⌊it^(1/3)⌋` - Ignores modulo, direct cube root
+    /// - **Square Root**: `√it` - Ignores modulo, direct square root
+    /// - **Cube Root**: `∛it` - Ignores modulo, direct cube root
     ///
     /// ## Logarithmic Functions
-    /// - **Logarithmic**: `⌊ln(it)⌋` - Natural log, ignores modulo
-    /// - **Double Log**: `⌊ln(ln(it))⌋` - Double natural log, ignores modulo
+    /// - **Logarithmic**: `ln(it)` - Natural log, ignores modulo
+    /// - **Double Log**: `ln(ln(it))` - Double natural log, ignores modulo
     ///
     /// ## Linear Function
     /// - **Linear**: `it` - Identity function, ignores modulo
     ///
-    /// # Overflow Handling
-    ///
-    /// Power functions use modulo to prevent integer overflow, while other
-    /// functions rely on the natural compression of their mathematical properties.
+    /// ## Histogram
+    /// - **Histogram**: `it` - The rank depends on the whole image and cannot be a plain
+    ///   function. Callers check `uses_histogram()` and color by
+    ///   `GradientColors::rank_to_color()` with the stage's `IterationHistogram` instead.
     ///
-    /// # Floating Point Precision
+    /// # Floating Point Values
     ///
-    /// Root and logarithmic functions use f64 precision internally and
-    /// truncate to u32 for final result.
-    pub fn assignment_function(&self) -> fn(u32, u32) -> u32 {
+    /// The functions work on `f64` so that fractional (smooth) iteration counts
+    /// keep their fraction. Integer coloring truncates the result to the stripe index.
+    /// Undefined results (e.g. `ln(0)`) are mapped to 0.
+    pub fn assignment_function(&self) -> fn(f64, u32) -> f64 {
         match self {
-            // Power functions: Use modulo to prevent overflow
-            Self::Cubic => |it, modulo| (it % modulo) * (it % modulo) * (it % modulo),
-            Self::Squared => |it, modulo| (it % modulo) * (it % modulo),
-            
+            // Power functions: Use modulo to keep values in range
+            Self::Cubic => |it, modulo| (it % modulo as f64).powi(3),
+            Self::Squared => |it, modulo| (it % modulo as f64).powi(2),
+
             // Linear function: Direct mapping
             Self::Linear => |it, _modulo| it,
-            
+
             // Root functions: Natural compression, ignore modulo
-            Self::SquareRoot => |it, _modulo| (it as f64).sqrt() as u32,
-            Self::CubicRoot => |it, _modulo| (it as f64).powf(1.0 / 3.0) as u32,
-            
+            Self::SquareRoot => |it, _modulo| it.sqrt().max(0.0),
+            Self::CubicRoot => |it, _modulo| it.cbrt().max(0.0),
+
             // Logarithmic functions: Extreme compression, ignore modulo
-            Self::Logarithmic => |it, _modulo| (it as f64).ln() as u32,
-            Self::LogLog => |it, _modulo| (it as f64).ln().ln() as u32,
+            Self::Logarithmic => |it, _modulo| it.ln().max(0.0),
+            Self::LogLog => |it, _modulo| it.ln().ln().max(0.0),
+
+            // Histogram: Mapped by the caller through the stage's histogram
+            Self::Histogram => |it, _modulo| it,
         }
     }
+    /// Returns whether colors are assigned by rank in the iteration histogram.
+    ///
+    /// If `true`, `assignment_function()` is only a placeholder and colors must be
+    /// obtained by `GradientColors::rank_to_color()`.
+    pub fn uses_histogram(&self) -> bool {
+        *self == Self::Histogram
+    }
 }
 
 impl std::fmt::Display for IterationAssignment {
@@ -0,0 +1,351 @@
+//! User-defined gradient color schemes and the palette file storing them.
+//!
+//! Besides the compiled-in `GradientColorPreset`s, users can create their own schemes
+//! in the GUI's gradient editor. These schemes are kept in one palette file per user,
+//! so they are available in every session beside the built-in presets.
+//!
+//! # File Format
+//!
+//! The palette file is a TOML document with a top-level `version` key, like parameter files.
+//! Colors are sRGB components in `0.0..=1.0`, positions run from 0.0 to 1.0 over the gradient:
+//!
+//! ```toml
+//! version = 1
+//!
+//! [[scheme]]
+//! name = "Ember"
+//! body = [0.0, 0.0, 0.0]
+//! anchors = [
+//!     { position = 0.0, color = [0.1, 0.0, 0.0] },
+//!     { position = 0.7, color = [1.0, 0.5, 0.0] },
+//! ]
+//! ```
+//!
+//! # Location
+//!
+//! The file is `mandel-rs/palettes.toml` in the user's configuration directory:
+//! `$XDG_CONFIG_HOME` or `~/.config` on Unix-like systems, `%APPDATA%` on Windows.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use palette::Srgb;
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
+
+use crate::storage::visualization::coloring::base::GradientColorScheme;
+
+/// Version of the palette file written by this program
+pub const CURRENT_VERSION: u32 = 1;
+
+/// Errors that can occur while reading or writing palette files.
+#[derive(Debug)]
+pub enum PaletteError {
+    /// Reading or writing the palette file failed
+    Io(std::io::Error),
+    /// The text is no valid TOML or does not match the structure of its version
+    Syntax(String),
+    /// The file has been written by a newer program version or is damaged
+    UnsupportedVersion(u32),
+    /// The gradient file has a format which cannot be imported
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "cannot access palette file: {}", error),
+            Self::Syntax(message) => write!(f, "malformed palette: {}", message),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported palette file version {} (supported: 1 to {})",
+                version, CURRENT_VERSION
+            ),
+            Self::UnsupportedFormat(file) => write!(
+                f,
+                "unsupported gradient format of {} (supported: .map, .ggr, .ugr)",
+                file
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PaletteError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for PaletteError {
+    fn from(error: toml::de::Error) -> Self {
+        Self::Syntax(error.to_string())
+    }
+}
+
+/// A gradient color scheme together with the name it is selected by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedColorScheme {
+    /// Name shown in the color scheme selection, unique within a palette
+    pub name: String,
+    /// The gradient definition
+    pub scheme: GradientColorScheme,
+}
+
+/// The user-defined color schemes, as stored in the palette file.
+#[derive(Debug, Clone, Default)]
+pub struct UserPalette {
+    /// All schemes in the order of their creation
+    pub schemes: Vec<NamedColorScheme>,
+}
+
+impl UserPalette {
+    /// Return the path of the user's palette file, None if no configuration directory is known.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_dir.join("mandel-rs").join("palettes.toml"))
+    }
+
+    /// Load the user's palette file.
+    ///
+    /// A missing file gives an empty palette, as the program works without.
+    pub fn load_default() -> Result<Self, PaletteError> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Write the palette into the user's palette file, creating its directory if needed.
+    pub fn save_default(&self) -> Result<(), PaletteError> {
+        let path = Self::default_path().ok_or_else(|| {
+            PaletteError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "no configuration directory",
+            ))
+        })?;
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        self.save(&path)
+    }
+
+    /// Parse a palette from TOML text of any supported version.
+    pub fn from_toml(text: &str) -> Result<Self, PaletteError> {
+        let probe: VersionProbe = toml::from_str(text)?;
+        match probe.version {
+            1 => {
+                let file: PaletteFileV1 = toml::from_str(text)?;
+                Ok(UserPalette {
+                    schemes: file
+                        .scheme
+                        .into_iter()
+                        .map(NamedColorScheme::from)
+                        .collect(),
+                })
+            }
+            version => Err(PaletteError::UnsupportedVersion(version)),
+        }
+    }
+
+    /// Serialize this palette as TOML text in the current version.
+    pub fn to_toml(&self) -> String {
+        let file = PaletteFileV1 {
+            version: CURRENT_VERSION,
+            scheme: self.schemes.iter().map(ColorSchemeV1::from).collect(),
+        };
+        // Serializing plain structs of strings and numbers into TOML cannot fail
+        toml::to_string(&file).expect("palette is always representable in TOML")
+    }
+
+    /// Read a palette from the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, PaletteError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write this palette into the file at `path`, replacing its content.
+    pub fn save(&self, path: &Path) -> Result<(), PaletteError> {
+        Ok(std::fs::write(path, self.to_toml())?)
+    }
+
+    /// Return the scheme with the given name.
+    pub fn find(&self, name: &str) -> Option<&NamedColorScheme> {
+        self.schemes.iter().find(|scheme| scheme.name == name)
+    }
+
+    /// Add a scheme, replacing the scheme of the same name if there is one.
+    pub fn insert(&mut self, scheme: NamedColorScheme) {
+        match self.schemes.iter_mut().find(|s| s.name == scheme.name) {
+            Some(existing) => *existing = scheme,
+            None => self.schemes.push(scheme),
+        }
+    }
+
+    /// Remove the scheme with the given name, returns whether there was one.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let count = self.schemes.len();
+        self.schemes.retain(|scheme| scheme.name != name);
+        self.schemes.len() != count
+    }
+}
+
+/// Format a color as `#rrggbb` hex string.
+pub fn color_to_hex(color: Srgb<f32>) -> String {
+    let color: Srgb<u8> = color.into_format();
+    format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue)
+}
+
+/// Parse a color given as `#rrggbb` or `rrggbb` hex string, None if it is malformed.
+pub fn color_from_hex(text: &str) -> Option<Srgb<f32>> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Srgb::new(component(0)?, component(2)?, component(4)?).into_format())
+}
+
+/// Minimal view of a palette file to find out its version.
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+/// Palette file as stored on disc in version 1.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PaletteFileV1 {
+    version: u32,
+    #[serde(default)]
+    scheme: Vec<ColorSchemeV1>,
+}
+
+/// Named color scheme of version 1, also embedded into parameter files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColorSchemeV1 {
+    name: String,
+    body: [f32; 3],
+    anchors: Vec<AnchorV1>,
+}
+
+/// Anchor color of version 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnchorV1 {
+    #[serde(deserialize_with = "finite_position")]
+    position: f32,
+    color: [f32; 3],
+}
+
+/// Deserialize an anchor position, TOML's `nan` and `inf` are rejected.
+fn finite_position<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let position = f32::deserialize(deserializer)?;
+    if position.is_finite() {
+        Ok(position)
+    } else {
+        Err(D::Error::custom(format!(
+            "anchor position must be a finite number, found {}",
+            position
+        )))
+    }
+}
+
+impl From<&NamedColorScheme> for ColorSchemeV1 {
+    fn from(named: &NamedColorScheme) -> Self {
+        let rgb = |color: Srgb<f32>| [color.red, color.green, color.blue];
+        ColorSchemeV1 {
+            name: named.name.clone(),
+            body: rgb(named.scheme.body_color()),
+            anchors: named
+                .scheme
+                .anchors()
+                .into_iter()
+                .map(|(position, color)| AnchorV1 {
+                    position,
+                    color: rgb(color),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<ColorSchemeV1> for NamedColorScheme {
+    /// Converts the stored scheme, out-of-range components are clamped.
+    /// A scheme without anchors colors everything in its body color.
+    fn from(v1: ColorSchemeV1) -> Self {
+        let srgb = |[red, green, blue]: [f32; 3]| {
+            Srgb::new(
+                red.clamp(0.0, 1.0),
+                green.clamp(0.0, 1.0),
+                blue.clamp(0.0, 1.0),
+            )
+        };
+        NamedColorScheme {
+            name: v1.name,
+            scheme: GradientColorScheme::with_positions(
+                srgb(v1.body),
+                v1.anchors
+                    .into_iter()
+                    .map(|anchor| (anchor.position, srgb(anchor.color)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_survives_round_trip() {
+        let mut palette = UserPalette::default();
+        palette.insert(NamedColorScheme {
+            name: "Ember".to_string(),
+            scheme: GradientColorScheme::with_positions(
+                Srgb::new(0.0, 0.0, 0.1),
+                vec![
+                    (0.0, Srgb::new(0.1, 0.0, 0.0)),
+                    (0.7, Srgb::new(1.0, 0.5, 0.0)),
+                ],
+            ),
+        });
+        let restored = UserPalette::from_toml(&palette.to_toml()).unwrap();
+        assert_eq!(palette.schemes, restored.schemes);
+        assert!(matches!(
+            UserPalette::from_toml("version = 7"),
+            Err(PaletteError::UnsupportedVersion(7))
+        ));
+        let broken = palette
+            .to_toml()
+            .replace("position = 0.7", "position = nan");
+        assert!(matches!(
+            UserPalette::from_toml(&broken),
+            Err(PaletteError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn hex_colors_are_parsed() {
+        let color = color_from_hex("#ff8000").unwrap();
+        assert_eq!("#ff8000", color_to_hex(color));
+        assert_eq!(Some(color), color_from_hex(" FF8000 "));
+        assert_eq!(None, color_from_hex("#ff80"));
+        assert_eq!(None, color_from_hex("#gg8000"));
+    }
+}
+
+// end of file
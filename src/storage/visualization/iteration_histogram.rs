@@ -0,0 +1,120 @@
+//! Incrementally maintained histogram of iteration counts.
+//!
+//! Histogram equalisation colors a point by its _rank_ among all points of the image
+//! instead of by a fixed formula of its iteration count. This spreads the gradient
+//! evenly over the iteration counts which actually occur, even at deep zooms where
+//! they cluster in a narrow range.
+//!
+//! # Fenwick Tree
+//!
+//! The visualization stage changes point by point while the computation runs.
+//! Rebuilding the cumulative distribution for each frame would cost O(max_iteration)
+//! plus a full pass over the stage. The histogram is therefore kept in a Fenwick tree
+//! (binary indexed tree): adding or removing a point and querying the number of points
+//! below a given iteration count are all O(log max_iteration).
+
+/// Histogram of iteration counts with fast cumulative queries.
+///
+/// Counts beyond `max_iteration` are clamped to it. Points with `max_iteration`
+/// iterations did not escape, they are counted but excluded from the ranks.
+#[derive(Debug, Clone)]
+pub struct IterationHistogram {
+    /// Fenwick tree, element `i` (1-based) covers the counts `i - lowbit(i) .. i`
+    tree: Vec<u64>,
+    /// Maximum iteration count of the computation
+    max_iteration: u32,
+}
+
+impl IterationHistogram {
+    /// Creates an empty histogram for iteration counts `0..=max_iteration`.
+    pub fn new(max_iteration: u32) -> Self {
+        IterationHistogram {
+            tree: vec![0; max_iteration as usize + 2],
+            max_iteration,
+        }
+    }
+
+    /// Change the number of points with `iteration_count` iterations by `delta`.
+    fn update(&mut self, iteration_count: u32, delta: i64) {
+        let mut index = iteration_count.min(self.max_iteration) as usize + 1;
+        while index < self.tree.len() {
+            self.tree[index] = self.tree[index].wrapping_add_signed(delta);
+            index += index & index.wrapping_neg();
+        }
+    }
+
+    /// Add a point with `iteration_count` iterations.
+    pub fn add(&mut self, iteration_count: u32) {
+        self.update(iteration_count, 1);
+    }
+
+    /// Remove a previously added point with `iteration_count` iterations.
+    pub fn remove(&mut self, iteration_count: u32) {
+        self.update(iteration_count, -1);
+    }
+
+    /// Return the number of points with less than `iteration_count` iterations.
+    pub fn count_below(&self, iteration_count: u32) -> u64 {
+        let mut index = iteration_count.min(self.max_iteration + 1) as usize;
+        let mut sum = 0;
+        while index > 0 {
+            sum += self.tree[index];
+            index -= index & index.wrapping_neg();
+        }
+        sum
+    }
+
+    /// Return the number of escaped points, i.e. those below `max_iteration`.
+    pub fn escaped_count(&self) -> u64 {
+        self.count_below(self.max_iteration)
+    }
+
+    /// Return the rank of an iteration value among all escaped points.
+    ///
+    /// # Arguments
+    ///
+    /// * `iteration` - Integer or smooth iteration value
+    ///
+    /// # Returns
+    ///
+    /// Fraction of escaped points with fewer iterations, in `0.0..=1.0`. For fractional
+    /// values the rank is interpolated between the integer counts around it, so
+    /// smooth iteration values give smooth ranks.
+    pub fn rank(&self, iteration: f64) -> f64 {
+        let escaped = self.escaped_count();
+        if escaped == 0 {
+            return 0.0;
+        }
+        let floor = iteration.max(0.0).floor();
+        let count = (floor as u32).min(self.max_iteration);
+        let below = self.count_below(count);
+        let at = self.count_below(count + 1) - below;
+        let fraction = (iteration - floor).clamp(0.0, 1.0);
+        ((below as f64 + fraction * at as f64) / escaped as f64).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_follow_distribution() {
+        let mut histogram = IterationHistogram::new(100);
+        for count in [5, 5, 5, 10, 20, 100] {
+            histogram.add(count);
+        }
+        assert_eq!(5, histogram.escaped_count());
+        assert_eq!(0.0, histogram.rank(5.0));
+        assert_eq!(0.6, histogram.rank(6.0));
+        assert_eq!(0.8, histogram.rank(15.0));
+        assert_eq!(0.3, histogram.rank(5.5));
+        histogram.remove(5);
+        histogram.add(30);
+        assert_eq!(5, histogram.escaped_count());
+        assert_eq!(2, histogram.count_below(6));
+        assert_eq!(1.0, histogram.rank(100.0));
+    }
+}
+
+// end of file
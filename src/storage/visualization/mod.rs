@@ -1,3 +1,5 @@
+pub mod iteration_histogram;
+pub mod renderer;
 pub mod viz_stage;
 pub mod viz_storage;
 
@@ -0,0 +1,329 @@
+//! GUI-independent conversion of visualization data into RGBA pixels.
+//!
+//! The `Renderer` bundles everything which decides about the color of a pixel:
+//! the gradient color table, the iteration assignment, smooth coloring, normal-map
+//! lighting and the policy for points which have not been computed yet.
+//! The interactive GUI, image exporters and headless tools all render through it,
+//! so the same parameters give identical pixels everywhere.
+//!
+//! # Pixel Layout
+//!
+//! Rendered buffers contain the pixels in row-major order as RGBA bytes,
+//! `[R, G, B, A, R, G, B, A, ...]`, starting with the stage's first line.
+//!
+//! # Supersampling
+//!
+//! If the stage has been computed with supersampling, each rendered pixel covers
+//! `factor × factor` data points. Their colors, not their iteration counts, are averaged
+//! in linear RGB, so the result is the same as if a camera had taken the picture.
+//! Pixels refined by adaptive anti-aliasing carry their own sub-pixel samples,
+//! which are averaged the same way.
+
+use palette::{LinSrgb, Srgb};
+
+use crate::storage::{
+    data_point::{DataPoint, DataQuality},
+    image_comp_properties::Supersampling,
+    visualization::{
+        coloring::{
+            base::GradientColors, lighting::NormalMapLighting, presets::IterationAssignment,
+        },
+        viz_stage::VizStage,
+    },
+};
+
+/// Color of points for which neither data nor a guess is available
+pub const UNKNOWN_COLOR: [u8; 4] = [128, 128, 128, 255];
+
+/// Policy for rendering points which have not been computed yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GuessPolicy {
+    /// Render unknown points in the neutral `UNKNOWN_COLOR`
+    Neutral,
+    /// Use the nearest computed point on a coarser grid, see `Renderer::guess_point()`
+    CoarserGrid,
+}
+
+/// Converts the data points of a visualization stage into colors.
+pub struct Renderer {
+    /// Gradient color table including stripe count and offset
+    colors: GradientColors,
+    /// Function mapping iteration counts to gradient positions
+    assignment: IterationAssignment,
+    /// Whether to interpolate between stripes using the smooth iteration count
+    smooth: bool,
+    /// Normal-map lighting, `None` for flat colors
+    lighting: Option<NormalMapLighting>,
+    /// Handling of points without data
+    guess_policy: GuessPolicy,
+    /// Maximum iteration count of the computation, decides about body color
+    max_iteration: u32,
+    /// Supersampling of the stage, decides how many data points form one pixel
+    supersampling: Supersampling,
+}
+
+impl Renderer {
+    /// Creates a renderer.
+    ///
+    /// # Arguments
+    ///
+    /// * `colors` - Gradient color table to use
+    /// * `assignment` - Iteration assignment function
+    /// * `smooth` - Whether to use smooth coloring
+    /// * `lighting` - Normal-map lighting, `None` to disable it
+    /// * `guess_policy` - How to render points which have not been computed yet
+    /// * `max_iteration` - Maximum iteration count of the rendered data
+    /// * `supersampling` - Supersampling the stage has been computed with
+    pub fn new(
+        colors: GradientColors,
+        assignment: IterationAssignment,
+        smooth: bool,
+        lighting: Option<NormalMapLighting>,
+        guess_policy: GuessPolicy,
+        max_iteration: u32,
+        supersampling: Supersampling,
+    ) -> Self {
+        Renderer {
+            colors,
+            assignment,
+            smooth,
+            lighting,
+            guess_policy,
+            max_iteration,
+            supersampling,
+        }
+    }
+
+    /// Returns width and height of the rendered image of the stage in pixels.
+    pub fn output_size(&self, stage: &VizStage) -> (usize, usize) {
+        let factor = self.supersampling.factor() as usize;
+        (stage.width() / factor, stage.height() / factor)
+    }
+
+    /// Renders the complete stage into an RGBA buffer of the `output_size()`, 4 bytes per pixel.
+    pub fn render(&self, stage: &VizStage) -> Vec<u8> {
+        let factor = self.supersampling.factor() as usize;
+        let (width, height) = self.output_size(stage);
+        let mut rgba = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                if factor == 1 {
+                    rgba.extend_from_slice(&self.pixel(stage, x, y));
+                } else {
+                    let samples = (0..factor * factor).map(|sample| {
+                        self.pixel(
+                            stage,
+                            x * factor + sample % factor,
+                            y * factor + sample / factor,
+                        )
+                    });
+                    rgba.extend_from_slice(&average_color(samples));
+                }
+            }
+        }
+        rgba
+    }
+
+    /// Returns the color of the stage's pixel at `(x, y)`, guessing according to the policy.
+    ///
+    /// If the pixel has sub-pixel samples, the colors of the point and all samples are averaged.
+    pub fn pixel(&self, stage: &VizStage, x: usize, y: usize) -> [u8; 4] {
+        if let Some(point) = stage.get(x, y) {
+            let samples = stage.samples(x, y);
+            if samples.is_empty() {
+                self.color(stage, point)
+            } else {
+                average_color(
+                    std::iter::once(point)
+                        .chain(samples)
+                        .map(|sample| self.color(stage, sample)),
+                )
+            }
+        } else if self.guess_policy == GuessPolicy::CoarserGrid
+            && let Some(point) = Self::guess_point(stage, x, y)
+        {
+            self.color(stage, &point)
+        } else {
+            UNKNOWN_COLOR
+        }
+    }
+
+    /// Converts fractal data point to RGBA pixel color.
+    ///
+    /// # Arguments
+    ///
+    /// * `stage` - Stage the point belongs to, its histogram is used for histogram coloring
+    /// * `point` - Fractal computation result to colorize
+    ///
+    /// # Returns
+    ///
+    /// RGBA pixel data as `[red, green, blue, alpha]` bytes
+    ///
+    /// # Color Mapping Process
+    ///
+    /// 1. Apply iteration assignment function (linear, logarithmic, etc.)
+    ///    to the integer or, with smooth coloring, fractional iteration count,
+    ///    or look up its rank in the stage's iteration histogram
+    /// 2. Map result to color gradient position
+    /// 3. Extract RGBA values from gradient, interpolated between stripes for smooth coloring
+    /// 4. Shade with normal-map lighting if enabled and the point has a surface normal
+    pub fn color(&self, stage: &VizStage, point: &DataPoint) -> [u8; 4] {
+        let color = if self.assignment.uses_histogram() {
+            let iteration = if self.smooth {
+                point.smooth_iteration as f64
+            } else {
+                point.iteration_count as f64
+            };
+            self.colors.rank_to_color(
+                point.iteration_count,
+                stage.histogram().rank(iteration),
+                self.smooth,
+                self.max_iteration,
+            )
+        } else if self.smooth {
+            self.colors.smooth_iteration_to_color(
+                point.iteration_count,
+                point.smooth_iteration,
+                self.assignment.assignment_function(),
+                self.max_iteration,
+            )
+        } else {
+            self.colors.iteration_to_color(
+                point.iteration_count,
+                self.assignment.assignment_function(),
+                self.max_iteration,
+            )
+        };
+        match self.lighting {
+            Some(lighting) if point.distance_estimate_quality != DataQuality::Unknown => {
+                lighting.shade(color, point.surface_normal)
+            }
+            _ => color,
+        }
+    }
+
+    /// Estimates pixel data from nearby computed values.
+    ///
+    /// Uses a progressive sampling strategy to find the nearest computed
+    /// pixel and use its value as an estimate. This provides better visual
+    /// continuity during progressive computation.
+    ///
+    /// # Algorithm
+    ///
+    /// 1. Start with small sampling grid (2x2)
+    /// 2. Look for computed pixels at grid intersections
+    /// 3. Progressively increase grid size (4x4, 8x8, ...)
+    /// 4. Return first found value marked as "guessed" quality
+    ///
+    /// # Returns
+    ///
+    /// - `Some(DataPoint)` with estimated value and `Guessed` quality
+    /// - `None` if no nearby computed pixels found
+    pub fn guess_point(stage: &VizStage, x: usize, y: usize) -> Option<DataPoint> {
+        let mut modrest = 2;
+        while modrest < x || modrest < y {
+            if let Some(guesspix) = stage.get(x - (x % modrest), y - (y % modrest)) {
+                return Some(guesspix.as_guessed());
+            }
+            modrest *= 2;
+        }
+        None
+    }
+}
+
+/// Averages colors in linear RGB, alpha is averaged linearly.
+fn average_color(colors: impl Iterator<Item = [u8; 4]>) -> [u8; 4] {
+    let (mut sum, mut alpha, mut count) = (LinSrgb::new(0.0f32, 0.0, 0.0), 0u32, 0u32);
+    for color in colors {
+        sum += Srgb::new(color[0], color[1], color[2]).into_linear::<f32>();
+        alpha += color[3] as u32;
+        count += 1;
+    }
+    if count == 0 {
+        return UNKNOWN_COLOR;
+    }
+    let average: Srgb<u8> = Srgb::from_linear(sum / count as f32);
+    [
+        average.red,
+        average.green,
+        average.blue,
+        (alpha / count) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{
+        computation::comp_stage::CompStage, visualization::coloring::presets::GradientColorPreset,
+    };
+    use euclid::{Point2D, Size2D};
+
+    fn renderer(guess_policy: GuessPolicy) -> Renderer {
+        Renderer::new(
+            GradientColors::new(&GradientColorPreset::Sunrise.scheme(), 16, 0),
+            IterationAssignment::Linear,
+            false,
+            None,
+            guess_policy,
+            100,
+            Supersampling::Off,
+        )
+    }
+
+    #[test]
+    fn guess_policy_decides_about_unknown_points() {
+        let comp_stage = CompStage::new(Size2D::new(8, 8));
+        comp_stage.set(
+            4,
+            4,
+            DataPoint::computed(5, Point2D::new(3.0, 0.0), 5.0, None),
+        );
+        let stage = VizStage::new(&comp_stage, 100);
+        let computed = renderer(GuessPolicy::Neutral).pixel(&stage, 4, 4);
+        assert_ne!(UNKNOWN_COLOR, computed);
+        assert_eq!(
+            UNKNOWN_COLOR,
+            renderer(GuessPolicy::Neutral).pixel(&stage, 5, 5)
+        );
+        assert_eq!(
+            computed,
+            renderer(GuessPolicy::CoarserGrid).pixel(&stage, 5, 5)
+        );
+        let rgba = renderer(GuessPolicy::CoarserGrid).render(&stage);
+        assert_eq!(8 * 8 * 4, rgba.len());
+        assert_eq!(computed, rgba[(4 * 8 + 4) * 4..(4 * 8 + 5) * 4]);
+    }
+
+    #[test]
+    fn supersampled_colors_are_averaged() {
+        let comp_stage = CompStage::new(Size2D::new(4, 2));
+        for x in 0..4 {
+            for y in 0..2 {
+                let iteration = if x < 2 && y == 0 { 100 } else { 3 };
+                comp_stage.set(
+                    x,
+                    y,
+                    DataPoint::computed(iteration, Point2D::zero(), 3.0, None),
+                );
+            }
+        }
+        let stage = VizStage::new(&comp_stage, 100);
+        let mut supersampled = renderer(GuessPolicy::Neutral);
+        supersampled.supersampling = Supersampling::Grid2x2;
+        assert_eq!((2, 1), supersampled.output_size(&stage));
+        let rgba = supersampled.render(&stage);
+        let (body, band) = (
+            supersampled.pixel(&stage, 0, 0),
+            supersampled.pixel(&stage, 0, 1),
+        );
+        assert_eq!(band, rgba[4..8]);
+        assert_eq!(
+            average_color([body, body, band, band].into_iter()),
+            rgba[0..4]
+        );
+        assert!(rgba[0] > body[0].min(band[0]) && rgba[0] < body[0].max(band[0]));
+    }
+}
+
+// end of file
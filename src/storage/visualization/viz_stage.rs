@@ -40,11 +40,16 @@
 //! - **O(1)** random access to any pixel
 //! - **O(1)** progress ratio calculation (cached count)
 //! - **O(n)** full data iteration (optimal for rendering)
+//! - **O(log m)** histogram maintenance per update, `m` being the maximum iteration
 //! - **Minimal overhead** for event-driven updates
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use crate::storage::computation::comp_stage::CompStage;
 use crate::storage::data_point::DataPoint;
 use crate::storage::event::data_point_change_event::DataPointChange;
+use crate::storage::visualization::iteration_histogram::IterationHistogram;
 
 /// Sequential-access fractal data storage optimized for visualization operations.
 ///
@@ -90,6 +95,11 @@ pub struct VizStage {
     data: Vec<Option<DataPoint>>,
     /// Cached count of computed pixels for O(1) progress queries
     set_count: usize,
+    /// Iteration histogram for histogram coloring, for tiles that of the whole image
+    /// given to `with_histogram()`
+    histogram: Arc<IterationHistogram>,
+    /// Additional sub-pixel samples of refined edge pixels, keyed by array index
+    samples: HashMap<usize, Vec<DataPoint>>,
 }
 
 impl VizStage {
@@ -102,6 +112,7 @@ impl VizStage {
     /// # Arguments
     ///
     /// * `comp_stage` - Source computation stage to copy from
+    /// * `max_iteration` - Maximum iteration count, sizes the iteration histogram
     ///
     /// # Returns
     ///
@@ -111,7 +122,7 @@ impl VizStage {
     ///
     /// 1. **Dimension Copy**: Captures width/height from computation stage
     /// 2. **Data Snapshot**: Creates independent copy of all pixel data
-    /// 3. **Count Calculation**: Computes initial progress metrics
+    /// 3. **Count Calculation**: Computes initial progress metrics and iteration histogram
     /// 4. **Independence**: Result is fully independent of source stage
     ///
     /// # Performance
@@ -124,8 +135,38 @@ impl VizStage {
     ///
     /// Typically called once during VizStorage initialization to establish
     /// the visualization baseline before event-driven updates begin.
-    pub fn new(comp_stage: &CompStage) -> Self {
+    pub fn new(comp_stage: &CompStage, max_iteration: u32) -> Self {
         let data = comp_stage.get_full_data();
+        let mut histogram = IterationHistogram::new(max_iteration);
+        data.iter()
+            .flatten()
+            .for_each(|p| histogram.add(p.iteration_count));
+        Self::with_data(comp_stage, data, Arc::new(histogram))
+    }
+
+    /// Creates a new visualization stage from a computation stage with a given histogram.
+    ///
+    /// Used when the computation stage is only one tile of a larger image: the histogram
+    /// of the complete image replaces that of the tile, so that histogram coloring ranks
+    /// the points among all points of the image and the tiles fit together seamlessly.
+    ///
+    /// # Arguments
+    ///
+    /// * `comp_stage` - Source computation stage to copy from
+    /// * `histogram` - Iteration histogram to use for the stage, shared by all tiles and
+    ///   only copied if the stage is updated later
+    pub fn with_histogram(comp_stage: &CompStage, histogram: Arc<IterationHistogram>) -> Self {
+        Self::with_data(comp_stage, comp_stage.get_full_data(), histogram)
+    }
+
+    /// Creates a new visualization stage from a snapshot of the computation stage's data.
+    ///
+    /// The snapshot is taken by the caller, so that a histogram built from it matches the data.
+    fn with_data(
+        comp_stage: &CompStage,
+        data: Vec<Option<DataPoint>>,
+        histogram: Arc<IterationHistogram>,
+    ) -> Self {
         let set_count = data
             .iter()
             .filter(|p| p.is_some_and(|q| q.iteration_count_quality.is_accurate()))
@@ -135,6 +176,8 @@ impl VizStage {
             height: comp_stage.height(),
             data,
             set_count,
+            histogram,
+            samples: comp_stage.get_full_samples(),
         }
     }
     /// Returns the stage width in pixels.
@@ -255,6 +298,13 @@ impl VizStage {
         }
         y * self.width + x
     }
+    /// Returns the histogram of the iteration counts of all points in the stage.
+    ///
+    /// The histogram is updated with every `set()`, so it always reflects
+    /// the current stage content without being rebuilt.
+    pub fn histogram(&self) -> &IterationHistogram {
+        &self.histogram
+    }
     /// Retrieves fractal data for a specific pixel.
     ///
     /// Provides read-only access to the computed fractal data at the
@@ -290,6 +340,19 @@ impl VizStage {
     pub fn get(&self, x: usize, y: usize) -> Option<&DataPoint> {
         self.data[self.index(x, y)].as_ref()
     }
+    /// Returns the additional sub-pixel samples of a pixel.
+    ///
+    /// Samples are only present for edge pixels refined by adaptive anti-aliasing,
+    /// they do not contribute to progress tracking or the iteration histogram.
+    ///
+    /// # Returns
+    ///
+    /// The samples in the order they have been added, empty if the pixel is not refined
+    pub fn samples(&self, x: usize, y: usize) -> &[DataPoint] {
+        self.samples
+            .get(&self.index(x, y))
+            .map_or(&[], |samples| samples.as_slice())
+    }
     /// Updates fractal data for a specific pixel.
     ///
     /// Stores computed fractal data at the specified coordinates and
@@ -306,11 +369,12 @@ impl VizStage {
     ///
     /// - **New Data**: Increments `set_count` if pixel was previously uncomputed
     /// - **Update**: Overwrites existing data without changing count
+    /// - **Histogram**: Replaces the old iteration count by the new one in the histogram
     /// - **Progress Tracking**: Automatically maintains completion metrics
     ///
     /// # Performance
     ///
-    /// O(1) operation with minimal overhead for count maintenance.
+    /// O(log m) operation for the histogram maintenance, `m` being the maximum iteration.
     ///
     /// # Panics
     ///
@@ -325,12 +389,19 @@ impl VizStage {
         if self.data[index].is_none_or(|p| !p.iteration_count_quality.is_accurate()) {
             self.set_count += 1
         }
+        let histogram = Arc::make_mut(&mut self.histogram);
+        if let Some(old) = self.data[index] {
+            histogram.remove(old.iteration_count);
+        }
+        histogram.add(data_point.iteration_count);
         self.data[index] = Some(data_point);
     }
     /// Applies a pixel change event to update visualization data.
     ///
     /// Convenience method that extracts coordinates and data from a
     /// `DataPointChange` event and applies it to the visualization stage.
+    /// A run event sets all pixels of the run to the same data,
+    /// a sample event adds a sub-pixel sample to its pixel.
     /// This is the primary interface for event-driven updates.
     ///
     /// # Arguments
@@ -345,7 +416,8 @@ impl VizStage {
     ///
     /// # Performance
     ///
-    /// O(1) operation equivalent to direct `set()` call.
+    /// Equivalent to a direct `set()` call for single points,
+    /// O(n) for runs of n pixels.
     ///
     /// # Usage
     ///
@@ -359,11 +431,29 @@ impl VizStage {
     /// }
     /// ```
     pub fn set_from_change(&mut self, data_point_change: DataPointChange) {
-        self.set(
-            data_point_change.x as usize,
-            data_point_change.y as usize,
-            data_point_change.data,
-        );
+        match data_point_change {
+            DataPointChange::Point { x, y, data } => self.set(x as usize, y as usize, data),
+            DataPointChange::Run {
+                x_start,
+                x_end,
+                y,
+                data,
+            } => {
+                for x in x_start..x_end {
+                    self.set(x as usize, y as usize, data);
+                }
+            }
+            DataPointChange::Sample { x, y, sample, data } => {
+                // Samples of a pixel arrive in order, one not stored yet is the next one.
+                // A sample which is already part of the snapshot is replaced, not added again.
+                let index = self.index(x as usize, y as usize);
+                let pixel_samples = self.samples.entry(index).or_default();
+                match pixel_samples.get_mut(sample as usize) {
+                    Some(stored) => *stored = data,
+                    None => pixel_samples.push(data),
+                }
+            }
+        }
     }
 }
 
@@ -47,11 +47,11 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::Receiver;
 use tokio::sync::mpsc::error::TryRecvError;
 
 use super::viz_stage::VizStage;
-use crate::storage::computation::comp_storage::CompStorage;
+use crate::storage::computation::comp_storage::{CompStorage, SubscriberId};
 use crate::storage::event::stage_event_batcher::StageEvent;
 use crate::storage::image_comp_properties::{ImageCompProperties, StageState};
 
@@ -103,8 +103,10 @@ pub struct VizStorage {
     pub seen_state: StageState,
     /// Reference to source computation storage for lifecycle management
     comp_storage: Arc<CompStorage>,
-    /// Async event receiver for computation updates, None after completion
-    event_receiver: Option<UnboundedReceiver<StageEvent>>,
+    /// Subscription to the event system of the computation storage
+    subscriber_id: SubscriberId,
+    /// Async event receiver for computation updates
+    event_receiver: Option<Receiver<StageEvent>>,
 }
 
 impl VizStorage {
@@ -135,24 +137,51 @@ impl VizStorage {
     /// The initialization process ensures no data is lost during setup by
     /// establishing event synchronization before copying data.
     pub fn new(arc_of_comp_storage: &Arc<CompStorage>) -> VizStorage {
-        // Step 1: Establish event synchronization to prevent missing updates
         // Configure batching: up to 1000 events per batch, maximum 50ms delay
-        let event_receiver_result = arc_of_comp_storage
-            .get_event_receiver(1000, Duration::from_millis(50))
-            .ok();
+        Self::with_batching(arc_of_comp_storage, 1000, Duration::from_millis(50))
+    }
+
+    /// Creates a new visualization storage with its own batching parameters.
+    ///
+    /// Several visualization storages can follow the same computation storage,
+    /// e.g. a main canvas with short batching intervals and a minimap or histogram
+    /// panel with longer ones. Each one subscribes separately, a storage created
+    /// while the computation is running starts from a consistent snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `arc_of_comp_storage` - Shared reference to the computation storage
+    /// * `max_capacity` - Maximum events per batch
+    /// * `max_interval` - Maximum time between batches
+    ///
+    /// # Returns
+    ///
+    /// New `VizStorage` instance synchronized with the computation storage
+    pub fn with_batching(
+        arc_of_comp_storage: &Arc<CompStorage>,
+        max_capacity: usize,
+        max_interval: Duration,
+    ) -> VizStorage {
+        // Step 1: Establish event synchronization to prevent missing updates
+        let (subscriber_id, event_receiver) =
+            arc_of_comp_storage.get_event_receiver(max_capacity, max_interval);
 
         // Step 2: Capture current computation state for progress tracking
         let seen_state = arc_of_comp_storage.stage.get_state();
 
         // Step 3: Create visualization stage with initial data snapshot
         // This reads all current computation data into visualization-optimized format
-        let stage = VizStage::new(&arc_of_comp_storage.as_ref().stage);
+        let stage = VizStage::new(
+            &arc_of_comp_storage.as_ref().stage,
+            arc_of_comp_storage.properties.max_iteration,
+        );
         VizStorage {
             properties: arc_of_comp_storage.properties.clone(),
             stage,
             seen_state,
             comp_storage: arc_of_comp_storage.clone(),
-            event_receiver: event_receiver_result,
+            subscriber_id,
+            event_receiver: Some(event_receiver),
         }
     }
 
@@ -170,9 +199,11 @@ impl VizStorage {
     ///
     /// # Event Types Handled
     ///
-    /// - **ContentChange**: Single pixel update from computation
+    /// - **ContentChange**: Single pixel or horizontal run update from computation
     /// - **ContentMultiChange**: Batch of pixel updates for efficiency
     /// - **StateChange**: Computation state transitions (evolving/completed/stalled)
+    /// - **InitialState**: State at subscription, does not end the event processing
+    /// - **Resync**: Updates have been dropped, rebuild the stage from the computation storage
     ///
     /// # Performance
     ///
@@ -203,7 +234,7 @@ impl VizStorage {
                     Ok(event) => {
                         events_handled = EventProcessResult::UpdateAndContinue;
                         match event {
-                            // Single pixel update: Apply directly to visualization stage
+                            // Single pixel or run update: Apply directly to visualization stage
                             StageEvent::ContentChange(change) => {
                                 self.stage.set_from_change(change);
                             }
@@ -214,13 +245,25 @@ impl VizStorage {
                                     .iter()
                                     .for_each(|change| self.stage.set_from_change(*change));
                             }
+                            // Dropped updates: Take a new snapshot of all computation data
+                            StageEvent::Resync => {
+                                self.stage = VizStage::new(
+                                    &self.comp_storage.stage,
+                                    self.properties.max_iteration,
+                                );
+                            }
                             // Computation state change: Handle lifecycle management
+                            // State at subscription: The computation may (re-)start later
+                            StageEvent::InitialState(thestate) => {
+                                self.seen_state = thestate;
+                            }
                             StageEvent::StateChange(thestate) => {
                                 // Clean up event system when computation ends
                                 if thestate == StageState::Stalled
                                     || thestate == StageState::Completed
                                 {
-                                    let _ = self.comp_storage.drop_event_receiver();
+                                    let _ =
+                                        self.comp_storage.drop_event_receiver(self.subscriber_id);
                                 }
                             }
                         }
@@ -247,4 +290,11 @@ impl VizStorage {
     }
 }
 
+impl Drop for VizStorage {
+    /// Ends the subscription, so that the computation stops sending events to it.
+    fn drop(&mut self) {
+        let _ = self.comp_storage.drop_event_receiver(self.subscriber_id);
+    }
+}
+
 // end of file